@@ -1,9 +1,18 @@
 #![allow(clippy::pedantic, clippy::restriction, clippy::cargo, missing_docs)]
 
 use crate::helper::{create_options, print_details, MyFruit};
-use bson::doc;
+use bson::{doc, Document};
+use futures::TryStreamExt;
+use mongodb::options::{Collation, CollationStrength, FindOptions};
 use mongodb::Client;
-use mongodb_cursor_pagination::{FindResult, Pagination};
+use mongodb_cursor_pagination::{
+    merge_pages, parse_id, AdaptiveLimit, Checkpoint, CompositeCursor, Connection, ConnectionArgs,
+    Count, CursorCodec, CursorError, CursorOptions, DirectedCursor, Edge, FindResult,
+    FindResultProjector, HasPageStrategy, InsertPosition, LruPageCache, MissingLimit, PageLinks,
+    Pagination, PaginationConfig, Paginator, SkipPolicy,
+};
+use std::collections::HashSet;
+use std::time::Duration;
 
 mod helper;
 
@@ -41,11 +50,34 @@ async fn main() {
         find_results.items,
         vec![MyFruit::new("Apple", 5), MyFruit::new("Bananas", 8),]
     );
+    assert!(find_results.page_info.is_first_page());
+    assert!(find_results.page_info.previous_request().is_none());
     print_details("First page", &find_results);
+    let links = PageLinks::new(
+        "https://example.com/fruits?limit=2&before=old#top",
+        &find_results.page_info,
+    )
+    .expect("Unable to build links");
+    let end_cursor = find_results.page_info.end_cursor.as_ref().expect("No end");
+    assert_eq!(
+        links.next,
+        Some(format!(
+            "https://example.com/fruits?limit=2&after={}#top",
+            end_cursor.inner()
+        ))
+    );
+    assert_eq!(links.prev, None);
+    assert_eq!(
+        links.link_header(),
+        links
+            .next
+            .as_ref()
+            .map(|next| format!("<{next}>; rel=\"next\""))
+    );
 
     // get the second page
     options = create_options(2, 0, doc! { "name": 1 });
-    let mut cursor = find_results.page_info.end_cursor;
+    let mut cursor = find_results.page_info.next_request();
     find_results = fruits
         .find_paginated(None, Some(options), cursor)
         .await
@@ -56,6 +88,37 @@ async fn main() {
     );
     print_details("Second page", &find_results);
 
+    // place new items without fetching the page again
+    assert_eq!(
+        find_results
+            .insert_position(&MyFruit::new("Cherry", 1))
+            .expect("Unable to compare"),
+        InsertPosition::Within(1)
+    );
+    assert_eq!(
+        find_results
+            .insert_position(&MyFruit::new("Avocado", 1))
+            .expect("Unable to compare"),
+        InsertPosition::Before
+    );
+    assert!(!find_results
+        .would_contain(&MyFruit::new("Kiwi", 1))
+        .expect("Unable to compare"));
+
+    // the items before the last one of the second page
+    let before_end: FindResult<MyFruit> = fruits
+        .find_paginated(
+            None,
+            Some(create_options(2, 0, doc! { "name": 1 })),
+            find_results.page_info.end_cursor_backward.clone(),
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        before_end.items,
+        vec![MyFruit::new("Bananas", 8), MyFruit::new("Blueberry", 25),]
+    );
+
     // get previous page
     options = create_options(2, 0, doc! { "name": 1 });
     cursor = find_results.page_info.start_cursor;
@@ -110,6 +173,1118 @@ async fn main() {
         &find_results,
     );
 
+    // collect every page, but never more than the cap
+    options = create_options(2, 0, doc! { "name": 1 });
+    let all: Vec<MyFruit> = fruits
+        .collect_all(None, Some(options.clone()), 5)
+        .await
+        .expect("Unable to collect data");
+    assert_eq!(all.len(), 5);
+    let capped = fruits.collect_all::<MyFruit>(None, Some(options), 4).await;
+    assert!(matches!(capped, Err(CursorError::TooManyItems(4))));
+
+    // an export resumes from the checkpoint it stored, e.g. after a restart
+    let export_options = create_options(2, 0, doc! { "name": 1 });
+    let mut stored = Checkpoint::new(None, Some(&export_options)).to_string();
+    let mut exported: Vec<MyFruit> = Vec::new();
+    loop {
+        let checkpoint: Checkpoint = stored.parse().expect("Invalid checkpoint");
+        if checkpoint.is_finished() {
+            assert_eq!((checkpoint.items(), checkpoint.pages()), (5, 3));
+            break;
+        }
+        let page: FindResult<MyFruit> = fruits
+            .resume_from(None, Some(export_options.clone()), &checkpoint)
+            .await
+            .expect("Unable to find data");
+        exported.extend(page.items.iter().cloned());
+        stored = checkpoint.advance(&page).to_string();
+    }
+    assert_eq!(exported.len(), 5);
+    let other_filter = fruits
+        .resume_from::<MyFruit>(
+            Some(doc! { "how_many": 5 }),
+            Some(export_options),
+            &stored.parse().expect("Invalid checkpoint"),
+        )
+        .await;
+    assert!(matches!(
+        other_filter,
+        Err(CursorError::CheckpointMismatch("filter"))
+    ));
+
+    // disjoint ranges scanned in parallel, together in sort order
+    let partitions = fruits
+        .partition_scan::<MyFruit>(None, Some(create_options(2, 0, doc! { "name": 1 })), 2)
+        .await
+        .expect("Unable to partition");
+    assert_eq!(partitions.len(), 2);
+    let scanned: Vec<Vec<FindResult<MyFruit>>> =
+        futures::future::try_join_all(partitions.into_iter().map(TryStreamExt::try_collect))
+            .await
+            .expect("Unable to find data");
+    let scanned: Vec<MyFruit> = scanned
+        .into_iter()
+        .flatten()
+        .flat_map(|page| page.items)
+        .collect();
+    assert_eq!(
+        scanned,
+        vec![
+            MyFruit::new("Apple", 5),
+            MyFruit::new("Bananas", 8),
+            MyFruit::new("Blueberry", 25),
+            MyFruit::new("Grapes", 12),
+            MyFruit::new("Orange", 3),
+        ]
+    );
+
+    // names are unique, so no `_id` tiebreak is needed
+    let by_name = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().unique_sort(true),
+    );
+    let first_page: FindResult<MyFruit> = by_name
+        .find_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    let end_cursor = first_page.page_info.next_request().expect("No next page");
+    assert_eq!(end_cursor.inner().keys().collect::<Vec<_>>(), vec!["name"]);
+    assert!(by_name
+        .validate_cursor(Some(create_options(2, 0, doc! { "name": 1 })), &end_cursor)
+        .is_ok());
+    assert!(matches!(
+        by_name.validate_cursor(Some(create_options(2, 0, doc! { "how_many": 1 })), &end_cursor),
+        Err(CursorError::MissingSortKey(key)) if key == "how_many"
+    ));
+    let second_page: FindResult<MyFruit> = by_name
+        .find_paginated(
+            None,
+            Some(create_options(2, 0, doc! { "name": 1 })),
+            Some(end_cursor),
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        second_page.items,
+        vec![MyFruit::new("Blueberry", 25), MyFruit::new("Grapes", 12),]
+    );
+
+    // the etag of a page changes with its items only
+    let tagged = Paginator::new(fruits.clone(), PaginationConfig::default().etags(true));
+    let etag = |page: FindResult<MyFruit>| page.etag.expect("No etag");
+    let options = create_options(2, 0, doc! { "name": 1 });
+    let first_etag = etag(
+        tagged
+            .find_paginated(None, Some(options.clone()), None)
+            .await
+            .expect("Unable to find data"),
+    );
+    let same_etag = etag(
+        tagged
+            .find_paginated(None, Some(options.clone()), None)
+            .await
+            .expect("Unable to find data"),
+    );
+    assert_eq!(first_etag, same_etag);
+    db.collection::<Document>("myfruits")
+        .update_one(
+            doc! { "name": "Apple" },
+            doc! { "$set": { "how_many": 6 } },
+            None,
+        )
+        .await
+        .expect("Unable to update data");
+    let changed_etag = etag(
+        tagged
+            .find_paginated(None, Some(options), None)
+            .await
+            .expect("Unable to find data"),
+    );
+    assert_ne!(first_etag, changed_etag);
+    db.collection::<Document>("myfruits")
+        .update_one(
+            doc! { "name": "Apple" },
+            doc! { "$set": { "how_many": 5 } },
+            None,
+        )
+        .await
+        .expect("Unable to update data");
+
+    // in insertion order
+    let first_page: FindResult<MyFruit> = fruits
+        .find_paginated(
+            None,
+            Some(create_options(2, 0, doc! { "$natural": 1 })),
+            None,
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        first_page.items,
+        vec![MyFruit::new("Apple", 5), MyFruit::new("Orange", 3),]
+    );
+    let second_page: FindResult<MyFruit> = fruits
+        .find_paginated(
+            None,
+            Some(create_options(2, 0, doc! { "$natural": 1 })),
+            first_page.page_info.next_request(),
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        second_page.items,
+        vec![MyFruit::new("Blueberry", 25), MyFruit::new("Bananas", 8),]
+    );
+
+    // the projection is extended by the sort keys needed for the cursors
+    let mut projected = create_options(2, 0, doc! { "name": 1 });
+    projected.projection = Some(doc! { "name": 1, "how_many": 1, "_id": 0 });
+    let first_page: FindResult<bson::Document> = fruits
+        .find_paginated(None, Some(projected.clone()), None)
+        .await
+        .expect("Unable to find data");
+    assert!(first_page
+        .items
+        .iter()
+        .all(|item| !item.contains_key("_id")));
+    let end_cursor = first_page.page_info.next_request().expect("No next page");
+    assert!(end_cursor.inner().contains_key("_id"));
+    let second_page: FindResult<MyFruit> = fruits
+        .find_paginated(None, Some(projected), Some(end_cursor))
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        second_page.items,
+        vec![MyFruit::new("Blueberry", 25), MyFruit::new("Grapes", 12),]
+    );
+
+    // large pages can be fetched in chunks, each continuing after the previous one
+    let chunked: FindResult<MyFruit> = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default()
+            .chunk_size(2)
+            .has_page_strategy(HasPageStrategy::OverFetch),
+    )
+    .find_paginated(None, Some(create_options(3, 1, doc! { "name": 1 })), None)
+    .await
+    .expect("Unable to find data");
+    let unchunked: FindResult<MyFruit> = fruits
+        .find_paginated(None, Some(create_options(3, 1, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(chunked.items, unchunked.items);
+    assert_eq!(chunked.page_info.has_next_page, Some(true));
+
+    // only the fields of MyFruit and the sort keys are fetched
+    let minimal: FindResult<MyFruit> = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().minimize_projection(true),
+    )
+    .find_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })), None)
+    .await
+    .expect("Unable to find data");
+    assert_eq!(
+        minimal.items,
+        vec![MyFruit::new("Apple", 5), MyFruit::new("Bananas", 8),]
+    );
+    assert!(minimal.edges.iter().all(|edge| edge.contains_key("_id")));
+
+    // the page flags can come from fetching one item more instead of extra queries
+    let overfetching = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().has_page_strategy(HasPageStrategy::OverFetch),
+    );
+    let first_overfetched: FindResult<MyFruit> = overfetching
+        .find_paginated(None, Some(create_options(3, 0, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(first_overfetched.items.len(), 3);
+    assert_eq!(first_overfetched.page_info.has_next_page, Some(true));
+    assert_eq!(first_overfetched.page_info.has_previous_page, Some(false));
+    let last_overfetched: FindResult<MyFruit> = overfetching
+        .find_paginated(
+            None,
+            Some(create_options(3, 0, doc! { "name": 1 })),
+            first_overfetched.page_info.next_request(),
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(last_overfetched.items.len(), 2);
+    assert_eq!(last_overfetched.page_info.has_next_page, Some(false));
+    assert_eq!(last_overfetched.page_info.has_previous_page, None);
+    let effective = &last_overfetched.effective_options;
+    assert_eq!(effective.limit, Some(4));
+    assert_eq!(effective.sort, doc! { "name": 1, "_id": -1 });
+    assert!(effective.filter.contains_key("$or"));
+    let planned = overfetching
+        .plan(
+            None,
+            Some(create_options(3, 0, doc! { "name": 1 })),
+            first_overfetched.page_info.next_request(),
+        )
+        .expect("Unable to plan query");
+    assert_eq!(&planned.effective_options, effective);
+    assert!(matches!(
+        fruits.plan(None, Some(create_options(-1, 0, doc! { "name": 1 })), None),
+        Err(CursorError::NegativeLimit(-1))
+    ));
+
+    // the options the cursors are built from end with the `_id` tiebreak
+    let cursor_options = CursorOptions::builder()
+        .options(create_options(2, 0, doc! { "name": 1 }))
+        .build();
+    assert_eq!(cursor_options.sort(), Some(&doc! { "name": 1, "_id": -1 }));
+    let first_item = doc! { "name": "Apple", "_id": minimal.edges[0].get("_id").cloned() };
+    assert_eq!(Edge::new(&first_item, &cursor_options), minimal.edges[0]);
+
+    // only the fields a client selected are fetched
+    let projector = FindResultProjector::new(["name", "__typename"]);
+    let selected: FindResult<Document> = fruits
+        .clone_with_type::<Document>()
+        .find_paginated(
+            None,
+            Some(
+                projector
+                    .project(Some(create_options(2, 0, doc! { "how_many": 1 })))
+                    .expect("Sort keys are selected"),
+            ),
+            None,
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        selected.items,
+        vec![doc! { "name": "Orange" }, doc! { "name": "Apple" }]
+    );
+    assert!(matches!(
+        FindResultProjector::new(["name.first"]).project(Some(create_options(
+            2,
+            0,
+            doc! { "name": 1 }
+        ))),
+        Err(CursorError::ProjectionExcludesSortKey(_))
+    ));
+
+    // a skip only applies to the first page of a walk
+    let skipped: Vec<MyFruit> = fruits
+        .collect_all(None, Some(create_options(2, 1, doc! { "name": 1 })), 5)
+        .await
+        .expect("Unable to collect data");
+    assert_eq!(skipped.len(), 4);
+
+    // a skip combined with a cursor is rejected unless it should skip after the cursor
+    let first_page: FindResult<MyFruit> = fruits
+        .find_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    let rejected = fruits
+        .find_paginated::<MyFruit>(
+            None,
+            Some(create_options(2, 1, doc! { "name": 1 })),
+            first_page.page_info.next_request(),
+        )
+        .await;
+    assert!(matches!(rejected, Err(CursorError::SkipWithCursor(1))));
+    let negative = fruits
+        .find_paginated::<MyFruit>(None, Some(create_options(-2, 0, doc! { "name": 1 })), None)
+        .await;
+    assert!(matches!(negative, Err(CursorError::NegativeLimit(-2))));
+    let jumped: FindResult<MyFruit> = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().skip_policy(SkipPolicy::AfterCursor),
+    )
+    .find_paginated(
+        None,
+        Some(create_options(2, 1, doc! { "name": 1 })),
+        first_page.page_info.next_request(),
+    )
+    .await
+    .expect("Unable to find data");
+    assert_eq!(
+        jumped.items,
+        vec![MyFruit::new("Grapes", 12), MyFruit::new("Orange", 3),]
+    );
+    let backwards = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().skip_policy(SkipPolicy::AfterCursor),
+    )
+    .find_paginated::<MyFruit>(
+        None,
+        Some(create_options(2, 1, doc! { "name": 1 })),
+        jumped.page_info.previous_request(),
+    )
+    .await;
+    assert!(matches!(backwards, Err(CursorError::InvalidCombination(_))));
+
+    // a page without a limit follows the configured policy
+    let unlimited_options = FindOptions::builder().sort(doc! { "name": 1 }).build();
+    let defaulted: FindResult<MyFruit> = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().missing_limit(MissingLimit::DefaultTo(2)),
+    )
+    .find_paginated(None, Some(unlimited_options.clone()), None)
+    .await
+    .expect("Unable to find data");
+    assert_eq!(defaulted.items.len(), 2);
+    assert_eq!(defaulted.page_info.has_next_page, Some(true));
+    let required = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().missing_limit(MissingLimit::Reject),
+    )
+    .find_paginated::<MyFruit>(None, Some(unlimited_options.clone()), None)
+    .await;
+    assert!(matches!(required, Err(CursorError::MissingLimit)));
+    let bounded = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().missing_limit(MissingLimit::UpTo(4)),
+    )
+    .find_paginated::<MyFruit>(None, Some(unlimited_options), None)
+    .await;
+    assert!(matches!(bounded, Err(CursorError::TooManyItems(4))));
+
+    // the limit shrinks to the byte budget, but never below one item
+    let adaptive = AdaptiveLimit::new(1);
+    let tiny: FindResult<MyFruit> = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().adaptive_limit(adaptive.clone()),
+    )
+    .find_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })), None)
+    .await
+    .expect("Unable to find data");
+    assert_eq!(tiny.items, vec![MyFruit::new("Apple", 5)]);
+    assert!(adaptive.average_item_size().is_some());
+
+    // a page ends early at the byte budget and continues after the last item kept
+    let budgeted = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().max_page_bytes(1),
+    );
+    let first_page: FindResult<MyFruit> = budgeted
+        .find_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(first_page.items, vec![MyFruit::new("Apple", 5)]);
+    assert_eq!(first_page.page_info.has_next_page, Some(true));
+    let second_page: FindResult<MyFruit> = budgeted
+        .find_paginated(
+            None,
+            Some(create_options(2, 0, doc! { "name": 1 })),
+            first_page.page_info.next_request(),
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(second_page.items, vec![MyFruit::new("Bananas", 8)]);
+
+    // a find needing more driver batches than allowed is stopped
+    let mut small_batches = create_options(3, 0, doc! { "name": 1 });
+    small_batches.batch_size = Some(1);
+    let capped_batches = Paginator::new(fruits.clone(), PaginationConfig::default().max_batches(2))
+        .find_paginated::<MyFruit>(None, Some(small_batches), None)
+        .await;
+    assert!(matches!(
+        capped_batches,
+        Err(CursorError::TooManyBatches(2))
+    ));
+
+    // batch jobs can keep the cursor of a page open between its batches
+    let long_running = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().no_cursor_timeout(true),
+    )
+    .find_paginated::<MyFruit>(None, Some(create_options(3, 0, doc! { "name": 1 })), None)
+    .await
+    .expect("Unable to find data");
+    assert_eq!(long_running.items.len(), 3);
+
+    // max_await_time has no effect without a tailable await cursor
+    let mut awaiting = create_options(3, 0, doc! { "name": 1 });
+    awaiting.max_await_time = Some(Duration::from_secs(1));
+    let awaited = fruits
+        .find_paginated::<MyFruit>(None, Some(awaiting), None)
+        .await;
+    assert!(matches!(
+        awaited,
+        Err(CursorError::MaxAwaitTimeWithoutAwait)
+    ));
+
+    // an item moved past the cursor by an update is seen again
+    let drifting = Paginator::new(fruits.clone(), PaginationConfig::default().detect_drift(10));
+    let mut drift_cursor = None;
+    let mut drift_pages = Vec::new();
+    loop {
+        let page: FindResult<MyFruit> = drifting
+            .find_paginated(
+                None,
+                Some(create_options(2, 0, doc! { "how_many": 1 })),
+                drift_cursor,
+            )
+            .await
+            .expect("Unable to find data");
+        if drift_pages.is_empty() {
+            assert_eq!(page.items[0], MyFruit::new("Orange", 3));
+            fruits
+                .update_one(
+                    doc! { "name": "Orange" },
+                    doc! { "$set": { "how_many": 30 } },
+                    None,
+                )
+                .await
+                .expect("Unable to update data");
+        }
+        drift_pages.push(page.drift_detected);
+        drift_cursor = page.page_info.next_request();
+        if drift_cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(drift_pages, vec![false, false, true]);
+    fruits
+        .update_one(
+            doc! { "name": "Orange" },
+            doc! { "$set": { "how_many": 3 } },
+            None,
+        )
+        .await
+        .expect("Unable to update data");
+
+    // walks leave out the items inserted after their first page
+    let tolerant = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().insertion_key("_id"),
+    );
+    let before_insert: FindResult<MyFruit> = tolerant
+        .find_paginated(None, Some(create_options(3, 0, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    let inserted = db
+        .collection::<Document>("myfruits")
+        .insert_one(doc! { "name": "Zucchini", "how_many": 1 }, None)
+        .await
+        .expect("Unable to insert data");
+    let after_insert: FindResult<MyFruit> = tolerant
+        .find_paginated(
+            None,
+            Some(create_options(3, 0, doc! { "name": 1 })),
+            before_insert.page_info.next_request(),
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        after_insert.items,
+        vec![MyFruit::new("Grapes", 12), MyFruit::new("Orange", 3)]
+    );
+    assert!(after_insert.edges[0].insertion_bound().is_some());
+    db.collection::<Document>("myfruits")
+        .delete_one(doc! { "_id": inserted.inserted_id }, None)
+        .await
+        .expect("Unable to delete data");
+
+    // pages of disjoint sources merge into pages in sort order
+    let sources = [
+        doc! { "how_many": { "$lt": 10 } },
+        doc! { "how_many": { "$gte": 10 } },
+    ];
+    let mut composite = CompositeCursor::default();
+    let mut merged_pages = Vec::new();
+    loop {
+        let mut pages = Vec::new();
+        for (index, filter) in sources.iter().enumerate() {
+            let page: FindResult<MyFruit> = fruits
+                .find_paginated(
+                    Some(filter.clone()),
+                    Some(create_options(2, 0, doc! { "name": 1 })),
+                    composite.source(index).cloned(),
+                )
+                .await
+                .expect("Unable to find data");
+            pages.push(page);
+        }
+        let sort = pages[0].effective_options.sort.clone();
+        let merged = merge_pages(pages, composite.cursors(), &sort, 2).expect("Unable to merge");
+        merged_pages.push(merged.items.clone());
+        if !merged.has_next_page {
+            break;
+        }
+        // one opaque cursor for the client, split back into the cursors of the sources
+        composite = merged
+            .composite_cursor()
+            .to_string()
+            .parse()
+            .expect("Unable to parse cursor");
+        assert_eq!(composite.clone().split(), merged.cursors);
+    }
+    assert_eq!(
+        merged_pages,
+        vec![
+            vec![MyFruit::new("Apple", 5), MyFruit::new("Bananas", 8)],
+            vec![MyFruit::new("Blueberry", 25), MyFruit::new("Grapes", 12)],
+            vec![MyFruit::new("Orange", 3)],
+        ]
+    );
+
+    // several pages at once, in the requested order
+    let report: Vec<FindResult<MyFruit>> = fruits
+        .find_pages_parallel(
+            None,
+            Some(create_options(2, 0, doc! { "name": 1 })),
+            &[3, 1],
+            2,
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        report
+            .into_iter()
+            .map(|page| page.items)
+            .collect::<Vec<_>>(),
+        vec![
+            vec![MyFruit::new("Orange", 3)],
+            vec![MyFruit::new("Apple", 5), MyFruit::new("Bananas", 8)],
+        ]
+    );
+    let no_page = fruits
+        .find_pages_parallel::<MyFruit>(None, None, &[0], 2)
+        .await;
+    assert!(matches!(no_page, Err(CursorError::InvalidPageNumber(0))));
+
+    // process every page in order
+    let mut processed = Vec::new();
+    fruits
+        .for_each_page(
+            None,
+            Some(create_options(2, 0, doc! { "name": 1 })),
+            |page: FindResult<MyFruit>| {
+                processed.push(page.items.len());
+                async { Ok::<_, CursorError>(()) }
+            },
+        )
+        .await
+        .expect("Unable to process pages");
+    assert_eq!(processed, vec![2, 2, 1]);
+
+    // every query of a paginator is scoped by its ambient filter
+    let restockable = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().ambient_filter(doc! { "how_many": { "$gt": 5 } }),
+    );
+    find_results = restockable
+        .find_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        find_results.items,
+        vec![MyFruit::new("Bananas", 8), MyFruit::new("Blueberry", 25),]
+    );
+    assert_eq!(find_results.total_count, Count::Exact(3));
+
+    // edges know their position among all items, pages their number
+    let numbered = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().page_numbers(true),
+    );
+    let first_page: FindResult<MyFruit> = numbered
+        .find_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    let second_page: FindResult<MyFruit> = numbered
+        .find_paginated(
+            None,
+            Some(create_options(2, 0, doc! { "name": 1 })),
+            first_page.page_info.next_request(),
+        )
+        .await
+        .expect("Unable to find data");
+    let offsets: Vec<_> = second_page.edges.iter().map(|edge| edge.offset()).collect();
+    assert_eq!(offsets, vec![Some(2), Some(3)]);
+    assert_eq!(second_page.page_info.approx_page_number, Some(2));
+    assert_eq!(second_page.page_info.approx_total_pages, Some(3));
+    let back_page: FindResult<MyFruit> = numbered
+        .find_paginated(
+            None,
+            Some(create_options(2, 0, doc! { "name": 1 })),
+            second_page.page_info.previous_request(),
+        )
+        .await
+        .expect("Unable to find data");
+    let offsets: Vec<_> = back_page.edges.iter().map(|edge| edge.offset()).collect();
+    assert_eq!(offsets, vec![Some(0), Some(1)]);
+
+    // the last two items, like `last: 2` of a GraphQL connection
+    let last_page: FindResult<MyFruit> = fruits
+        .find_connection(
+            None,
+            Some(create_options(0, 0, doc! { "name": 1 })),
+            ConnectionArgs::new(None, None, Some(2), None),
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        last_page.items,
+        vec![MyFruit::new("Grapes", 12), MyFruit::new("Orange", 3),]
+    );
+    assert!(last_page.page_info.is_last_page() && !last_page.page_info.is_first_page());
+    let relay: Connection<MyFruit> = fruits
+        .find_relay_connection(
+            None,
+            Some(create_options(0, 0, doc! { "name": 1 })),
+            ConnectionArgs::new(None, None, Some(2), None),
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        relay
+            .edges
+            .into_iter()
+            .map(|edge| edge.node)
+            .collect::<Vec<_>>(),
+        last_page.items
+    );
+    assert!(relay.page_info.has_previous_page && !relay.page_info.has_next_page);
+    let invalid = fruits
+        .find_relay_connection::<MyFruit>(
+            None,
+            None,
+            ConnectionArgs::new(Some(2), None, Some(2), None),
+        )
+        .await;
+    assert!(matches!(
+        invalid,
+        Err(CursorError::InvalidConnectionArgs(_))
+    ));
+    let found_last: FindResult<MyFruit> = fruits
+        .find_last_page(None, Some(create_options(2, 0, doc! { "name": 1 })))
+        .await
+        .expect("Unable to find data");
+    assert_eq!(found_last.items, last_page.items);
+    let found_first: FindResult<MyFruit> = fruits
+        .find_first_page(None, Some(create_options(2, 0, doc! { "name": 1 })))
+        .await
+        .expect("Unable to find data");
+    assert!(found_first.page_info.is_first_page());
+
+    // filters can be typed, they are serialized into a document
+    #[derive(serde::Serialize)]
+    struct ByName<'name> {
+        name: &'name str,
+    }
+    let typed: FindResult<MyFruit> = fruits
+        .find_paginated_with_filter(&ByName { name: "Grapes" }, None, None)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(typed.items, vec![MyFruit::new("Grapes", 12)]);
+
+    // pages can be returned in the opposite order of the sort
+    let reversed: FindResult<MyFruit> = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().reverse_results(true),
+    )
+    .find_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })), None)
+    .await
+    .expect("Unable to find data");
+    assert_eq!(
+        reversed.items,
+        vec![MyFruit::new("Bananas", 8), MyFruit::new("Apple", 5),]
+    );
+    assert_eq!(
+        reversed
+            .page_info
+            .end_cursor
+            .as_ref()
+            .map(|cursor| cursor.inner()),
+        reversed.edges.first()
+    );
+
+    // cursors can be inspected
+    let end_cursor = find_results
+        .page_info
+        .end_cursor
+        .clone()
+        .expect("No end cursor");
+    assert_eq!(
+        end_cursor
+            .inner()
+            .get_typed::<String>("name")
+            .ok()
+            .as_deref(),
+        Some("Blueberry")
+    );
+
+    // cursors are equal by their encoding, e.g. to coalesce requests for the same page
+    let decoded: Edge = end_cursor
+        .inner()
+        .to_string()
+        .parse()
+        .expect("Invalid cursor");
+    let requested: HashSet<DirectedCursor> = [
+        end_cursor.clone(),
+        DirectedCursor::Forward(decoded.clone()),
+        DirectedCursor::Backwards(decoded),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(requested.len(), 2);
+    assert!(requested.contains(&end_cursor));
+
+    // cursors of one tenant are rejected by another
+    let tenant = |scope: &str| {
+        Paginator::new(
+            fruits.clone(),
+            PaginationConfig::default().cursor_codec(CursorCodec::default().scope(scope)),
+        )
+    };
+    let encoded = tenant("a")
+        .encode_cursor(end_cursor.inner())
+        .expect("Unable to encode cursor");
+    assert_eq!(
+        tenant("a").decode_cursor(&encoded).ok().as_ref(),
+        Some(end_cursor.inner())
+    );
+    assert!(matches!(
+        tenant("b").decode_cursor(&encoded),
+        Err(CursorError::ScopeMismatch)
+    ));
+    // the pages of a tenant encode their cursors with its codec, unscoped cursors are rejected
+    let scoped: FindResult<MyFruit> = tenant("a")
+        .find_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    let scoped_end = scoped.page_info.end_cursor.expect("No end cursor");
+    assert!(tenant("a")
+        .decode_cursor(&scoped_end.inner().to_string())
+        .is_ok());
+    let unscoped = tenant("a")
+        .find_paginated::<MyFruit>(
+            None,
+            Some(create_options(2, 0, doc! { "name": 1 })),
+            end_cursor
+                .inner()
+                .to_string()
+                .parse::<Edge>()
+                .ok()
+                .map(DirectedCursor::Forward),
+        )
+        .await;
+    assert!(matches!(unscoped, Err(CursorError::ScopeMismatch)));
+
+    // signed cursors can't be forged
+    let signed = CursorCodec::default().issuer("fruits-api").sign("secret");
+    let token = signed
+        .encode(end_cursor.inner())
+        .expect("Unable to encode cursor");
+    assert_eq!(
+        signed.decode(&token).ok().as_ref(),
+        Some(end_cursor.inner())
+    );
+    assert!(matches!(
+        CursorCodec::default()
+            .issuer("fruits-api")
+            .sign("other")
+            .decode(&token),
+        Err(CursorError::InvalidSignature)
+    ));
+
+    // cursors can be capped in length
+    assert!(matches!(
+        CursorCodec::default().max_len(8).encode(end_cursor.inner()),
+        Err(CursorError::CursorTooLong { max: 8, .. })
+    ));
+
+    // update and delete in batches of two
+    let restocked = fruits
+        .update_paginated(
+            Some(doc! { "how_many": { "$gt": 5 } }),
+            Some(create_options(2, 0, doc! { "name": 1 })),
+            doc! { "$set": { "restock": true } },
+        )
+        .await
+        .expect("Unable to update data");
+    assert_eq!(restocked, 3);
+    // writing through a paginator drops the pages it cached
+    let cached = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().cache(LruPageCache::new(8)),
+    );
+    let before_delete: FindResult<MyFruit> = cached
+        .find_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(before_delete.items.len(), 2);
+    let deleted = cached
+        .delete_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })))
+        .await
+        .expect("Unable to delete data");
+    assert_eq!(deleted, 5);
+    let after_delete: FindResult<MyFruit> = cached
+        .find_paginated(None, Some(create_options(2, 0, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    assert!(after_delete.items.is_empty());
+
+    // items of another shape are collected instead of failing the page
+    let produce = db.collection::<MyFruit>("myproduce");
+    produce.drop(None).await.expect("Failed to drop table");
+    db.collection("myproduce")
+        .insert_many(
+            vec![
+                doc! { "_id": 1, "name": "Apple", "how_many": 5 },
+                doc! { "_id": 2, "name": "Carrot", "color": "orange" },
+            ],
+            None,
+        )
+        .await
+        .expect("Unable to insert data");
+    let mixed: FindResult<MyFruit> = Paginator::new(
+        produce.clone(),
+        PaginationConfig::default().collect_mismatched(true),
+    )
+    .find_paginated(None, Some(create_options(2, 0, doc! { "_id": 1 })), None)
+    .await
+    .expect("Unable to find data");
+    assert_eq!(mixed.items, vec![MyFruit::new("Apple", 5)]);
+    assert_eq!(mixed.edges.len(), 1);
+    assert_eq!(
+        mixed
+            .mismatched
+            .iter()
+            .map(|item| &item.id)
+            .collect::<Vec<_>>(),
+        vec![&bson::Bson::Int32(2)]
+    );
+    // a page left empty by them moves on to the next page
+    let skipping = Paginator::new(
+        produce.clone(),
+        PaginationConfig::default()
+            .collect_mismatched(true)
+            .skip_empty_pages(1),
+    );
+    let skipped: FindResult<MyFruit> = skipping
+        .find_paginated(None, Some(create_options(1, 0, doc! { "_id": -1 })), None)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(skipped.items, vec![MyFruit::new("Apple", 5)]);
+    assert!(skipped.page_info.is_last_page());
+    let apple_id: i32 = parse_id("1").expect("Id is an integer");
+    assert!(matches!(
+        parse_id::<bson::oid::ObjectId>("1"),
+        Err(CursorError::InvalidId { .. })
+    ));
+    let after_apple: FindResult<Document> = produce
+        .find_paginated_after_id(
+            None,
+            Some(create_options(2, 0, doc! { "_id": 1 })),
+            apple_id,
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        after_apple.items,
+        vec![doc! { "_id": 2, "name": "Carrot", "color": "orange" }]
+    );
+    produce.drop(None).await.expect("Unable to drop collection");
+
+    // the first pages of many parents are found with a single query
+    let baskets = db.collection::<MyFruit>("mybaskets");
+    baskets.drop(None).await.expect("Failed to drop table");
+    db.collection("mybaskets")
+        .insert_many(
+            vec![
+                doc! { "basket": 1, "name": "Apple", "how_many": 1 },
+                doc! { "basket": 1, "name": "Banana", "how_many": 2 },
+                doc! { "basket": 1, "name": "Cherry", "how_many": 3 },
+                doc! { "basket": 2, "name": "Date", "how_many": 4 },
+            ],
+            None,
+        )
+        .await
+        .expect("Unable to insert data");
+    let by_basket: Vec<(bson::Bson, FindResult<MyFruit>)> = baskets
+        .find_first_pages_by_parent(
+            "basket",
+            vec![2.into(), 1.into(), 3.into()],
+            None,
+            Some(create_options(2, 0, doc! { "name": 1 })),
+        )
+        .await
+        .expect("Unable to find data");
+    let items: Vec<Vec<MyFruit>> = by_basket
+        .iter()
+        .map(|(_basket, page)| page.items.clone())
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            vec![MyFruit::new("Date", 4)],
+            vec![MyFruit::new("Apple", 1), MyFruit::new("Banana", 2)],
+            vec![],
+        ]
+    );
+    assert_eq!(by_basket[1].1.page_info.has_next_page, Some(true));
+    let rest: FindResult<MyFruit> = baskets
+        .find_paginated(
+            Some(doc! { "basket": 1 }),
+            Some(create_options(2, 0, doc! { "name": 1 })),
+            by_basket[1].1.page_info.next_request(),
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(rest.items, vec![MyFruit::new("Cherry", 3)]);
+    let grouped: Vec<(bson::Bson, FindResult<MyFruit>)> = baskets
+        .find_paginated_grouped(
+            "basket",
+            None,
+            Some(create_options(2, 0, doc! { "name": 1 })),
+        )
+        .await
+        .expect("Unable to find data");
+    let items: Vec<(bson::Bson, Vec<MyFruit>)> = grouped
+        .iter()
+        .map(|(basket, page)| (basket.clone(), page.items.clone()))
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            (
+                1.into(),
+                vec![MyFruit::new("Apple", 1), MyFruit::new("Banana", 2)]
+            ),
+            (2.into(), vec![MyFruit::new("Date", 4)]),
+        ]
+    );
+    assert_eq!(grouped[0].1.page_info.has_next_page, Some(true));
+    assert_eq!(grouped[1].1.page_info.has_next_page, Some(false));
+    baskets.drop(None).await.expect("Unable to drop collection");
+
+    // the cursors record the latest change, later changes are found from there
+    let synced = Paginator::new(
+        db.collection::<MyFruit>("mysynced"),
+        PaginationConfig::default().change_key("updated_at"),
+    );
+    synced
+        .collection()
+        .drop(None)
+        .await
+        .expect("Failed to drop table");
+    db.collection("mysynced")
+        .insert_many(
+            vec![
+                doc! { "name": "Apple", "how_many": 1, "updated_at": 3 },
+                doc! { "name": "Banana", "how_many": 2, "updated_at": 1 },
+                doc! { "name": "Cherry", "how_many": 3, "updated_at": 2 },
+            ],
+            None,
+        )
+        .await
+        .expect("Unable to insert data");
+    let by_name = create_options(2, 0, doc! { "name": 1 });
+    let first_page: FindResult<MyFruit> = synced
+        .find_paginated(None, Some(by_name.clone()), None)
+        .await
+        .expect("Unable to find data");
+    let last_page: FindResult<MyFruit> = synced
+        .find_paginated(
+            None,
+            Some(by_name.clone()),
+            first_page.page_info.next_request(),
+        )
+        .await
+        .expect("Unable to find data");
+    let since = last_page.page_info.end_cursor.expect("No end cursor");
+    assert_eq!(since.inner().high_water_mark(), Some(&bson::Bson::Int32(3)));
+    db.collection::<Document>("mysynced")
+        .update_one(
+            doc! { "name": "Banana" },
+            doc! { "$set": { "how_many": 4, "updated_at": 4 } },
+            None,
+        )
+        .await
+        .expect("Unable to update data");
+    let changes: FindResult<MyFruit> = synced
+        .find_changes_since(None, Some(by_name), since.inner(), None)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(changes.items, vec![MyFruit::new("Banana", 4)]);
+    assert_eq!(
+        changes
+            .page_info
+            .end_cursor
+            .as_ref()
+            .and_then(|cursor| cursor.inner().high_water_mark()),
+        Some(&bson::Bson::Int32(4))
+    );
+    synced
+        .collection()
+        .drop(None)
+        .await
+        .expect("Unable to drop collection");
+
+    // the flags of a collated page are peeked under the same collation, binary order would put
+    // "apple" last
+    let collated = db.collection::<MyFruit>("mycollated");
+    collated.drop(None).await.expect("Failed to drop table");
+    db.collection("mycollated")
+        .insert_many(
+            vec![
+                doc! { "name": "apple", "how_many": 1 },
+                doc! { "name": "Banana", "how_many": 2 },
+                doc! { "name": "cherry", "how_many": 3 },
+            ],
+            None,
+        )
+        .await
+        .expect("Unable to insert data");
+    let mut collated_options = create_options(1, 0, doc! { "name": 1 });
+    collated_options.collation = Some(
+        Collation::builder()
+            .locale("en")
+            .strength(CollationStrength::Secondary)
+            .build(),
+    );
+    let first_collated: FindResult<MyFruit> = collated
+        .find_paginated(None, Some(collated_options.clone()), None)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(first_collated.items, vec![MyFruit::new("apple", 1)]);
+    assert_eq!(first_collated.page_info.has_next_page, Some(true));
+    let second_collated: FindResult<MyFruit> = collated
+        .find_paginated(
+            None,
+            Some(collated_options.clone()),
+            first_collated.page_info.next_request(),
+        )
+        .await
+        .expect("Unable to find data");
+    // the cursors carry the collation, they are rejected by a query in binary order
+    assert!(first_collated.edges[0].collation().is_some());
+    let uncollated: Result<FindResult<MyFruit>, _> = collated
+        .find_paginated(
+            None,
+            Some(create_options(1, 0, doc! { "name": 1 })),
+            first_collated.page_info.next_request(),
+        )
+        .await;
+    assert!(matches!(uncollated, Err(CursorError::CollationMismatch)));
+    // and cursors of a query in binary order are rejected by a collated one
+    let binary_page: FindResult<MyFruit> = collated
+        .find_paginated(None, Some(create_options(1, 0, doc! { "name": 1 })), None)
+        .await
+        .expect("Unable to find data");
+    let recollated: Result<FindResult<MyFruit>, _> = collated
+        .find_paginated(
+            None,
+            Some(collated_options),
+            binary_page.page_info.next_request(),
+        )
+        .await;
+    assert!(matches!(recollated, Err(CursorError::CollationMismatch)));
+    assert_eq!(second_collated.items, vec![MyFruit::new("Banana", 2)]);
+    assert_eq!(second_collated.page_info.has_previous_page, Some(true));
+    assert_eq!(second_collated.page_info.has_next_page, Some(true));
+    collated
+        .drop(None)
+        .await
+        .expect("Unable to drop collection");
+
     db.collection::<MyFruit>("myfruits")
         .drop(None)
         .await
@@ -30,7 +30,7 @@ pub fn create_options(limit: i64, skip: u64, sort: Document) -> FindOptions {
 
 pub fn print_details<T: Debug>(name: &str, find_results: &FindResult<T>) {
     println!(
-        "{}:\nitems: {:?}\ntotal: {}\nstart: {:?}\nend: {:?}\nhas_previous: {}\nhas_next: {}",
+        "{}:\nitems: {:?}\ntotal: {}\nstart: {:?}\nend: {:?}\nhas_previous: {:?}\nhas_next: {:?}",
         name,
         find_results.items,
         find_results.total_count,
@@ -0,0 +1,69 @@
+#![allow(clippy::pedantic, clippy::restriction, clippy::cargo, missing_docs)]
+
+use crate::helper::{create_options, print_details, MyFruit};
+use bson::doc;
+use mongodb::Client;
+use mongodb_cursor_pagination::{assert_invariants, FindResult, Pagination};
+
+mod helper;
+
+#[tokio::main]
+async fn main() {
+    let client = Client::with_uri_str("mongodb://localhost:27017/")
+        .await
+        .expect("Failed to initialize client.");
+    let db = client.database("mongodb_cursor_pagination");
+    let fruits = db.collection::<MyFruit>("myfruits");
+
+    // Ensure there is no collection myfruits
+    fruits.drop(None).await.expect("Failed to drop table");
+
+    let docs = vec![
+        doc! { "name": "Apple", "how_many": 5 },
+        doc! { "name": "Avocado", "how_many": 5 },
+        doc! { "name": "Orange", "how_many": 3 },
+        doc! { "name": "Blueberry", "how_many": 10 },
+        doc! { "name": "Bananas", "how_many": 10 },
+        doc! { "name": "Blackberry", "how_many": 12 },
+        doc! { "name": "Grapes", "how_many": 12 },
+    ];
+
+    db.collection("myfruits")
+        .insert_many(docs, None)
+        .await
+        .expect("Unable to insert data");
+
+    let options = create_options(3, 0, doc! { "how_many": 1, "name": -1 });
+    let find_results: FindResult<MyFruit> = fruits
+        .find_paginated(None, Some(options), None)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        find_results.items,
+        vec![
+            MyFruit::new("Orange", 3),
+            MyFruit::new("Avocado", 5),
+            MyFruit::new("Apple", 5)
+        ]
+    );
+    print_details("First page", &find_results);
+
+    for limit in 1..=8 {
+        assert_invariants(&fruits, None, doc! { "name": 1 }, limit).await;
+        assert_invariants(&fruits, None, doc! { "how_many": 1, "name": -1 }, limit).await;
+        assert_invariants(&fruits, None, doc! { "how_many": -1 }, limit).await;
+        assert_invariants(
+            &fruits,
+            Some(doc! { "how_many": { "$gt": 4 } }),
+            doc! { "how_many": 1 },
+            limit,
+        )
+        .await;
+    }
+    println!("All invariants hold");
+
+    db.collection::<MyFruit>("myfruits")
+        .drop(None)
+        .await
+        .expect("Unable to drop collection");
+}
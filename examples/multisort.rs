@@ -1,8 +1,13 @@
 #![allow(clippy::pedantic, clippy::restriction, clippy::cargo, missing_docs)]
+use std::cmp::Ordering;
+
 use crate::helper::{create_options, print_details, MyFruit};
 use bson::{doc, Document};
 use mongodb::Client;
-use mongodb_cursor_pagination::{FindResult, Pagination};
+use mongodb_cursor_pagination::{
+    sort, BackwardStart, CursorError, FindResult, Pagination, PaginationConfig, Paginator, Sort,
+    SortAudit,
+};
 
 mod helper;
 
@@ -57,6 +62,18 @@ async fn main() {
     );
     print_details("First page", &find_results);
 
+    // edges compare like their items under the sort
+    let sort = &find_results.effective_options.sort;
+    assert!(find_results.edges[0]
+        .precedes(&find_results.edges[1], sort)
+        .expect("Unable to compare"));
+    assert_eq!(
+        find_results.edges[2]
+            .cmp_with(&find_results.edges[1], sort)
+            .expect("Unable to compare"),
+        Ordering::Greater
+    );
+
     // get the second page
     options = create_options(3, 0, doc! { "how_many": 1, "name": -1, "non_existent": 1 });
     let mut cursor = find_results.page_info.end_cursor;
@@ -131,7 +148,7 @@ async fn main() {
     options = create_options(3, 0, doc! { "how_many": 1, "name": -1, "non_existent": 1 });
     cursor = find_results.page_info.start_cursor;
     find_results = fruits
-        .find_paginated(None, Some(options), cursor)
+        .find_paginated(None, Some(options), cursor.clone())
         .await
         .expect("Unable to find data");
     assert_eq!(find_results.items, vec![MyFruit::new("Orange", 3),]);
@@ -140,6 +157,190 @@ async fn main() {
         &find_results,
     );
 
+    // or a full first page instead
+    options = create_options(3, 0, doc! { "how_many": 1, "name": -1, "non_existent": 1 });
+    find_results = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().backward_start(BackwardStart::Refill),
+    )
+    .find_paginated(None, Some(options), cursor)
+    .await
+    .expect("Unable to find data");
+    assert_eq!(
+        find_results.items,
+        vec![
+            MyFruit::new("Orange", 3),
+            MyFruit::new("Avocado", 5),
+            MyFruit::new("Apple", 5)
+        ]
+    );
+    assert!(find_results.page_info.is_first_page());
+
+    // walking back from the end returns the pages of the forward walk in reverse
+    let mut back_pages = Vec::new();
+    let mut before = None;
+    loop {
+        options = create_options(3, 0, doc! { "how_many": 1, "name": -1, "non_existent": 1 });
+        find_results = fruits
+            .find_paginated_back(None, Some(options), before)
+            .await
+            .expect("Unable to find data");
+        back_pages.push(find_results.items.clone());
+        if find_results.page_info.is_first_page() {
+            break;
+        }
+        before = find_results
+            .page_info
+            .start_cursor
+            .map(|cursor| cursor.into_inner());
+    }
+    assert_eq!(
+        back_pages,
+        vec![
+            vec![MyFruit::new("Blackberry", 12)],
+            vec![
+                MyFruit::new("Blueberry", 10),
+                MyFruit::new("Bananas", 10),
+                MyFruit::new("Grapes", 12)
+            ],
+            vec![
+                MyFruit::new("Orange", 3),
+                MyFruit::new("Avocado", 5),
+                MyFruit::new("Apple", 5)
+            ],
+        ]
+    );
+
+    // the pages after a skip of 4 are aligned to the skip
+    options = create_options(3, 4, doc! { "how_many": 1, "name": -1, "non_existent": 1 });
+    find_results = fruits
+        .find_paginated_back(None, Some(options), None)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        find_results.items,
+        vec![
+            MyFruit::new("Bananas", 10),
+            MyFruit::new("Grapes", 12),
+            MyFruit::new("Blackberry", 12)
+        ]
+    );
+    assert!(find_results.page_info.is_first_page());
+
+    // aligned pages keep to multiples of the limit in both directions after the skip
+    let aligned = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().align_pages(true),
+    );
+    let mut aligned_pages = Vec::new();
+    options = create_options(3, 4, doc! { "how_many": 1, "name": -1, "non_existent": 1 });
+    find_results = aligned
+        .find_paginated(None, Some(options), None)
+        .await
+        .expect("Unable to find data");
+    aligned_pages.push(find_results.items.clone());
+    let first_aligned = find_results.page_info.start_cursor.clone();
+    options = create_options(3, 0, doc! { "how_many": 1, "name": -1, "non_existent": 1 });
+    find_results = aligned
+        .find_paginated(None, Some(options), find_results.page_info.end_cursor)
+        .await
+        .expect("Unable to find data");
+    aligned_pages.push(find_results.items.clone());
+    cursor = first_aligned;
+    for _ in 0..2 {
+        options = create_options(3, 0, doc! { "how_many": 1, "name": -1, "non_existent": 1 });
+        find_results = aligned
+            .find_paginated(None, Some(options), cursor)
+            .await
+            .expect("Unable to find data");
+        aligned_pages.push(find_results.items.clone());
+        cursor = find_results.page_info.start_cursor;
+    }
+    assert_eq!(
+        aligned_pages,
+        vec![
+            vec![MyFruit::new("Bananas", 10), MyFruit::new("Grapes", 12)],
+            vec![MyFruit::new("Blackberry", 12)],
+            vec![MyFruit::new("Blueberry", 10)],
+            vec![
+                MyFruit::new("Orange", 3),
+                MyFruit::new("Avocado", 5),
+                MyFruit::new("Apple", 5)
+            ],
+        ]
+    );
+
+    // the offset of a cursor from an unaligned page is counted
+    options = create_options(3, 0, doc! { "how_many": 1, "name": -1, "non_existent": 1 });
+    find_results = fruits
+        .find_paginated(None, Some(options), None)
+        .await
+        .expect("Unable to find data");
+    options = create_options(3, 0, doc! { "how_many": 1, "name": -1, "non_existent": 1 });
+    find_results = aligned
+        .find_paginated(None, Some(options), find_results.page_info.next_request())
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        find_results.items,
+        vec![
+            MyFruit::new("Blueberry", 10),
+            MyFruit::new("Bananas", 10),
+            MyFruit::new("Grapes", 12)
+        ]
+    );
+
+    // how_many and name are unique together, so the cursor omits _id
+    let compound = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default().unique_sort(true),
+    );
+    options = create_options(3, 0, doc! { "how_many": 1, "name": -1 });
+    find_results = compound
+        .find_paginated(None, Some(options), None)
+        .await
+        .expect("Unable to find data");
+    cursor = find_results.page_info.next_request();
+    assert!(cursor
+        .as_ref()
+        .is_some_and(|cursor| !cursor.inner().contains_key("_id")));
+    let sort = Sort::asc("how_many").then_desc("name");
+    assert_eq!(sort! { how_many: asc, "name": desc }, sort);
+    assert_eq!(
+        Sort::try_from(doc! { "how_many": 1, "name": -1 }).ok(),
+        Some(sort.clone())
+    );
+    assert!(Sort::try_from(doc! { "how_many": 2 }).is_err());
+    options = create_options(3, 0, sort.into());
+    find_results = compound
+        .find_paginated(None, Some(options), cursor)
+        .await
+        .expect("Unable to find data");
+    assert_eq!(
+        find_results.items,
+        vec![
+            MyFruit::new("Blueberry", 10),
+            MyFruit::new("Bananas", 10),
+            MyFruit::new("Grapes", 12)
+        ]
+    );
+
+    // how_many alone is not unique, the audit catches items sharing it
+    let audited = Paginator::new(
+        fruits.clone(),
+        PaginationConfig::default()
+            .unique_sort(true)
+            .audit_sort(SortAudit::Deny),
+    );
+    let unstable: Result<FindResult<MyFruit>, _> = audited
+        .find_paginated(
+            None,
+            Some(create_options(3, 0, doc! { "how_many": 1 })),
+            None,
+        )
+        .await;
+    assert!(matches!(unstable, Err(CursorError::UnstableSort(_))));
+
     db.collection::<Document>("myfruits")
         .drop(None)
         .await
@@ -0,0 +1,265 @@
+#![allow(clippy::pedantic, clippy::restriction, clippy::cargo, missing_docs)]
+
+use std::str::FromStr;
+
+use bson::spec::BinarySubtype;
+use bson::{doc, Binary, Bson, DateTime, Decimal128, Document, Timestamp, Uuid};
+use mongodb::options::FindOptions;
+use mongodb::{Client, Collection};
+use mongodb_cursor_pagination::{
+    CursorError, CursorOptions, DirectedCursor, Edge, FindResult, Pagination, PaginationConfig,
+    Paginator,
+};
+
+fn options(sort: Document) -> FindOptions {
+    FindOptions::builder().limit(2).sort(sort).build()
+}
+
+fn decimal(value: &str) -> Bson {
+    Bson::Decimal128(Decimal128::from_str(value).expect("Invalid decimal"))
+}
+
+fn ids(page: &FindResult<Document>) -> Vec<i32> {
+    page.items
+        .iter()
+        .map(|item| item.get_i32("_id").expect("Missing _id"))
+        .collect()
+}
+
+/// Walks all pages of `sort` two at a time, every cursor passing through its string form.
+async fn walk(items: &Collection<Document>, sort: Document, backwards: bool) -> Vec<Vec<i32>> {
+    let mut pages = Vec::new();
+    let mut cursor = backwards.then(DirectedCursor::from_end);
+    loop {
+        let page: FindResult<Document> = items
+            .find_paginated(None, Some(options(sort.clone())), cursor)
+            .await
+            .expect("Unable to find data");
+        pages.push(ids(&page));
+        let next = if backwards {
+            page.page_info.previous_request()
+        } else {
+            page.page_info.next_request()
+        };
+        let Some(next) = next else {
+            return pages;
+        };
+        let edge = Edge::from_str(&next.inner().to_string()).expect("Invalid cursor");
+        assert_eq!(&edge, next.inner());
+        cursor = Some(if backwards {
+            DirectedCursor::Backwards(edge)
+        } else {
+            DirectedCursor::Forward(edge)
+        });
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let client = Client::with_uri_str("mongodb://localhost:27017/")
+        .await
+        .expect("Failed to initialize client.");
+    let db = client.database("mongodb_cursor_pagination");
+    let items = db.collection::<Document>("mytypes");
+
+    // Ensure there is no collection mytypes
+    items.drop(None).await.expect("Failed to drop table");
+
+    items
+        .insert_many(
+            vec![
+                doc! {
+                    "_id": 1, "price": decimal("10.00"),
+                    "created": DateTime::from_millis(5_000),
+                    "stamp": Timestamp { time: 3, increment: 1 },
+                },
+                doc! {
+                    "_id": 2, "price": decimal("2.5"),
+                    "created": DateTime::from_millis(-1_000),
+                    "stamp": Timestamp { time: 1, increment: 2 },
+                },
+                doc! {
+                    "_id": 3, "price": decimal("2.50"),
+                    "created": DateTime::from_millis(3_000),
+                    "stamp": Timestamp { time: 1, increment: 1 },
+                },
+                doc! {
+                    "_id": 4, "price": decimal("-1E+1"),
+                    "created": DateTime::from_millis(0),
+                    "stamp": Timestamp { time: 2, increment: 0 },
+                },
+                doc! {
+                    "_id": 5, "price": decimal("0.125"),
+                    "created": DateTime::from_millis(4_000),
+                    "stamp": Timestamp { time: 4, increment: 0 },
+                },
+            ],
+            None,
+        )
+        .await
+        .expect("Unable to insert data");
+
+    // decimals compare by value, 2.5 and 2.50 are ordered by the _id tiebreak
+    assert_eq!(
+        walk(&items, doc! { "price": 1 }, false).await,
+        vec![vec![4, 5], vec![3, 2], vec![1]]
+    );
+    assert_eq!(
+        walk(&items, doc! { "price": -1 }, false).await,
+        vec![vec![1, 3], vec![2, 5], vec![4]]
+    );
+    assert_eq!(
+        walk(&items, doc! { "price": 1 }, true).await,
+        vec![vec![2, 1], vec![5, 3], vec![4]]
+    );
+
+    // dates before the epoch come first
+    assert_eq!(
+        walk(&items, doc! { "created": 1 }, false).await,
+        vec![vec![2, 4], vec![3, 5], vec![1]]
+    );
+    assert_eq!(
+        walk(&items, doc! { "created": -1 }, true).await,
+        vec![vec![4, 2], vec![5, 3], vec![1]]
+    );
+
+    // timestamps compare by time, then by increment
+    assert_eq!(
+        walk(&items, doc! { "stamp": -1, "created": 1 }, false).await,
+        vec![vec![5, 1], vec![4, 2], vec![3]]
+    );
+
+    // a cursor no range query can continue from is rejected before any query runs
+    let cursor_options = CursorOptions::new(options(doc! { "price": 1 }), None);
+    for price in [decimal("NaN"), Bson::Double(f64::NAN)] {
+        let edge = Edge::new(&doc! { "_id": 6, "price": price }, &cursor_options);
+        let rejected: Result<FindResult<Document>, _> = items
+            .find_paginated(
+                None,
+                Some(options(doc! { "price": 1 })),
+                Some(DirectedCursor::Forward(edge)),
+            )
+            .await;
+        assert!(matches!(
+            rejected,
+            Err(CursorError::CursorFieldTypeMismatch { .. })
+        ));
+    }
+
+    // numbers of every type compare by value, the cursors hold them as Int64 where exact
+    items.drop(None).await.expect("Failed to drop table");
+    items
+        .insert_many(
+            vec![
+                doc! { "_id": 1, "score": 5_i32 },
+                doc! { "_id": 2, "score": 5.0_f64 },
+                doc! { "_id": 3, "score": 5_i64 },
+                doc! { "_id": 4, "score": 5.5_f64 },
+                doc! { "_id": 5, "score": 6_i32 },
+                doc! { "_id": 6, "score": 4.0_f64 },
+            ],
+            None,
+        )
+        .await
+        .expect("Unable to insert data");
+    assert_eq!(
+        walk(&items, doc! { "score": 1 }, false).await,
+        vec![vec![6, 3], vec![2, 1], vec![4, 5]]
+    );
+    assert_eq!(
+        walk(&items, doc! { "score": -1 }, true).await,
+        vec![vec![1, 6], vec![3, 2], vec![5, 4]]
+    );
+    let cursor_options = CursorOptions::new(options(doc! { "score": 1 }), None);
+    let mut edge = Edge::new(&doc! { "_id": 2, "score": 5.0_f64 }, &cursor_options);
+    assert_eq!(edge.get("score"), Some(&Bson::Int64(5)));
+    let canonical = edge.to_string();
+    // also when modified after it was created
+    edge.insert("score", 5_i32);
+    assert_eq!(edge.to_string(), canonical);
+    let page: FindResult<Document> = items
+        .find_paginated(
+            None,
+            Some(options(doc! { "score": 1 })),
+            Some(DirectedCursor::Forward(edge)),
+        )
+        .await
+        .expect("Unable to find data");
+    assert_eq!(ids(&page), vec![1, 4]);
+
+    // UUID keys keep their subtype through the string form of the cursors
+    items.drop(None).await.expect("Failed to drop table");
+    let mut uuids: Vec<Uuid> = (0..5).map(|_| Uuid::new()).collect();
+    items
+        .insert_many(
+            uuids
+                .iter()
+                .enumerate()
+                .map(|(id, uuid)| doc! { "_id": id as i32, "key": *uuid }),
+            None,
+        )
+        .await
+        .expect("Unable to insert data");
+    uuids.sort_by_key(|uuid| uuid.bytes());
+    let key_ids = |page: &FindResult<Document>| -> Vec<Uuid> {
+        page.items
+            .iter()
+            .map(|item| match item.get("key") {
+                Some(Bson::Binary(binary)) => {
+                    assert_eq!(binary.subtype, BinarySubtype::Uuid);
+                    Uuid::from_bytes(binary.bytes.clone().try_into().expect("Not a UUID"))
+                }
+                key => panic!("Unexpected key {key:?}"),
+            })
+            .collect()
+    };
+    let keyed: Vec<Vec<Uuid>> = {
+        let mut pages = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page: FindResult<Document> = items
+                .find_paginated(None, Some(options(doc! { "key": 1 })), cursor)
+                .await
+                .expect("Unable to find data");
+            pages.push(key_ids(&page));
+            let Some(next) = page.page_info.next_request() else {
+                break pages;
+            };
+            let edge = Edge::from_str(&next.inner().to_string()).expect("Invalid cursor");
+            assert_eq!(
+                edge.get_typed::<Uuid>("key").ok(),
+                uuids.get(pages.len() * 2 - 1).copied()
+            );
+            cursor = Some(DirectedCursor::Forward(edge));
+        }
+    };
+    assert_eq!(keyed.concat(), uuids);
+
+    // cursors of UUIDs serialized as plain bytes or strings are given the stored subtype
+    let uuid_keys = Paginator::new(
+        items.clone(),
+        PaginationConfig::default().binary_subtype("key", BinarySubtype::Uuid),
+    );
+    let key_options = CursorOptions::new(options(doc! { "key": 1 }), None);
+    let second = uuids.get(1).copied().expect("Missing UUID");
+    for key in [
+        Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: second.bytes().to_vec(),
+        }),
+        Bson::String(second.to_string()),
+    ] {
+        let edge = Edge::new(&doc! { "_id": 1, "key": key }, &key_options);
+        let page: FindResult<Document> = uuid_keys
+            .find_paginated(
+                None,
+                Some(options(doc! { "key": 1 })),
+                Some(DirectedCursor::Forward(edge)),
+            )
+            .await
+            .expect("Unable to find data");
+        assert_eq!(key_ids(&page), uuids.get(2..4).expect("Missing UUIDs"));
+    }
+
+    items.drop(None).await.expect("Unable to drop collection");
+}
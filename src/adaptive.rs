@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use bson::{doc, Document, RawDocumentBuf};
+use futures_util::TryStreamExt;
+use mongodb::Collection;
+
+use crate::config::PaginationConfig;
+use crate::error::CursorError;
+
+/// Shrinks the limit of every page so its items fit into a byte budget, e.g. the maximum
+/// response size of an API gateway. The limit of the find options still caps the page.
+///
+/// The size of an item is the average size of the items of the previous page, or before the
+/// first page the average size of the documents in the collection from `$collStats`, if it can
+/// be read. Clones share what they learned, so keep one per collection and query shape.
+#[derive(Clone, Debug)]
+pub struct AdaptiveLimit {
+    budget: u64,
+    average: Arc<AtomicU64>,
+}
+
+/// Marks an unknown average item size.
+const UNKNOWN: u64 = 0;
+
+impl AdaptiveLimit {
+    /// Creates a controller keeping the items of a page within `budget` bytes.
+    #[must_use]
+    pub fn new(budget: u64) -> Self {
+        Self {
+            budget,
+            average: Arc::new(AtomicU64::new(UNKNOWN)),
+        }
+    }
+
+    /// Returns the byte budget of a page.
+    #[must_use]
+    pub const fn budget(&self) -> u64 {
+        self.budget
+    }
+
+    /// Returns the average item size in bytes the limit is currently derived from.
+    #[must_use]
+    pub fn average_item_size(&self) -> Option<u64> {
+        Some(self.average.load(Ordering::Relaxed)).filter(|average| *average != UNKNOWN)
+    }
+
+    /// Returns the limit of the next page, at most `limit` and at least one item.
+    pub(crate) async fn limit(
+        &self,
+        collection: &Collection<Document>,
+        config: &PaginationConfig,
+        limit: Option<i64>,
+    ) -> Option<i64> {
+        let average = match self.average_item_size() {
+            Some(average) => Some(average),
+            // `$collStats` needs the `collStats` privilege and fails on views, the page then
+            // keeps the configured limit until it learned the item size.
+            None => collection_average(collection, config).await.ok().flatten(),
+        };
+        let Some(average) = average.filter(|average| *average > 0) else {
+            return limit;
+        };
+        let fitting = self.budget.checked_div(average).unwrap_or_default().max(1);
+        let fitting = i64::try_from(fitting).unwrap_or(i64::MAX);
+        Some(limit.map_or(fitting, |limit| limit.min(fitting)))
+    }
+
+    /// Learns the average item size from the `documents` of a page.
+    pub(crate) fn record(&self, documents: &[RawDocumentBuf]) {
+        let bytes = documents
+            .iter()
+            .map(|document| document.as_bytes().len())
+            .sum::<usize>();
+        if let Some(average) = bytes.checked_div(documents.len()) {
+            let average = u64::try_from(average).unwrap_or(u64::MAX);
+            self.average.store(average.max(1), Ordering::Relaxed);
+        }
+    }
+}
+
+/// Returns the average document size of `collection` from `$collStats`, if it has documents.
+async fn collection_average(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+) -> Result<Option<u64>, CursorError> {
+    let pipeline = vec![doc! { "$collStats": { "storageStats": {} } }];
+    let stats: Vec<Document> = config
+        .run(collection.name(), || async {
+            Ok(collection
+                .aggregate(pipeline.clone(), None)
+                .await?
+                .try_collect()
+                .await?)
+        })
+        .await?;
+    Ok(stats.iter().find_map(|stats| {
+        let average = stats.get_document("storageStats").ok()?.get("avgObjSize")?;
+        if let Some(average) = average.as_f64() {
+            // Truncating a size in bytes is fine, negative sizes saturate to zero.
+            #[allow(
+                clippy::as_conversions,
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss
+            )]
+            let average = average as u64;
+            return Some(average);
+        }
+        let average = average
+            .as_i64()
+            .or_else(|| average.as_i32().map(i64::from))?;
+        u64::try_from(average).ok()
+    }))
+}
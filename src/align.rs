@@ -0,0 +1,115 @@
+use bson::Document;
+use mongodb::options::FindOptions;
+use mongodb::Collection;
+
+use crate::config::PaginationConfig;
+use crate::error::CursorError;
+use crate::model::DirectedCursor;
+use crate::session::SessionLock;
+
+/// Shrinks the limit of `find_options` so the page ends at a multiple of the limit, counted
+/// from the first item. The offset of the cursor is counted once if it carries none, later
+/// cursors carry the offsets of their items.
+pub(crate) async fn align(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Option<&Document>,
+    find_options: Option<FindOptions>,
+    cursor: Option<DirectedCursor>,
+    session: Option<&SessionLock<'_>>,
+) -> Result<(Option<FindOptions>, Option<DirectedCursor>), CursorError> {
+    let Some(limit) = find_options
+        .as_ref()
+        .and_then(|options| options.limit)
+        .and_then(|limit| u64::try_from(limit).ok())
+        .filter(|limit| *limit > 0)
+    else {
+        return Ok((find_options, cursor));
+    };
+    let mut options = find_options.unwrap_or_default();
+    let (size, cursor) = match cursor {
+        None => {
+            let skip = options.skip.unwrap_or_default();
+            (size_from(skip, limit), None)
+        }
+        Some(cursor) => {
+            let offset = match cursor.inner().offset() {
+                Some(offset) => offset,
+                None => preceding(collection, config, filter, &options, &cursor, session).await?,
+            };
+            let size = if let DirectedCursor::Forward(_) = cursor {
+                size_from(offset.saturating_add(1), limit)
+            } else {
+                size_before(offset, limit)
+            };
+            (
+                size,
+                Some(cursor.map_edge(|edge| edge.with_offset(Some(offset)))),
+            )
+        }
+    };
+    options.limit = Some(i64::try_from(size).unwrap_or(i64::MAX));
+    Ok((Some(options), cursor))
+}
+
+/// Returns the size of the page starting at the item at `start` that ends at a multiple of
+/// `limit`.
+fn size_from(start: u64, limit: u64) -> u64 {
+    limit.saturating_sub(start.checked_rem(limit).unwrap_or_default())
+}
+
+/// Returns the size of the page ending before the item at `end` that starts at a multiple of
+/// `limit`, a full page if `end` is a multiple itself.
+fn size_before(end: u64, limit: u64) -> u64 {
+    match end.checked_rem(limit) {
+        Some(0) | None => limit,
+        Some(rest) => rest,
+    }
+}
+
+/// Counts the items before the item of `cursor`, its offset.
+async fn preceding(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Option<&Document>,
+    options: &FindOptions,
+    cursor: &DirectedCursor,
+    session: Option<&SessionLock<'_>>,
+) -> Result<u64, CursorError> {
+    let before = DirectedCursor::Backwards(cursor.inner().clone());
+    let prepared = crate::prepare(config, options.clone(), Some(before.clone()));
+    let filter = filter.cloned().unwrap_or_default();
+    let query = crate::get_query(filter, &prepared, Some(&before), config.unique_sort)?;
+    // Counted with the collation, hint and read concern of the find, so both see the same items.
+    let count = crate::count_up_to(
+        collection,
+        config,
+        Some(query),
+        u64::MAX,
+        Some(options.clone()),
+        session,
+    )
+    .await?;
+    Ok(count.value().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{size_before, size_from};
+
+    #[test]
+    fn forward_pages_end_at_a_multiple_of_the_limit() {
+        assert_eq!(size_from(0, 5), 5);
+        assert_eq!(size_from(3, 5), 2);
+        assert_eq!(size_from(5, 5), 5);
+        assert_eq!(size_from(12, 5), 3);
+    }
+
+    #[test]
+    fn backwards_pages_start_at_a_multiple_of_the_limit() {
+        assert_eq!(size_before(0, 5), 5);
+        assert_eq!(size_before(3, 5), 3);
+        assert_eq!(size_before(10, 5), 5);
+        assert_eq!(size_before(12, 5), 2);
+    }
+}
@@ -0,0 +1,39 @@
+use bson::spec::BinarySubtype;
+use bson::{Binary, Bson, Uuid};
+
+use crate::model::DirectedCursor;
+
+/// Gives the values of the keys of `subtypes` in `cursor` the binary subtype the items store
+/// them with. A UUID serialized by a type without BSON support, e.g. through
+/// [`Edge::from_item`](crate::Edge::from_item), is a binary of the generic subtype, which `MongoDB`
+/// sorts before every UUID of the same length, or a string.
+pub(crate) fn coerce(
+    subtypes: &[(String, BinarySubtype)],
+    cursor: DirectedCursor,
+) -> DirectedCursor {
+    if subtypes.is_empty() {
+        return cursor;
+    }
+    cursor.map_edge(|mut edge| {
+        for (key, subtype) in subtypes {
+            let Some(value) = edge.get_mut(key) else {
+                continue;
+            };
+            if let Bson::Binary(binary) = value {
+                binary.subtype = *subtype;
+                continue;
+            }
+            let uuid = match (&*value, subtype) {
+                (Bson::String(uuid), BinarySubtype::Uuid) => Uuid::parse_str(uuid).ok(),
+                _ => None,
+            };
+            if let Some(uuid) = uuid {
+                *value = Bson::Binary(Binary {
+                    subtype: BinarySubtype::Uuid,
+                    bytes: uuid.bytes().to_vec(),
+                });
+            }
+        }
+        edge
+    })
+}
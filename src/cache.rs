@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use bson::Document;
+
+use crate::model::FindResult;
+
+/// Identifies a cached page.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct PageKey {
+    /// Namespace of the collection, `database.collection`
+    pub namespace: String,
+    /// SHA-256 of the canonical BSON of the filter and the find options shaping the page, stable
+    /// across processes
+    pub filter_hash: String,
+    /// The cursor of the page prefixed with its direction, `None` for the first page
+    pub cursor: Option<String>,
+    /// Maximum number of items of the page
+    pub limit: Option<i64>,
+}
+
+/// A cache `find_paginated` consults before querying `MongoDB`, for read-heavy collections whose
+/// pages rarely change. Pages are stored with their items as plain documents.
+pub trait PageCache: Send + Sync {
+    /// Returns the page cached for `key`.
+    fn get(&self, key: &PageKey) -> Option<FindResult<Document>>;
+
+    /// Caches `page` for `key`.
+    fn insert(&self, key: PageKey, page: FindResult<Document>);
+
+    /// Drops every cached page of the collection with `namespace`, e.g. after writing to it.
+    fn invalidate(&self, namespace: &str);
+}
+
+#[derive(Debug, Default)]
+struct LruState {
+    tick: u64,
+    pages: HashMap<PageKey, (u64, FindResult<Document>)>,
+}
+
+/// An in-memory [`PageCache`] holding at most `capacity` pages, evicting the least recently used.
+pub struct LruPageCache {
+    capacity: usize,
+    state: Mutex<LruState>,
+}
+
+impl LruPageCache {
+    /// Creates an empty [`LruPageCache`] holding at most `capacity` pages.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Returns the number of cached pages.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().pages.len()
+    }
+
+    /// Returns `true` if no page is cached.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().pages.is_empty()
+    }
+
+    /// Drops every cached page.
+    pub fn clear(&self) {
+        self.lock().pages.clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl PageCache for LruPageCache {
+    fn get(&self, key: &PageKey) -> Option<FindResult<Document>> {
+        let mut state = self.lock();
+        state.tick = state.tick.saturating_add(1);
+        let tick = state.tick;
+        let (used, page) = state.pages.get_mut(key)?;
+        *used = tick;
+        Some(page.clone())
+    }
+
+    fn insert(&self, key: PageKey, page: FindResult<Document>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.lock();
+        state.tick = state.tick.saturating_add(1);
+        let tick = state.tick;
+        if !state.pages.contains_key(&key) && state.pages.len() >= self.capacity {
+            let least_recent = state
+                .pages
+                .iter()
+                .min_by_key(|(_name, (used, _page))| *used)
+                .map(|(name, _entry)| name.clone());
+            if let Some(least_recent) = least_recent {
+                state.pages.remove(&least_recent);
+            }
+        }
+        state.pages.insert(key, (tick, page));
+    }
+
+    fn invalidate(&self, namespace: &str) {
+        self.lock()
+            .pages
+            .retain(|key, _entry| key.namespace != namespace);
+    }
+}
+
+impl fmt::Debug for LruPageCache {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("LruPageCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,107 @@
+use bson::{doc, Bson, Document};
+use mongodb::options::FindOptions;
+use mongodb::Collection;
+use serde::de::DeserializeOwned;
+
+use crate::compare::compare_values;
+use crate::config::PaginationConfig;
+use crate::error::CursorError;
+use crate::model::{DirectedCursor, Edge, FindResult};
+use crate::option::sort_value;
+
+/// Returns the latest value of `key` among the `documents` of a page and the high-water mark of
+/// the `cursor` the page continues from.
+pub(crate) fn high_water_mark(
+    key: &str,
+    cursor: Option<&DirectedCursor>,
+    documents: &[Document],
+) -> Option<Bson> {
+    let cursor_mark = cursor.and_then(|cursor| cursor.inner().high_water_mark());
+    cursor_mark
+        .into_iter()
+        .chain(
+            documents
+                .iter()
+                .filter_map(|document| sort_value(document, key)),
+        )
+        .fold(None, |latest: Option<&Bson>, value| match latest {
+            Some(latest) if !is_later(value, latest) => Some(latest),
+            _ => Some(value),
+        })
+        .cloned()
+}
+
+/// Whether `value` comes after `than`. Numbers compare by value across their types, values of
+/// other different types are never later.
+fn is_later(value: &Bson, than: &Bson) -> bool {
+    match (value, than) {
+        (
+            Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_),
+            Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_),
+        ) => compare_values(value, than).is_gt(),
+        (Bson::DateTime(value), Bson::DateTime(than)) => value > than,
+        (Bson::Timestamp(value), Bson::Timestamp(than)) => {
+            (value.time, value.increment) > (than.time, than.increment)
+        }
+        (Bson::String(value), Bson::String(than)) => value > than,
+        (Bson::ObjectId(value), Bson::ObjectId(than)) => value > than,
+        _ => false,
+    }
+}
+
+/// Finds a page of the items matching `filter` whose change key is later than the high-water
+/// mark of `since`.
+pub(crate) async fn find_changes_since<T: DeserializeOwned>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Option<Document>,
+    options: Option<FindOptions>,
+    since: &Edge,
+    cursor: Option<DirectedCursor>,
+) -> Result<FindResult<T>, CursorError> {
+    let (Some(key), Some(mark)) = (&config.change_key, since.high_water_mark()) else {
+        return Err(CursorError::MissingHighWaterMark);
+    };
+    let changed = doc! { key: { "$gt": mark.clone() } };
+    let filter = match filter {
+        Some(filter) => doc! { "$and": [filter, changed] },
+        None => changed,
+    };
+    Box::pin(crate::find(
+        collection,
+        config,
+        Some(filter),
+        options,
+        cursor,
+        None,
+    ))
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::{doc, Bson};
+
+    use super::high_water_mark;
+    use crate::model::{DirectedCursor, Edge};
+
+    #[test]
+    fn finds_the_latest_nested_change_key() {
+        let documents = [
+            doc! { "meta": { "updated_at": 3_i64 } },
+            doc! { "meta": { "updated_at": 5_i64 } },
+            doc! { "meta": {} },
+        ];
+        assert_eq!(
+            high_water_mark("meta.updated_at", None, &documents),
+            Some(Bson::Int64(5))
+        );
+        let cursor = DirectedCursor::Forward(Edge::from_document(
+            doc! { "_id": 1_i64, "$highWater": 7_i64 },
+        ));
+        assert_eq!(
+            high_water_mark("meta.updated_at", Some(&cursor), &documents),
+            Some(Bson::Int64(7))
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt;
+use std::str::FromStr;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bson::{doc, Bson, Document};
+use mongodb::options::FindOptions;
+use sha2::{Digest, Sha256};
+
+use crate::error::CursorError;
+use crate::model::{empty_page, DirectedCursor, Edge, FindResult, PageInfo};
+
+/// The progress of a walk over every page, e.g. of a long-running export, which survives a
+/// restart of the process. It encodes into a single string with `to_string` and decodes with
+/// `parse`.
+///
+/// Start with [`Checkpoint::new`], fetch the next page with
+/// [`Pagination::resume_from`](crate::Pagination::resume_from) and store
+/// [`Checkpoint::advance`] once the page is processed, until the checkpoint
+/// [is finished](Checkpoint::is_finished).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    cursor: Option<Edge>,
+    filter_hash: String,
+    sort: Document,
+    items: u64,
+    pages: u64,
+    finished: bool,
+}
+
+impl Checkpoint {
+    /// Creates the checkpoint before the first page of the items matching `filter` in the order
+    /// of `options`.
+    #[must_use]
+    pub fn new(filter: Option<&Document>, options: Option<&FindOptions>) -> Self {
+        Self {
+            cursor: None,
+            filter_hash: filter_hash(filter),
+            sort: sort_of(options),
+            items: 0,
+            pages: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns the checkpoint after `page`, the page found with this checkpoint.
+    #[must_use]
+    pub fn advance<T>(self, page: &FindResult<T>) -> Self {
+        let next = page.page_info.next_request();
+        Self {
+            finished: next.is_none(),
+            cursor: next.map(DirectedCursor::into_inner).or(self.cursor),
+            items: self
+                .items
+                .saturating_add(u64::try_from(page.items.len()).unwrap_or(u64::MAX)),
+            pages: self.pages.saturating_add(1),
+            ..self
+        }
+    }
+
+    /// Returns the edge of the last item walked, `None` before the first page.
+    #[must_use]
+    pub const fn cursor(&self) -> Option<&Edge> {
+        self.cursor.as_ref()
+    }
+
+    /// Returns the number of items walked so far.
+    #[must_use]
+    pub const fn items(&self) -> u64 {
+        self.items
+    }
+
+    /// Returns the number of pages walked so far.
+    #[must_use]
+    pub const fn pages(&self) -> u64 {
+        self.pages
+    }
+
+    /// Whether the last page was walked.
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Rejects resuming the walk with a different filter or sort than it was started with.
+    pub(crate) fn check(
+        &self,
+        filter: Option<&Document>,
+        options: Option<&FindOptions>,
+    ) -> Result<(), CursorError> {
+        if self.filter_hash != filter_hash(filter) {
+            return Err(CursorError::CheckpointMismatch("filter"));
+        }
+        if self.sort != sort_of(options) {
+            return Err(CursorError::CheckpointMismatch("sort"));
+        }
+        Ok(())
+    }
+}
+
+/// The empty page after the last page of a walk.
+pub(crate) fn finished_page<T>() -> FindResult<T> {
+    empty_page(PageInfo {
+        has_previous_page: Some(true),
+        has_next_page: Some(false),
+        ..PageInfo::default()
+    })
+}
+
+/// Hashes `filter`, stable across processes unlike the hashers of the standard library.
+fn filter_hash(filter: Option<&Document>) -> String {
+    let bytes = filter
+        .and_then(|filter| bson::to_vec(filter).ok())
+        .unwrap_or_default();
+    let digest = Sha256::digest(bytes);
+    URL_SAFE_NO_PAD.encode(digest.get(..16).unwrap_or_default())
+}
+
+fn sort_of(options: Option<&FindOptions>) -> Document {
+    options
+        .and_then(|options| options.sort.clone())
+        .unwrap_or_default()
+}
+
+impl fmt::Display for Checkpoint {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut document = doc! {
+            "filter": &self.filter_hash,
+            "sort": &self.sort,
+            "items": i64::try_from(self.items).unwrap_or(i64::MAX),
+            "pages": i64::try_from(self.pages).unwrap_or(i64::MAX),
+            "finished": self.finished,
+        };
+        if let Some(cursor) = &self.cursor {
+            document.insert("cursor", cursor.to_document());
+        }
+        let bytes = bson::to_vec(&document).map_err(|_error| fmt::Error)?;
+        fmt.write_str(&URL_SAFE_NO_PAD.encode(bytes))
+    }
+}
+
+impl FromStr for Checkpoint {
+    type Err = CursorError;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(str)
+            .map_err(|_decode| CursorError::InvalidCursor)?;
+        let document: Document = bson::from_slice(&bytes)?;
+        let count = |key: &str| {
+            document
+                .get_i64(key)
+                .ok()
+                .and_then(|count| u64::try_from(count).ok())
+                .ok_or(CursorError::InvalidCursor)
+        };
+        Ok(Self {
+            cursor: match document.get("cursor") {
+                Some(Bson::Document(cursor)) => Some(Edge::from_document(cursor.clone())),
+                Some(_) => return Err(CursorError::InvalidCursor),
+                None => None,
+            },
+            filter_hash: document.get_str("filter")?.to_owned(),
+            sort: document.get_document("sort")?.clone(),
+            items: count("items")?,
+            pages: count("pages")?,
+            finished: document.get_bool("finished")?,
+        })
+    }
+}
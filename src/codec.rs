@@ -0,0 +1,480 @@
+use std::fmt;
+#[cfg(feature = "compression")]
+use std::io::Read;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{mem, str};
+
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+use base64::Engine;
+use bson::spec::BinarySubtype;
+use bson::{doc, Binary, Bson, Document, RawDocument, RawDocumentBuf};
+#[cfg(feature = "compression")]
+use flate2::read::{DeflateDecoder, DeflateEncoder};
+#[cfg(feature = "compression")]
+use flate2::Compression;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::error::CursorError;
+use crate::model::{DirectedCursor, Edge, FindResult, PageInfo};
+
+/// Key of the [`Edge`] inside an envelope. Sort keys can't start with `$`, so it never clashes.
+const CURSOR: &str = "$cursor";
+/// Key of the scope inside an envelope.
+const SCOPE: &str = "$scope";
+/// Keys of the claims inside an envelope.
+const ISSUER: &str = "$iss";
+const AUDIENCE: &str = "$aud";
+const ISSUED_AT: &str = "$iat";
+const EXPIRES_AT: &str = "$exp";
+/// Keys of a signed cursor, holding the encoded envelope and its signature.
+const SIGNED: &str = "$signed";
+const SIGNATURE: &str = "$sig";
+/// First byte of a compressed payload. A BSON document is recognized by its length prefix instead.
+const COMPRESSED: u8 = 0xfe;
+/// Upper bound of a decompressed payload, so a crafted cursor can't inflate into a huge buffer.
+#[cfg(feature = "compression")]
+const MAX_DECOMPRESSED_LEN: u64 = 64 * 1024;
+
+/// The text encoding of a cursor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CursorEncoding {
+    /// Url-safe base64 alphabet without padding
+    #[default]
+    UrlSafeNoPad,
+    /// Url-safe base64 alphabet with padding
+    UrlSafe,
+    /// Standard base64 alphabet with padding
+    Standard,
+    /// Standard base64 alphabet without padding
+    StandardNoPad,
+    /// Lowercase hexadecimal
+    Hex,
+}
+
+impl CursorEncoding {
+    fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            Self::UrlSafeNoPad => URL_SAFE_NO_PAD.encode(bytes),
+            Self::UrlSafe => URL_SAFE.encode(bytes),
+            Self::Standard => STANDARD.encode(bytes),
+            Self::StandardNoPad => STANDARD_NO_PAD.encode(bytes),
+            Self::Hex => bytes
+                .iter()
+                .flat_map(|byte| [byte >> 4_u8, byte & 0x0f])
+                .filter_map(|nibble| char::from_digit(u32::from(nibble), 16))
+                .collect(),
+        }
+    }
+
+    fn decode(self, cursor: &str) -> Result<Vec<u8>, CursorError> {
+        match self {
+            Self::UrlSafeNoPad => URL_SAFE_NO_PAD.decode(cursor),
+            Self::UrlSafe => URL_SAFE.decode(cursor),
+            Self::Standard => STANDARD.decode(cursor),
+            Self::StandardNoPad => STANDARD_NO_PAD.decode(cursor),
+            Self::Hex => {
+                return cursor
+                    .as_bytes()
+                    .chunks(2)
+                    .map(|pair| {
+                        str::from_utf8(pair)
+                            .ok()
+                            .filter(|pair| pair.len() == 2)
+                            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                            .ok_or(CursorError::InvalidCursor)
+                    })
+                    .collect();
+            }
+        }
+        .map_err(|_decode| CursorError::InvalidCursor)
+    }
+}
+
+/// Encodes [`Edge`]s into the strings handed to clients and decodes them back.
+///
+/// Without any setting a cursor is the url-safe base64 encoded BSON of the [`Edge`], exactly like
+/// its `Display` implementation. Other settings wrap the [`Edge`] in an envelope carrying a scope
+/// and claims, which are verified on decode. With a signing key cursors become self-validating
+/// tokens, without one the claims protect against mistakes but not against forgery.
+#[derive(Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CursorCodec {
+    scope: Option<String>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    ttl: Option<Duration>,
+    key: Option<Vec<u8>>,
+    encoding: CursorEncoding,
+    compress: bool,
+    max_len: Option<usize>,
+}
+
+impl CursorCodec {
+    /// Embeds `scope`, e.g. a tenant id, into every cursor and rejects cursors of any other scope
+    /// with [`CursorError::ScopeMismatch`], so a cursor of one tenant can't be replayed by another.
+    #[must_use]
+    pub fn scope<S: Into<String>>(mut self, scope: S) -> Self {
+        self.scope = Some(scope.into());
+        self
+    }
+
+    /// Embeds `issuer` into every cursor and rejects cursors of any other issuer.
+    #[must_use]
+    pub fn issuer<S: Into<String>>(mut self, issuer: S) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Embeds `audience` into every cursor and rejects cursors meant for any other audience.
+    #[must_use]
+    pub fn audience<S: Into<String>>(mut self, audience: S) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Lets cursors expire `ttl` after they were issued with [`CursorError::CursorExpired`].
+    #[must_use]
+    pub const fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Signs every cursor with HMAC-SHA256 using `key` and rejects cursors without a valid
+    /// signature with [`CursorError::InvalidSignature`].
+    #[must_use]
+    pub fn sign<K: Into<Vec<u8>>>(mut self, key: K) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Uses `encoding` for the cursor strings instead of unpadded url-safe base64. The edges of
+    /// the pages of a [`Paginator`](crate::Paginator) display and serialize in it too, but
+    /// `str::parse` and deserializing only read unpadded url-safe base64, decode other encodings
+    /// with [`CursorCodec::decode`].
+    #[must_use]
+    pub const fn encoding(mut self, encoding: CursorEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Deflates cursor payloads when that makes them shorter, for sorts on many or long keys.
+    /// Compressed cursors are detected on decode, whether or not this is set.
+    #[cfg(feature = "compression")]
+    #[must_use]
+    pub const fn compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Fails encoding with [`CursorError::CursorTooLong`] if a cursor would be longer than
+    /// `max_len` bytes, and rejects longer cursors on decode with the same error before parsing
+    /// them.
+    #[must_use]
+    pub const fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Encodes `edge` into a cursor string.
+    pub fn encode(&self, edge: &Edge) -> Result<String, CursorError> {
+        self.encode_document(edge.to_document())
+    }
+
+    /// Encodes the payload of a cursor, wrapping it in an envelope with the configured claims.
+    pub(crate) fn encode_document(&self, payload: Document) -> Result<String, CursorError> {
+        let document = if self.is_enveloped() {
+            let mut envelope = doc! { CURSOR: payload };
+            if let Some(scope) = &self.scope {
+                envelope.insert(SCOPE, scope);
+            }
+            if let Some(issuer) = &self.issuer {
+                envelope.insert(ISSUER, issuer);
+            }
+            if let Some(audience) = &self.audience {
+                envelope.insert(AUDIENCE, audience);
+            }
+            let now = unix_time(SystemTime::now());
+            envelope.insert(ISSUED_AT, now);
+            if let Some(ttl) = self.ttl {
+                let ttl = i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX);
+                envelope.insert(EXPIRES_AT, now.saturating_add(ttl));
+            }
+            envelope
+        } else {
+            payload
+        };
+        let mut bytes = bson::to_vec(&document)?;
+        if let Some(key) = &self.key {
+            let signature = mac(key)?.chain_update(&bytes).finalize().into_bytes();
+            bytes = bson::to_vec(&doc! {
+                SIGNED: binary(bytes),
+                SIGNATURE: binary(signature.to_vec()),
+            })?;
+        }
+        if self.compress {
+            bytes = compress(bytes);
+        }
+        let cursor = self.encoding.encode(&bytes);
+        match self.max_len {
+            Some(max) if cursor.len() > max => Err(CursorError::CursorTooLong {
+                len: cursor.len(),
+                max,
+            }),
+            _ => Ok(cursor),
+        }
+    }
+
+    /// Decodes a cursor string, verifying its signature, scope and claims.
+    pub fn decode(&self, cursor: &str) -> Result<Edge, CursorError> {
+        self.decode_document(cursor).map(Edge::from_document)
+    }
+
+    /// Decodes the payload of a cursor, verifying its signature, scope and claims.
+    pub(crate) fn decode_document(&self, cursor: &str) -> Result<Document, CursorError> {
+        if let Some(max) = self.max_len.filter(|max| cursor.len() > *max) {
+            return Err(CursorError::CursorTooLong {
+                len: cursor.len(),
+                max,
+            });
+        }
+        let mut bytes = self.encoding.decode(cursor)?;
+        if bytes.first() == Some(&COMPRESSED) && RawDocument::from_bytes(&bytes).is_err() {
+            bytes = decompress(&bytes)?;
+        }
+        let mut document = RawDocumentBuf::from_bytes(bytes)?.to_document()?;
+        if let Some(key) = &self.key {
+            let (Some(Bson::Binary(payload)), Some(Bson::Binary(signature))) =
+                (document.remove(SIGNED), document.remove(SIGNATURE))
+            else {
+                return Err(CursorError::InvalidSignature);
+            };
+            mac(key)?
+                .chain_update(&payload.bytes)
+                .verify_slice(&signature.bytes)
+                .map_err(|_mismatch| CursorError::InvalidSignature)?;
+            document = RawDocumentBuf::from_bytes(payload.bytes)?.to_document()?;
+        }
+        if !document.contains_key(CURSOR) {
+            document = doc! { CURSOR: document };
+        }
+
+        if claim(&mut document, SCOPE)? != self.scope {
+            return Err(CursorError::ScopeMismatch);
+        }
+        let issuer = claim(&mut document, ISSUER)?;
+        if self.issuer.is_some() && issuer != self.issuer {
+            return Err(CursorError::InvalidCursorClaim("iss"));
+        }
+        let audience = claim(&mut document, AUDIENCE)?;
+        if self.audience.is_some() && audience != self.audience {
+            return Err(CursorError::InvalidCursorClaim("aud"));
+        }
+        match document.remove(EXPIRES_AT) {
+            Some(Bson::Int64(expires_at)) if expires_at < unix_time(SystemTime::now()) => {
+                return Err(CursorError::CursorExpired);
+            }
+            Some(Bson::Int64(_)) => {}
+            Some(_) => return Err(CursorError::InvalidCursorClaim("exp")),
+            None if self.ttl.is_some() => return Err(CursorError::InvalidCursorClaim("exp")),
+            None => {}
+        }
+
+        match document.remove(CURSOR) {
+            Some(Bson::Document(payload)) => Ok(payload),
+            _ => Err(CursorError::InvalidCursor),
+        }
+    }
+
+    /// Rejects `edge` if it was parsed from a string without a codec, e.g. with `str::parse`,
+    /// while this codec would have verified a signature, scope or claims on decode.
+    pub(crate) fn check_verified(&self, edge: &Edge) -> Result<(), CursorError> {
+        if !edge.is_unverified() {
+            return Ok(());
+        }
+        if self.key.is_some() {
+            Err(CursorError::InvalidSignature)
+        } else if self.scope.is_some() {
+            Err(CursorError::ScopeMismatch)
+        } else if self.issuer.is_some() {
+            Err(CursorError::InvalidCursorClaim("iss"))
+        } else if self.audience.is_some() {
+            Err(CursorError::InvalidCursorClaim("aud"))
+        } else if self.ttl.is_some() {
+            Err(CursorError::InvalidCursorClaim("exp"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn is_enveloped(&self) -> bool {
+        self.scope.is_some()
+            || self.issuer.is_some()
+            || self.audience.is_some()
+            || self.ttl.is_some()
+            || self.key.is_some()
+    }
+}
+
+impl fmt::Debug for CursorCodec {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("CursorCodec")
+            .field("scope", &self.scope)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("ttl", &self.ttl)
+            .field("signed", &self.key.is_some())
+            .field("encoding", &self.encoding)
+            .field("compress", &self.compress)
+            .field("max_len", &self.max_len)
+            .finish()
+    }
+}
+
+/// Encodes the edges and cursors of `page` with `codec`, unless it encodes like the default
+/// codec anyway, so they display and serialize in its encoding.
+pub(crate) fn attach<T>(page: &mut FindResult<T>, codec: &CursorCodec) -> Result<(), CursorError> {
+    if *codec == CursorCodec::default() {
+        return Ok(());
+    }
+    page.edges = mem::take(&mut page.edges)
+        .into_iter()
+        .map(|edge| edge.encoded_with(codec))
+        .collect::<Result<_, _>>()?;
+    let PageInfo {
+        start_cursor,
+        end_cursor,
+        start_cursor_forward,
+        end_cursor_backward,
+        ..
+    } = &mut page.page_info;
+    for cursor in [
+        start_cursor,
+        end_cursor,
+        start_cursor_forward,
+        end_cursor_backward,
+    ] {
+        *cursor = match cursor.take() {
+            Some(DirectedCursor::Forward(edge)) => {
+                Some(DirectedCursor::Forward(edge.encoded_with(codec)?))
+            }
+            Some(DirectedCursor::Backwards(edge)) => {
+                Some(DirectedCursor::Backwards(edge.encoded_with(codec)?))
+            }
+            None => None,
+        };
+    }
+    Ok(())
+}
+
+/// Removes the string claim `key` from an envelope.
+fn claim(envelope: &mut Document, key: &str) -> Result<Option<String>, CursorError> {
+    match envelope.remove(key) {
+        Some(Bson::String(value)) => Ok(Some(value)),
+        Some(_) => Err(CursorError::InvalidCursor),
+        None => Ok(None),
+    }
+}
+
+/// Deflates `bytes` behind the [`COMPRESSED`] marker, unless that doesn't make them shorter.
+#[cfg(feature = "compression")]
+fn compress(bytes: Vec<u8>) -> Vec<u8> {
+    let mut compressed = vec![COMPRESSED];
+    let deflated = DeflateEncoder::new(bytes.as_slice(), Compression::best())
+        .read_to_end(&mut compressed)
+        .is_ok();
+    if deflated && compressed.len() < bytes.len() {
+        compressed
+    } else {
+        bytes
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+const fn compress(bytes: Vec<u8>) -> Vec<u8> {
+    bytes
+}
+
+#[cfg(feature = "compression")]
+fn decompress(bytes: &[u8]) -> Result<Vec<u8>, CursorError> {
+    let mut decompressed = Vec::new();
+    DeflateDecoder::new(bytes.get(1..).unwrap_or_default())
+        .take(MAX_DECOMPRESSED_LEN)
+        .read_to_end(&mut decompressed)
+        .map_err(|_inflate| CursorError::InvalidCursor)?;
+    Ok(decompressed)
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(_bytes: &[u8]) -> Result<Vec<u8>, CursorError> {
+    Err(CursorError::InvalidCursor)
+}
+
+fn mac(key: &[u8]) -> Result<Hmac<Sha256>, CursorError> {
+    Hmac::new_from_slice(key).map_err(|_length| CursorError::InvalidSignature)
+}
+
+fn binary(bytes: Vec<u8>) -> Binary {
+    Binary {
+        subtype: BinarySubtype::Generic,
+        bytes,
+    }
+}
+
+/// Seconds since the unix epoch, as stored in the `$iat` and `$exp` claims.
+fn unix_time(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |since| {
+        i64::try_from(since.as_secs()).unwrap_or(i64::MAX)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::doc;
+
+    use super::{attach, CursorCodec, CursorEncoding};
+    use crate::error::CursorError;
+    use crate::model::{DirectedCursor, Edge, FindResult};
+
+    fn page() -> FindResult<i32> {
+        let edge = Edge::from_document(doc! { "name": "a".repeat(64), "_id": 1_i64 });
+        let mut page = FindResult {
+            edges: vec![edge.clone()],
+            items: vec![1_i32],
+            ..FindResult::default()
+        };
+        page.page_info.end_cursor = Some(DirectedCursor::Forward(edge));
+        page
+    }
+
+    #[test]
+    fn fails_pages_with_overlong_cursors() {
+        assert!(matches!(
+            attach(&mut page(), &CursorCodec::default().max_len(16)),
+            Err(CursorError::CursorTooLong { max: 16, .. })
+        ));
+    }
+
+    #[test]
+    fn encodes_page_cursors_with_the_codec() {
+        let codec = CursorCodec::default().encoding(CursorEncoding::Hex);
+        let mut page = page();
+        assert!(matches!(attach(&mut page, &codec), Ok(())));
+        let end_cursor = page
+            .page_info
+            .end_cursor
+            .map(|cursor| cursor.inner().to_string())
+            .unwrap_or_default();
+        assert!(end_cursor.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        assert_eq!(codec.decode(&end_cursor).ok().as_ref(), page.edges.first());
+    }
+
+    #[test]
+    fn rejects_overlong_cursors_before_decoding() {
+        assert!(matches!(
+            CursorCodec::default().max_len(4).decode("not a cursor"),
+            Err(CursorError::CursorTooLong { len: 12, max: 4 })
+        ));
+    }
+}
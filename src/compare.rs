@@ -0,0 +1,474 @@
+use std::cmp::Ordering;
+
+use bson::{Bson, Document};
+
+use crate::error::CursorError;
+use crate::option::{cursor_key, sort_value};
+
+/// Smallest power of two no `i64` reaches, `2^63`.
+const I64_LIMIT: f64 = 9.223_372_036_854_776e18;
+/// Smallest `i64`, `-2^63`.
+const I64_MIN: f64 = -9.223_372_036_854_776e18;
+
+/// Compares the sort keys of `sort` of two documents, or of a document and an
+/// [`Edge`](crate::Edge), in the order of the sort. A missing key compares like `null`, as
+/// `MongoDB` sorts it.
+pub(crate) fn compare_keys(
+    sort: &Document,
+    document: &Document,
+    other: &Document,
+) -> Result<Ordering, CursorError> {
+    for key in sort.keys() {
+        let field = cursor_key(key);
+        let ordering = compare_values(
+            sort_value(document, field).unwrap_or(&Bson::Null),
+            sort_value(other, field).unwrap_or(&Bson::Null),
+        );
+        let ordering = if crate::sort_direction(sort, key)? < 0 {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        if ordering.is_ne() {
+            return Ok(ordering);
+        }
+    }
+    Ok(Ordering::Equal)
+}
+
+/// Compares two values in the BSON comparison order of `MongoDB`: values of different types
+/// compare by the rank of their type, numbers of different types by their value. Strings
+/// compare by their bytes, as without a collation.
+pub(crate) fn compare_values(value: &Bson, other: &Bson) -> Ordering {
+    let by_type = type_rank(value).cmp(&type_rank(other));
+    if by_type.is_ne() {
+        return by_type;
+    }
+    match (value, other) {
+        (Bson::String(value) | Bson::Symbol(value), Bson::String(other) | Bson::Symbol(other))
+        | (Bson::JavaScriptCode(value), Bson::JavaScriptCode(other)) => value.cmp(other),
+        (Bson::Document(value), Bson::Document(other)) => compare_documents(value, other),
+        (Bson::Array(value), Bson::Array(other)) => value
+            .iter()
+            .zip(other)
+            .map(|(value, other)| compare_values(value, other))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| value.len().cmp(&other.len())),
+        (Bson::Binary(value), Bson::Binary(other)) => value
+            .bytes
+            .len()
+            .cmp(&other.bytes.len())
+            .then_with(|| u8::from(value.subtype).cmp(&u8::from(other.subtype)))
+            .then_with(|| value.bytes.cmp(&other.bytes)),
+        (Bson::ObjectId(value), Bson::ObjectId(other)) => value.cmp(other),
+        (Bson::Boolean(value), Bson::Boolean(other)) => value.cmp(other),
+        (Bson::DateTime(value), Bson::DateTime(other)) => value.cmp(other),
+        (Bson::Timestamp(value), Bson::Timestamp(other)) => {
+            (value.time, value.increment).cmp(&(other.time, other.increment))
+        }
+        (Bson::RegularExpression(value), Bson::RegularExpression(other)) => value
+            .pattern
+            .cmp(&other.pattern)
+            .then_with(|| value.options.cmp(&other.options)),
+        (Bson::JavaScriptCodeWithScope(value), Bson::JavaScriptCodeWithScope(other)) => value
+            .code
+            .cmp(&other.code)
+            .then_with(|| compare_documents(&value.scope, &other.scope)),
+        _ => compare_numbers(value, other),
+    }
+}
+
+/// Compares the fields of two documents in order, each by the type of its value, its name and
+/// its value. A document that is a prefix of the other comes first.
+fn compare_documents(document: &Document, other: &Document) -> Ordering {
+    document
+        .iter()
+        .zip(other)
+        .map(|((key, value), (other_key, other_value))| {
+            type_rank(value)
+                .cmp(&type_rank(other_value))
+                .then_with(|| key.cmp(other_key))
+                .then_with(|| compare_values(value, other_value))
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| document.len().cmp(&other.len()))
+}
+
+/// Compares two numbers by their value, exactly except for a `Decimal128` and a double. `NaN`
+/// comes before every other number. Values that aren't numbers compare equal.
+fn compare_numbers(value: &Bson, other: &Bson) -> Ordering {
+    match (number(value), number(other)) {
+        (Some(Number::Int(value)), Some(Number::Int(other))) => value.cmp(&other),
+        (Some(Number::Decimal(value)), Some(Number::Decimal(other))) => value.cmp(&other),
+        (Some(Number::Int(value)), Some(Number::Decimal(other))) => {
+            Decimal::from_int(value).cmp(&other)
+        }
+        (Some(Number::Decimal(value)), Some(Number::Int(other))) => {
+            value.cmp(&Decimal::from_int(other))
+        }
+        (Some(Number::Decimal(value)), Some(Number::Double(other))) => {
+            compare_double_decimal(other, &value).reverse()
+        }
+        (Some(Number::Double(value)), Some(Number::Decimal(other))) => {
+            compare_double_decimal(value, &other)
+        }
+        (Some(Number::Int(value)), Some(Number::Double(other))) => compare_int_double(value, other),
+        (Some(Number::Double(value)), Some(Number::Int(other))) => {
+            compare_int_double(other, value).reverse()
+        }
+        (Some(Number::Double(value)), Some(Number::Double(other))) => compare_doubles(value, other),
+        _ => Ordering::Equal,
+    }
+}
+
+fn compare_doubles(value: f64, other: f64) -> Ordering {
+    match (value.is_nan(), other.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => value.partial_cmp(&other).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// Compares a double to a finite decimal, which is smaller than an infinite double even if its
+/// closest double is infinite.
+fn compare_double_decimal(double: f64, decimal: &Decimal) -> Ordering {
+    if double.is_infinite() {
+        return if double.is_sign_positive() {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        };
+    }
+    compare_doubles(double, decimal.to_double())
+}
+
+/// A number of any of the BSON number types.
+enum Number {
+    Int(i64),
+    Double(f64),
+    Decimal(Decimal),
+}
+
+/// A finite `Decimal128` as the digits of its significand and the position of its decimal point,
+/// `0.digits * 10^exponent`, so decimals with different precisions compare by value.
+#[derive(PartialEq, Eq)]
+struct Decimal {
+    negative: bool,
+    /// Without leading or trailing zeros, empty for zero
+    digits: Vec<u8>,
+    exponent: i64,
+}
+
+impl Decimal {
+    /// Parses the string form of a finite `Decimal128`, e.g. `-1.50E+3`.
+    fn parse(decimal: &str) -> Option<Self> {
+        let (negative, unsigned) = decimal
+            .strip_prefix('-')
+            .map_or((false, decimal), |unsigned| (true, unsigned));
+        let (significand, exponent) = unsigned
+            .split_once(['E', 'e'])
+            .map_or((unsigned, Some(0)), |(significand, exponent)| {
+                (significand, exponent.parse::<i64>().ok())
+            });
+        let (whole, fraction) = significand.split_once('.').unwrap_or((significand, ""));
+        if !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|byte| byte.is_ascii_digit())
+        {
+            return None;
+        }
+        let mut exponent = exponent?.checked_add(i64::try_from(whole.len()).ok()?)?;
+        let mut digits: Vec<u8> = whole.bytes().chain(fraction.bytes()).collect();
+        let leading = digits.iter().take_while(|digit| **digit == b'0').count();
+        digits.drain(..leading);
+        exponent = exponent.checked_sub(i64::try_from(leading).ok()?)?;
+        while digits.last() == Some(&b'0') {
+            digits.pop();
+        }
+        if digits.is_empty() {
+            return Some(Self {
+                negative: false,
+                digits,
+                exponent: 0,
+            });
+        }
+        Some(Self {
+            negative,
+            digits,
+            exponent,
+        })
+    }
+
+    fn from_int(int: i64) -> Self {
+        Self::parse(&int.to_string()).unwrap_or(Self {
+            negative: false,
+            digits: Vec::new(),
+            exponent: 0,
+        })
+    }
+
+    /// Returns the closest double.
+    fn to_double(&self) -> f64 {
+        let digits = String::from_utf8_lossy(&self.digits);
+        let sign = if self.negative { "-" } else { "" };
+        format!("{sign}0.{digits}e{}", self.exponent)
+            .parse()
+            .unwrap_or(0.0_f64)
+    }
+
+    /// Orders zero between the negative and the positive decimals.
+    fn sign(&self) -> i8 {
+        if self.digits.is_empty() {
+            0
+        } else if self.negative {
+            -1
+        } else {
+            1
+        }
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let by_sign = self.sign().cmp(&other.sign());
+        if by_sign.is_ne() {
+            return by_sign;
+        }
+        // Without leading zeros a larger exponent means a larger magnitude, without trailing
+        // zeros a shorter equal prefix a smaller one.
+        let magnitude = self
+            .exponent
+            .cmp(&other.exponent)
+            .then_with(|| self.digits.cmp(&other.digits));
+        if self.negative {
+            magnitude.reverse()
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Returns the value of a number. Finite `Decimal128` values compare exactly with integers and
+/// each other, with doubles as the closest double.
+fn number(value: &Bson) -> Option<Number> {
+    if let Some(value) = value.as_i64() {
+        return Some(Number::Int(value));
+    }
+    if let Some(value) = value.as_i32() {
+        return Some(Number::Int(i64::from(value)));
+    }
+    if let Some(value) = value.as_f64() {
+        return Some(Number::Double(value));
+    }
+    if let Bson::Decimal128(value) = value {
+        let value = value.to_string();
+        return Decimal::parse(&value)
+            .map(Number::Decimal)
+            .or_else(|| value.parse().ok().map(Number::Double));
+    }
+    None
+}
+
+fn compare_int_double(int: i64, double: f64) -> Ordering {
+    if double.is_nan() {
+        return Ordering::Greater;
+    }
+    if double >= I64_LIMIT {
+        return Ordering::Less;
+    }
+    if double < I64_MIN {
+        return Ordering::Greater;
+    }
+    // In range of `i64` and without a fraction, so the conversion is exact.
+    #[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
+    let whole = double.trunc() as i64;
+    int.cmp(&whole).then_with(|| {
+        0.0_f64
+            .partial_cmp(&double.fract())
+            .unwrap_or(Ordering::Equal)
+    })
+}
+
+/// Returns the rank of the type of `value` in the BSON comparison order.
+const fn type_rank(value: &Bson) -> u8 {
+    match value {
+        Bson::MinKey => 0,
+        Bson::Null | Bson::Undefined => 1,
+        Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_) | Bson::Decimal128(_) => 2,
+        Bson::String(_) | Bson::Symbol(_) => 3,
+        Bson::Document(_) => 4,
+        Bson::Array(_) => 5,
+        Bson::Binary(_) => 6,
+        Bson::ObjectId(_) => 7,
+        Bson::Boolean(_) => 8,
+        Bson::DateTime(_) => 9,
+        Bson::Timestamp(_) => 10,
+        Bson::RegularExpression(_) => 11,
+        Bson::DbPointer(_) => 12,
+        Bson::JavaScriptCode(_) => 13,
+        Bson::JavaScriptCodeWithScope(_) => 14,
+        Bson::MaxKey => 15,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use std::str::FromStr;
+
+    use bson::{doc, Bson, Decimal128};
+
+    use super::{compare_keys, compare_values, Decimal};
+
+    fn decimal(decimal: &str) -> Bson {
+        Bson::Decimal128(
+            Decimal128::from_str(decimal).unwrap_or_else(|_| Decimal128::from_bytes([0; 16])),
+        )
+    }
+
+    #[test]
+    fn orders_types_by_rank() {
+        let ascending = [
+            Bson::MinKey,
+            Bson::Null,
+            Bson::Int32(5),
+            Bson::String("a".to_owned()),
+            Bson::Document(doc! {}),
+            Bson::Array(Vec::new()),
+            Bson::Boolean(false),
+            Bson::MaxKey,
+        ];
+        assert!(ascending
+            .iter()
+            .zip(ascending.iter().skip(1))
+            .all(|(value, next)| compare_values(value, next) == Ordering::Less));
+        assert_eq!(
+            compare_values(&Bson::Null, &Bson::Undefined),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn compares_numbers_across_types() {
+        assert_eq!(
+            compare_values(&Bson::Int32(2), &Bson::Int64(2)),
+            Ordering::Equal
+        );
+        assert_eq!(
+            compare_values(&Bson::Int64(2), &Bson::Double(2.5)),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_values(&Bson::Double(-0.5), &Bson::Int32(0)),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_values(&Bson::Int64(i64::MAX), &Bson::Double(9.3e18)),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_values(&Bson::Double(f64::NAN), &Bson::Int64(i64::MIN)),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn compares_arrays_and_documents_element_wise() {
+        assert_eq!(
+            compare_values(
+                &Bson::from(vec![1_i32, 2_i32]),
+                &Bson::from(vec![1_i32, 2_i32, 0_i32])
+            ),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_values(
+                &Bson::from(doc! { "a": 2_i32 }),
+                &Bson::from(doc! { "b": 1_i32 })
+            ),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_values(
+                &Bson::from(doc! { "a": 1_i32 }),
+                &Bson::from(doc! { "a": "x" })
+            ),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn compares_dotted_keys_of_items_and_edges() {
+        let sort = doc! { "author.name": 1_i32, "_id": 1_i32 };
+        let item = doc! { "author": { "name": "b" }, "_id": 1_i32 };
+        let edge = doc! { "author.name": "a", "_id": 2_i32 };
+        assert_eq!(
+            compare_keys(&sort, &item, &edge).ok(),
+            Some(Ordering::Greater)
+        );
+    }
+
+    #[test]
+    fn compares_keys_in_sort_order() {
+        let sort = doc! { "group": -1_i32, "_id": 1_i32 };
+        let first = doc! { "group": 2_i32, "_id": 5_i32 };
+        let second = doc! { "group": 1_i32, "_id": 1_i32 };
+        let missing = doc! { "_id": 0_i32 };
+        assert_eq!(
+            compare_keys(&sort, &first, &second).ok(),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare_keys(&sort, &second, &missing).ok(),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare_keys(&sort, &first, &first).ok(),
+            Some(Ordering::Equal)
+        );
+    }
+
+    #[test]
+    fn compares_decimals_exactly() {
+        assert_eq!(
+            compare_values(&decimal("1.50"), &Bson::Double(1.5)),
+            Ordering::Equal
+        );
+        assert_eq!(
+            compare_values(&decimal("2"), &Bson::Int32(2)),
+            Ordering::Equal
+        );
+        assert_eq!(
+            compare_values(&decimal("-1E+3"), &Bson::Int32(-999)),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_values(&decimal("1E+400"), &Bson::Double(f64::INFINITY)),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn parses_decimals_by_value() {
+        assert!(Decimal::parse("1.50E+3") == Decimal::parse("1500"));
+        assert!(Decimal::parse("0.0") == Decimal::parse("-0E-5"));
+        assert!(Decimal::parse("-12.5") < Decimal::parse("-1.25"));
+        assert!(Decimal::parse("0.001") < Decimal::parse("0.01"));
+        assert!(Decimal::parse("1e1") == Some(Decimal::from_int(10)));
+        assert!(Decimal::parse("Infinity").is_none());
+        assert!(Decimal::parse("NaN").is_none());
+        assert!(Decimal::parse("1E+x").is_none());
+        assert_eq!(
+            Decimal::parse("-2.5E-1").map(|decimal| decimal.to_double()),
+            Some(-0.25_f64)
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt;
+use std::str::FromStr;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bson::{doc, Bson, Document};
+
+use crate::codec::CursorCodec;
+use crate::error::CursorError;
+use crate::merge::MergedPage;
+use crate::model::{DirectedCursor, Edge};
+
+/// Key of the position array inside an encoded [`CompositeCursor`].
+const SOURCES: &str = "sources";
+
+/// The positions of several sources in one cursor, e.g. for a gateway paginating over the
+/// backends merged with [`merge_pages`](crate::merge_pages) behind a single client-facing
+/// cursor. It encodes into one opaque string with `to_string` and decodes with `parse`; the
+/// positions are split back out with [`CompositeCursor::split`]. Those strings are neither signed
+/// nor scoped, and a paginator whose [`CursorCodec`] would verify its cursors rejects the
+/// positions parsed from them. Encode and decode with [`CompositeCursor::encode_with`] and
+/// [`CompositeCursor::decode_with`] for such a codec instead.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CompositeCursor {
+    cursors: Vec<Option<DirectedCursor>>,
+}
+
+impl CompositeCursor {
+    /// Combines the cursors of the sources, `None` for a source at its first page.
+    #[must_use]
+    pub const fn new(cursors: Vec<Option<DirectedCursor>>) -> Self {
+        Self { cursors }
+    }
+
+    /// Returns the cursor of every source, in the order of the sources. The cursor is empty
+    /// before the first page, so every source starts at its first page.
+    #[must_use]
+    pub fn cursors(&self) -> &[Option<DirectedCursor>] {
+        &self.cursors
+    }
+
+    /// Returns the cursor of the source at `index`, `None` for a source at its first page.
+    #[must_use]
+    pub fn source(&self, index: usize) -> Option<&DirectedCursor> {
+        self.cursors.get(index)?.as_ref()
+    }
+
+    /// Splits the cursor into the cursors of the sources.
+    #[must_use]
+    pub fn split(self) -> Vec<Option<DirectedCursor>> {
+        self.cursors
+    }
+
+    /// Encodes the cursor with `codec`, so it is signed, scoped and carries the claims of the
+    /// codec like the cursors of a single source.
+    pub fn encode_with(&self, codec: &CursorCodec) -> Result<String, CursorError> {
+        codec.encode_document(self.to_document())
+    }
+
+    /// Decodes a cursor encoded with [`CompositeCursor::encode_with`], verifying its signature,
+    /// scope and claims with `codec`.
+    pub fn decode_with(codec: &CursorCodec, cursor: &str) -> Result<Self, CursorError> {
+        Self::from_document(&codec.decode_document(cursor)?)
+    }
+
+    fn to_document(&self) -> Document {
+        let sources: Vec<Bson> = self
+            .cursors
+            .iter()
+            .map(|cursor| match cursor {
+                Some(DirectedCursor::Forward(edge)) => {
+                    Bson::Document(doc! { "forward": true, "edge": edge.to_document() })
+                }
+                Some(DirectedCursor::Backwards(edge)) => {
+                    Bson::Document(doc! { "forward": false, "edge": edge.to_document() })
+                }
+                None => Bson::Null,
+            })
+            .collect();
+        doc! { SOURCES: sources }
+    }
+
+    fn from_document(document: &Document) -> Result<Self, CursorError> {
+        let cursors = document
+            .get_array(SOURCES)?
+            .iter()
+            .map(|source| {
+                if let Bson::Null = source {
+                    return Ok(None);
+                }
+                let source = source.as_document().ok_or(CursorError::InvalidCursor)?;
+                let edge = Edge::from_document(source.get_document("edge")?.clone());
+                Ok(Some(if source.get_bool("forward")? {
+                    DirectedCursor::Forward(edge)
+                } else {
+                    DirectedCursor::Backwards(edge)
+                }))
+            })
+            .collect::<Result<_, CursorError>>()?;
+        Ok(Self { cursors })
+    }
+}
+
+impl<T> MergedPage<T> {
+    /// Returns the cursor of the next merged page, combining [`MergedPage::cursors`].
+    #[must_use]
+    pub fn composite_cursor(&self) -> CompositeCursor {
+        CompositeCursor::new(self.cursors.clone())
+    }
+}
+
+impl fmt::Display for CompositeCursor {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = bson::to_vec(&self.to_document()).map_err(|_error| fmt::Error)?;
+        fmt.write_str(&URL_SAFE_NO_PAD.encode(bytes))
+    }
+}
+
+impl FromStr for CompositeCursor {
+    type Err = CursorError;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(str)
+            .map_err(|_decode| CursorError::InvalidCursor)?;
+        let cursors = Self::from_document(&bson::from_slice(&bytes)?)?
+            .cursors
+            .into_iter()
+            .map(|cursor| cursor.map(|cursor| cursor.map_edge(Edge::unverified)))
+            .collect();
+        Ok(Self { cursors })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::doc;
+
+    use super::CompositeCursor;
+    use crate::codec::CursorCodec;
+    use crate::error::CursorError;
+    use crate::model::{DirectedCursor, Edge};
+
+    fn composite() -> CompositeCursor {
+        let edge = Edge::from_document(doc! { "name": "a", "_id": 1_i64 });
+        CompositeCursor::new(vec![
+            Some(DirectedCursor::Forward(edge.clone())),
+            None,
+            Some(DirectedCursor::Backwards(edge)),
+        ])
+    }
+
+    #[test]
+    fn round_trips_through_a_codec() {
+        let codec = CursorCodec::default().scope("a");
+        let encoded = composite().encode_with(&codec).unwrap_or_default();
+        let decoded = CompositeCursor::decode_with(&codec, &encoded).ok();
+        assert_eq!(decoded, Some(composite()));
+        assert!(decoded.is_some_and(|decoded| decoded
+            .cursors()
+            .iter()
+            .flatten()
+            .all(|cursor| !cursor.inner().is_unverified())));
+        assert!(matches!(
+            CompositeCursor::decode_with(&CursorCodec::default().scope("b"), &encoded),
+            Err(CursorError::ScopeMismatch)
+        ));
+    }
+
+    #[test]
+    fn parsed_positions_are_unverified() {
+        let parsed = composite().to_string().parse::<CompositeCursor>().ok();
+        assert_eq!(parsed, Some(composite()));
+        assert!(parsed.is_some_and(|parsed| parsed
+            .cursors()
+            .iter()
+            .flatten()
+            .all(|cursor| cursor.inner().is_unverified())));
+    }
+}
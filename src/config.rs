@@ -0,0 +1,951 @@
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bson::spec::BinarySubtype;
+use bson::{doc, Bson, Document};
+use mongodb::options::{FindOptions, Hint};
+use mongodb::{ClientSession, Collection};
+use serde::de::DeserializeOwned;
+
+use crate::adaptive::AdaptiveLimit;
+use crate::cache::PageCache;
+use crate::changes;
+use crate::codec::CursorCodec;
+use crate::error::CursorError;
+use crate::event::{LogObserver, PaginationEvent, PaginationObserver};
+use crate::grouped;
+use crate::link::PageLinks;
+use crate::metrics::PaginationMetrics;
+use crate::partition::{self, PageStream};
+#[cfg(feature = "tokio")]
+use crate::prefetch::Prefetcher;
+use crate::retry::{retry, RetryPolicy};
+use crate::session;
+use crate::write;
+use crate::{
+    Count, DirectedCursor, Edge, FindResult, GroupedPages, PageInfo, Pagination, QueryPlan,
+};
+
+/// Settings applied to every query of a [`Paginator`].
+/// Start from [`PaginationConfig::default`] and chain the setters.
+#[derive(Clone, Default)]
+#[non_exhaustive]
+#[allow(clippy::struct_excessive_bools)] // Independent switches, set through the setters only
+pub struct PaginationConfig {
+    pub(crate) metrics: Option<Arc<dyn PaginationMetrics>>,
+    pub(crate) observer: Option<Arc<dyn PaginationObserver>>,
+    pub(crate) collect_stats: bool,
+    pub(crate) etags: bool,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) max_time: Option<Duration>,
+    pub(crate) no_cursor_timeout: Option<bool>,
+    pub(crate) deadline: Option<Duration>,
+    pub(crate) batch_size: BatchSizePolicy,
+    pub(crate) comment: Option<String>,
+    pub(crate) count_hint: Option<Hint>,
+    pub(crate) count_mode: CountMode,
+    #[cfg(feature = "tokio")]
+    pub(crate) prefetcher: Option<Prefetcher>,
+    pub(crate) cache: Option<Arc<dyn PageCache>>,
+    pub(crate) ambient_filter: Option<Document>,
+    pub(crate) codec: CursorCodec,
+    pub(crate) offsets: bool,
+    pub(crate) page_numbers: bool,
+    pub(crate) reverse_results: bool,
+    pub(crate) skip: SkipPolicy,
+    pub(crate) missing_limit: MissingLimit,
+    pub(crate) unique_sort: bool,
+    pub(crate) collect_mismatched: bool,
+    pub(crate) minimize_projection: bool,
+    pub(crate) has_page: HasPageStrategy,
+    pub(crate) backward_start: BackwardStart,
+    pub(crate) align_pages: bool,
+    pub(crate) change_key: Option<String>,
+    pub(crate) insertion_key: Option<String>,
+    pub(crate) immutable_fields: Option<Vec<String>>,
+    pub(crate) drift_window: Option<usize>,
+    pub(crate) adaptive_limit: Option<AdaptiveLimit>,
+    pub(crate) max_page_bytes: Option<usize>,
+    pub(crate) max_batches: Option<u32>,
+    pub(crate) chunk_size: Option<u32>,
+    pub(crate) empty_page_retries: u32,
+    pub(crate) sort_audit: SortAudit,
+    pub(crate) binary_subtypes: Vec<(String, BinarySubtype)>,
+}
+
+/// Combines two filters so that both have to match.
+fn and(left: Document, right: Document) -> Document {
+    doc! { "$and": [left, right] }
+}
+
+/// Whether `filter` was merged with `ambient` by [`PaginationConfig::scope`].
+fn is_scoped(ambient: &Document, filter: &Document) -> bool {
+    matches!(
+        filter.get_array("$and").map(|all| all.first()),
+        Ok(Some(Bson::Document(first))) if first == ambient
+    )
+}
+
+/// How [`FindResult::total_count`] is computed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CountMode {
+    /// Counts every matching item, which can be slow on large collections.
+    #[default]
+    Exact,
+    /// Estimates the size of the whole collection from its metadata, ignoring the filter.
+    Estimate,
+    /// Counts matching items, but stops once more than the bound are found.
+    UpTo(u64),
+    /// Does not count the items at all.
+    Skip,
+}
+
+/// How the driver `batch_size` is chosen when the find options don't set one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BatchSizePolicy {
+    /// Uses `limit + 1`, so a whole page arrives in a single batch without a `getMore`.
+    #[default]
+    FitPage,
+    /// Leaves the batch size to the driver, which returns at most 101 documents in the first batch.
+    Driver,
+}
+
+/// What a `skip` in the find options means when a cursor is given as well.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SkipPolicy {
+    /// Fails with [`CursorError::SkipWithCursor`], a skip only applies to the first page.
+    #[default]
+    Reject,
+    /// Skips that many items after a forward cursor, e.g. to jump ahead. A backwards cursor
+    /// with a skip fails with [`CursorError::InvalidCombination`], aligned or refilled pages and
+    /// the page flags wouldn't account for the skipped items.
+    AfterCursor,
+}
+
+/// What a page without a `limit` in the find options holds, see
+/// [`PaginationConfig::missing_limit`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MissingLimit {
+    /// Returns every matching item on a single page. Bound the query with
+    /// [`PaginationConfig::max_batches`] or [`PaginationConfig::max_page_bytes`], or use
+    /// [`MissingLimit::UpTo`] instead, so a large collection isn't read at once.
+    #[default]
+    Unlimited,
+    /// Returns every matching item on a single page, but fails with
+    /// [`CursorError::TooManyItems`] once more than this many items match.
+    UpTo(u32),
+    /// Uses this many items as the limit.
+    DefaultTo(u32),
+    /// Fails with [`CursorError::MissingLimit`] before running any query.
+    Reject,
+}
+
+impl MissingLimit {
+    /// Returns the limit a page without one gets. [`MissingLimit::UpTo`] fetches one item more
+    /// to notice a page exceeding it.
+    pub(crate) fn limit(self) -> Option<i64> {
+        match self {
+            Self::DefaultTo(limit) => Some(i64::from(limit)),
+            Self::UpTo(max_items) => Some(i64::from(max_items).saturating_add(1)),
+            Self::Unlimited | Self::Reject => None,
+        }
+    }
+}
+
+/// How the `has_next_page` and `has_previous_page` flags of [`PageInfo`] are determined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HasPageStrategy {
+    /// Peeks past both ends of the page with one extra query each.
+    #[default]
+    ExtraQuery,
+    /// Fetches one item more than the limit to tell if there is a page in the direction of the
+    /// cursor. The flag of the other direction is unknown whenever the page starts at a cursor.
+    OverFetch,
+    /// Does not look for other pages at all, both flags are unknown.
+    Skip,
+}
+
+/// What a backwards page reaching the start holds when fewer items are left than the limit,
+/// see [`PaginationConfig::backward_start`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BackwardStart {
+    /// Returns the items left as a partial page.
+    #[default]
+    Partial,
+    /// Returns the full first page instead, as if paging from the start. Only pages known to
+    /// reach the start are refilled, so never with [`HasPageStrategy::Skip`].
+    Refill,
+}
+
+/// What happens when items of a page share all their sort keys, see
+/// [`PaginationConfig::audit_sort`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SortAudit {
+    /// Does not look for shared sort keys.
+    #[default]
+    Off,
+    /// Emits a [`PaginationEvent::UnstableSort`] and returns the page.
+    Warn,
+    /// Fails the page with [`CursorError::UnstableSort`].
+    Deny,
+}
+
+impl PaginationConfig {
+    /// Reports measurements of every query to `metrics`.
+    #[must_use]
+    pub fn metrics<M: PaginationMetrics + 'static>(mut self, metrics: M) -> Self {
+        self.metrics = Some(Arc::new(metrics));
+        self
+    }
+
+    /// Routes every [`PaginationEvent`] to `observer` instead of logging it with [`LogObserver`].
+    #[must_use]
+    pub fn on_event<O: PaginationObserver + 'static>(mut self, observer: O) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Populates [`FindResult::etag`] with a hash of the items and flags of every page, so an
+    /// HTTP layer can answer a request for an unchanged page with 304 Not Modified.
+    #[must_use]
+    pub const fn etags(mut self, etags: bool) -> Self {
+        self.etags = etags;
+        self
+    }
+
+    /// Populates [`FindResult::stats`] with the timings and sizes of the queries.
+    #[must_use]
+    pub const fn collect_stats(mut self, collect_stats: bool) -> Self {
+        self.collect_stats = collect_stats;
+        self
+    }
+
+    /// Retries the find and count queries on transient errors according to `policy`.
+    #[must_use]
+    pub const fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Overrides `max_time` of the find options, applying it to the find, count and
+    /// `has_page` queries alike.
+    #[must_use]
+    pub const fn max_time(mut self, max_time: Duration) -> Self {
+        self.max_time = Some(max_time);
+        self
+    }
+
+    /// Sets `no_cursor_timeout` of the finds whose options don't set it, e.g. for batch jobs
+    /// reading pages so large that the server would time out the cursor of a page after ten
+    /// minutes of inactivity between two of its batches. The cursor of a page is always closed
+    /// once the page is read, so it can't leak; set a [`max_time`](Self::max_time) to still
+    /// bound a single page.
+    #[must_use]
+    pub const fn no_cursor_timeout(mut self, no_cursor_timeout: bool) -> Self {
+        self.no_cursor_timeout = Some(no_cursor_timeout);
+        self
+    }
+
+    /// Fails a whole `find_paginated` call with [`CursorError::DeadlineExceeded`] if all of its
+    /// queries together, including retries, take longer than `deadline`.
+    #[must_use]
+    pub const fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Chooses the driver `batch_size` with `policy` unless the find options set one.
+    #[must_use]
+    pub const fn batch_size_policy(mut self, policy: BatchSizePolicy) -> Self {
+        self.batch_size = policy;
+        self
+    }
+
+    /// Determines the page flags of [`PageInfo`] according to `strategy`.
+    #[must_use]
+    pub const fn has_page_strategy(mut self, strategy: HasPageStrategy) -> Self {
+        self.has_page = strategy;
+        self
+    }
+
+    /// Decides with `policy` what a backwards page reaching the start holds, e.g.
+    /// [`BackwardStart::Refill`] for a full first page after going back past the start.
+    #[must_use]
+    pub const fn backward_start(mut self, policy: BackwardStart) -> Self {
+        self.backward_start = policy;
+        self
+    }
+
+    /// Keeps the pages aligned to multiples of the limit counted from the first item, also when
+    /// paging through cursors, so paging back after a skip returns the same pages as paging
+    /// forward from the start. A page starting off a boundary, e.g. after a skip of 4 with a
+    /// limit of 3, ends at the next boundary and holds fewer items. The cursors carry their
+    /// offsets, see [`Edge::offset`]; the offset of a cursor without one is counted once.
+    ///
+    /// [`Edge::offset`]: crate::Edge::offset
+    #[must_use]
+    pub const fn align_pages(mut self, align_pages: bool) -> Self {
+        self.align_pages = align_pages;
+        self
+    }
+
+    /// Decides with `policy` how a `skip` combines with a cursor.
+    #[must_use]
+    pub const fn skip_policy(mut self, policy: SkipPolicy) -> Self {
+        self.skip = policy;
+        self
+    }
+
+    /// Decides with `policy` what a page holds when the find options set no `limit`, e.g.
+    /// [`MissingLimit::DefaultTo`] to serve pages of a default size or [`MissingLimit::Reject`]
+    /// to make every caller choose one. By default such a page holds every matching item.
+    #[must_use]
+    pub const fn missing_limit(mut self, policy: MissingLimit) -> Self {
+        self.missing_limit = policy;
+        self
+    }
+
+    /// Declares that the sort keys are unique, e.g. a single key with a unique index like
+    /// `email` or several keys forming a unique compound index, so `_id` isn't appended to the
+    /// sort as a tiebreak and cursors hold the sort keys only. This keeps the keyset filter tight
+    /// and allows index-only plans, but items with equal sort keys would be skipped.
+    #[must_use]
+    pub const fn unique_sort(mut self, unique_sort: bool) -> Self {
+        self.unique_sort = unique_sort;
+        self
+    }
+
+    /// Derives the limit of every page from the byte budget of `adaptive`, see [`AdaptiveLimit`].
+    #[must_use]
+    pub fn adaptive_limit(mut self, adaptive: AdaptiveLimit) -> Self {
+        self.adaptive_limit = Some(adaptive);
+        self
+    }
+
+    /// Fails a page with [`CursorError::TooManyBatches`] once its find needs more than
+    /// `max_batches` driver batches, e.g. to stop a query without a limit from reading a whole
+    /// collection. Batches are counted from the batch size, see [`PageStats::batches`]; the
+    /// query stops after at most one further batch. Finds without a batch size get the 101
+    /// documents of the first batch of the server as their batch size, as later batches are
+    /// only bounded by their byte size.
+    ///
+    /// [`PageStats::batches`]: crate::PageStats::batches
+    #[must_use]
+    pub const fn max_batches(mut self, max_batches: u32) -> Self {
+        self.max_batches = Some(max_batches);
+        self
+    }
+
+    /// Fetches every page in chunks of at most `chunk_size` items, e.g. for pages of thousands of
+    /// items. Every chunk is a find query of its own that continues right after the last item of
+    /// the previous chunk, like the next page would, so no single query returns more than a
+    /// chunk and dropping the future, e.g. on a [`PaginationConfig::deadline`], cancels between
+    /// chunks. The items of all chunks are still held until the page is returned, bound its
+    /// memory with [`PaginationConfig::max_page_bytes`], which also stops fetching early.
+    /// [`PaginationConfig::max_batches`] applies to every chunk. Like between pages, items
+    /// written while the chunks are fetched may be missed or show up. Tailable cursors are
+    /// never chunked.
+    #[must_use]
+    pub const fn chunk_size(mut self, chunk_size: u32) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Ends a page early once the BSON size of its items would exceed `max_bytes`, e.g. to stay
+    /// below the message size limit of a downstream system. The cursors of the page point to the
+    /// last item kept, so the next page continues with the first item left out. A page holds at
+    /// least one item, even if that item alone is larger.
+    #[must_use]
+    pub const fn max_page_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_page_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Moves on to the following page, up to `max_retries` times, while a page comes back without
+    /// items although the flag in its direction reports more, e.g. as every item of the page
+    /// failed to deserialize with [`PaginationConfig::collect_mismatched`]. The page returned is
+    /// the first one holding items or the last one tried; its cursors continue from there. Pages
+    /// whose flag is unknown, e.g. with [`HasPageStrategy::Skip`], are returned as they are.
+    #[must_use]
+    pub const fn skip_empty_pages(mut self, max_retries: u32) -> Self {
+        self.empty_page_retries = max_retries;
+        self
+    }
+
+    /// Records the latest value of `key`, e.g. an `updated_at` date set on every write, in the
+    /// cursors as a high-water mark, see [`Edge::high_water_mark`]. After walking every page,
+    /// [`Paginator::find_changes_since`] finds the items written later for an incremental sync.
+    /// The key has to be fetched, so a projection has to include it.
+    #[must_use]
+    pub fn change_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.change_key = Some(key.into());
+        self
+    }
+
+    /// Makes walks over every page tolerant of concurrent inserts by leaving out the items
+    /// inserted after the first page. `key` has to be immutable and grow with every insert, like
+    /// an `ObjectId` `_id` from a single client or a creation date. The first page looks up the
+    /// latest value of `key` among the matching items, which the cursors carry as the insertion
+    /// bound, see [`Edge::insertion_bound`]. Pages without a bound, e.g. of a cursor issued
+    /// before, start a new bound. Items without `key` or with a `null` one are never left out.
+    ///
+    /// [`Edge::insertion_bound`]: crate::Edge::insertion_bound
+    #[must_use]
+    pub fn insertion_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.insertion_key = Some(key.into());
+        self
+    }
+
+    /// Declares the fields that never change once an item is inserted; `_id` always counts as
+    /// immutable. Every page sorting on another field emits a
+    /// [`PaginationEvent::MutableSortKey`], as an item updated between two pages moves within
+    /// the sort and may be skipped or repeated. Prefer sorts on immutable fields for pages of
+    /// busy collections.
+    #[must_use]
+    pub fn immutable_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.immutable_fields = Some(fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Declares that the items store the binary sort key `key` with `subtype`, e.g.
+    /// [`BinarySubtype::Uuid`] for UUIDs inserted as [`bson::Uuid`]. Cursors holding the key
+    /// as a binary of another subtype are converted before the query, as `MongoDB` orders binaries
+    /// by their subtype before their bytes. This happens for cursors of
+    /// [`Edge::from_item`](crate::Edge::from_item) with a type serializing a UUID as plain bytes.
+    /// With [`BinarySubtype::Uuid`] a UUID in its string form is converted as well.
+    #[must_use]
+    pub fn binary_subtype<S: Into<String>>(mut self, key: S, subtype: BinarySubtype) -> Self {
+        self.binary_subtypes.push((key.into(), subtype));
+        self
+    }
+
+    /// Detects items seen twice while walking pages sorted on a mutable field, e.g. `score`,
+    /// which happens when an item is updated between two pages, and sets
+    /// [`FindResult::drift_detected`](crate::FindResult::drift_detected) on the page. The cursor
+    /// continuing the walk, the `next_request` or for a backwards walk the `previous_request`,
+    /// carries the `_id`s of the last `window` items walked, so the sort has to hold `_id`.
+    #[must_use]
+    pub const fn detect_drift(mut self, window: usize) -> Self {
+        self.drift_window = Some(window);
+        self
+    }
+
+    /// Checks with `audit` that no two items of a page share all their sort keys, which happens
+    /// when the sort isn't total, e.g. with [`PaginationConfig::unique_sort`] on keys that aren't
+    /// unique. Items sharing their sort keys may be skipped or repeated across pages. Meant for
+    /// development, as it compares every item of every page.
+    #[must_use]
+    pub const fn audit_sort(mut self, audit: SortAudit) -> Self {
+        self.sort_audit = audit;
+        self
+    }
+
+    /// Collects items that don't deserialize into the item type in [`FindResult::mismatched`]
+    /// instead of failing the page, e.g. for a collection holding several document shapes read
+    /// into an internally tagged enum.
+    #[must_use]
+    pub const fn collect_mismatched(mut self, collect_mismatched: bool) -> Self {
+        self.collect_mismatched = collect_mismatched;
+        self
+    }
+
+    /// Fetches only the fields the item type deserializes, plus the sort keys, unless the find
+    /// options set a projection. See [`projection_of`](crate::projection_of) for which types
+    /// this works with, all fields are fetched for the others.
+    #[must_use]
+    pub const fn minimize_projection(mut self, minimize_projection: bool) -> Self {
+        self.minimize_projection = minimize_projection;
+        self
+    }
+
+    /// Replaces the comment every query is tagged with, e.g. `mongodb-cursor-pagination page=next limit=20`.
+    /// A comment set in the find options takes precedence over both.
+    #[must_use]
+    pub fn comment<S: Into<String>>(mut self, comment: S) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Uses `hint` for the count query instead of the hint of the find options, e.g. a smaller
+    /// partial index that covers the filter but not the sort.
+    #[must_use]
+    pub fn count_hint(mut self, hint: Hint) -> Self {
+        self.count_hint = Some(hint);
+        self
+    }
+
+    /// Computes [`FindResult::total_count`] according to `mode`.
+    #[must_use]
+    pub const fn count_mode(mut self, mode: CountMode) -> Self {
+        self.count_mode = mode;
+        self
+    }
+
+    /// Prefetches the next page after every page served, see [`Prefetcher`].
+    #[cfg(feature = "tokio")]
+    #[must_use]
+    pub fn prefetch(mut self, prefetcher: Prefetcher) -> Self {
+        self.prefetcher = Some(prefetcher);
+        self
+    }
+
+    /// Serves pages from `cache` when possible and caches every page fetched from `MongoDB`.
+    #[must_use]
+    pub fn cache<C: PageCache + 'static>(mut self, cache: C) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Merges `filter` into every query, including the count and `has_page` queries, e.g.
+    /// `{ "deleted_at": null }` or a tenant scope. Calling it again requires both filters to match.
+    #[must_use]
+    pub fn ambient_filter(mut self, filter: Document) -> Self {
+        self.ambient_filter = Some(match self.ambient_filter.take() {
+            Some(ambient) => and(ambient, filter),
+            None => filter,
+        });
+        self
+    }
+
+    /// Encodes and decodes cursors with `codec` in [`Paginator::encode_cursor`] and
+    /// [`Paginator::decode_cursor`]. The edges and cursors of the pages are encoded with it as
+    /// well when the page is built, a page whose cursors exceed its
+    /// [`CursorCodec::max_len`] fails with [`CursorError::CursorTooLong`]. Cursors parsed from a
+    /// string without it are rejected if it signs, scopes or adds claims to the cursors.
+    #[must_use]
+    pub fn cursor_codec(mut self, codec: CursorCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Annotates every [`Edge`] with its position among all matching items, see [`Edge::offset`].
+    /// The offset travels inside the cursors, so pages reached through cursors of pages fetched
+    /// without offsets have none.
+    #[must_use]
+    pub const fn offsets(mut self, offsets: bool) -> Self {
+        self.offsets = offsets;
+        self
+    }
+
+    /// Populates [`PageInfo::approx_page_number`] and [`PageInfo::approx_total_pages`] from the
+    /// limit, the offsets and the [`Count`], enabling [`PaginationConfig::offsets`] as well.
+    /// They are approximate as items may be inserted or deleted between pages.
+    ///
+    /// [`PageInfo::approx_page_number`]: crate::PageInfo::approx_page_number
+    /// [`PageInfo::approx_total_pages`]: crate::PageInfo::approx_total_pages
+    #[must_use]
+    pub const fn page_numbers(mut self, page_numbers: bool) -> Self {
+        self.page_numbers = page_numbers;
+        self
+    }
+
+    /// Returns the items and edges of every page in the opposite order of the sort, e.g. to fetch
+    /// a chat history newest first but display it oldest first. The cursors of [`PageInfo`] keep
+    /// their meaning: `end_cursor` still continues after the last item in sort order.
+    ///
+    /// [`PageInfo`]: crate::PageInfo
+    #[must_use]
+    pub const fn reverse_results(mut self, reverse_results: bool) -> Self {
+        self.reverse_results = reverse_results;
+        self
+    }
+
+    /// Merges the ambient filter into `filter`, unless `filter` was already scoped, e.g. the
+    /// filter of a [`QueryPlan`] passed on to [`Pagination::count_up_to`]. The ambient filter is
+    /// always wrapped in an `$and`, so nothing merged into the scoped filter can replace it.
+    pub(crate) fn scope(&self, filter: Option<Document>) -> Option<Document> {
+        match (self.ambient_filter.clone(), filter) {
+            (Some(ambient), Some(filter)) if is_scoped(&ambient, &filter) => Some(filter),
+            (Some(ambient), Some(filter)) if !filter.is_empty() => Some(and(ambient, filter)),
+            (Some(ambient), _) => Some(doc! { "$and": [ambient] }),
+            (None, filter) => filter,
+        }
+    }
+
+    pub(crate) fn emit(&self, event: &PaginationEvent) {
+        match &self.observer {
+            Some(observer) => observer.on_event(event),
+            None => LogObserver.on_event(event),
+        }
+    }
+
+    /// Runs a query on `collection` with the configured [`RetryPolicy`], reporting every retry.
+    pub(crate) async fn run<T, F, Fut>(
+        &self,
+        collection: &str,
+        operation: F,
+    ) -> Result<T, CursorError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, CursorError>>,
+    {
+        retry(self.retry.as_ref(), operation, |attempt, error| {
+            self.emit(&PaginationEvent::Retrying {
+                collection: collection.to_owned(),
+                attempt,
+                error: error.to_string(),
+            });
+        })
+        .await
+    }
+
+    /// Drops the pages of `collection` from the [`PageCache`] and the prefetcher, e.g. after
+    /// writing to it.
+    pub(crate) fn invalidate_page_sources<I>(&self, collection: &Collection<I>) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(&collection.namespace().to_string());
+        }
+        #[cfg(feature = "tokio")]
+        if let Some(prefetcher) = &self.prefetcher {
+            prefetcher.invalidate(collection);
+        }
+    }
+
+    /// Drops the [`PageCache`] and the prefetcher, so every page is read from the database.
+    pub(crate) fn drop_page_sources(&mut self) {
+        self.cache = None;
+        #[cfg(feature = "tokio")]
+        {
+            self.prefetcher = None;
+        }
+    }
+}
+
+impl fmt::Debug for PaginationConfig {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = fmt.debug_struct("PaginationConfig");
+        debug
+            .field("metrics", &self.metrics.is_some())
+            .field("observer", &self.observer.is_some())
+            .field("collect_stats", &self.collect_stats)
+            .field("etags", &self.etags)
+            .field("retry", &self.retry)
+            .field("max_time", &self.max_time)
+            .field("no_cursor_timeout", &self.no_cursor_timeout)
+            .field("deadline", &self.deadline)
+            .field("batch_size", &self.batch_size)
+            .field("skip", &self.skip)
+            .field("missing_limit", &self.missing_limit)
+            .field("unique_sort", &self.unique_sort)
+            .field("sort_audit", &self.sort_audit)
+            .field("change_key", &self.change_key)
+            .field("insertion_key", &self.insertion_key)
+            .field("immutable_fields", &self.immutable_fields)
+            .field("binary_subtypes", &self.binary_subtypes)
+            .field("drift_window", &self.drift_window)
+            .field("adaptive_limit", &self.adaptive_limit)
+            .field("max_page_bytes", &self.max_page_bytes)
+            .field("max_batches", &self.max_batches)
+            .field("chunk_size", &self.chunk_size)
+            .field("empty_page_retries", &self.empty_page_retries)
+            .field("collect_mismatched", &self.collect_mismatched)
+            .field("minimize_projection", &self.minimize_projection)
+            .field("has_page", &self.has_page)
+            .field("backward_start", &self.backward_start)
+            .field("align_pages", &self.align_pages)
+            .field("comment", &self.comment)
+            .field("count_hint", &self.count_hint)
+            .field("count_mode", &self.count_mode);
+        #[cfg(feature = "tokio")]
+        debug.field("prefetcher", &self.prefetcher);
+        debug
+            .field("cache", &self.cache.is_some())
+            .field("ambient_filter", &self.ambient_filter)
+            .field("codec", &self.codec)
+            .field("offsets", &self.offsets)
+            .field("page_numbers", &self.page_numbers)
+            .field("reverse_results", &self.reverse_results)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A [`Collection`] paired with the [`PaginationConfig`] used to paginate through it.
+///
+/// `Collection` itself implements [`Pagination`] with the default configuration.
+/// A [`Paginator`] is cheap to create, so one can be built per call to override settings
+/// such as [`PaginationConfig::max_time`] for a single request.
+#[derive(Clone, Debug)]
+pub struct Paginator<I> {
+    collection: Collection<I>,
+    config: PaginationConfig,
+}
+
+impl<I> Paginator<I> {
+    /// Creates a new [`Paginator`] over `collection` using `config` for every query.
+    #[must_use]
+    pub const fn new(collection: Collection<I>, config: PaginationConfig) -> Self {
+        Self { collection, config }
+    }
+
+    /// Returns a reference to the paginated collection.
+    #[must_use]
+    pub const fn collection(&self) -> &Collection<I> {
+        &self.collection
+    }
+
+    /// Returns a reference to the configuration of this [`Paginator`].
+    #[must_use]
+    pub const fn config(&self) -> &PaginationConfig {
+        &self.config
+    }
+
+    /// Encodes `edge` into a cursor string for a client with the configured [`CursorCodec`].
+    pub fn encode_cursor(&self, edge: &Edge) -> Result<String, CursorError> {
+        self.config.codec.encode(edge)
+    }
+
+    /// Finds a page of the items matching `filter` whose change key is later than the
+    /// high-water mark of `since`, in the order of `options`, e.g. with the `end_cursor` of the
+    /// last page of a full walk. Pass `since` unchanged while walking the pages of changes with
+    /// `cursor`, the `end_cursor` of the last of them is the `since` of the next sync. Without
+    /// any changes there is no `end_cursor`, keep using `since` then.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CursorError::MissingHighWaterMark`] if no [`PaginationConfig::change_key`] is
+    /// set or `since` has no high-water mark.
+    pub async fn find_changes_since<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        since: &Edge,
+        cursor: Option<DirectedCursor>,
+    ) -> Result<FindResult<T>, CursorError>
+    where
+        T: DeserializeOwned,
+    {
+        changes::find_changes_since(
+            &self.collection.clone_with_type(),
+            &self.config,
+            filter,
+            options,
+            since,
+            cursor,
+        )
+        .await
+    }
+
+    /// Checks that `cursor` fits the sort `find_paginated` would use with `options`, see
+    /// [`Edge::verify_round_trip`], and that the options can be paginated, without running
+    /// any query.
+    pub fn validate_cursor(
+        &self,
+        options: Option<FindOptions>,
+        cursor: &DirectedCursor,
+    ) -> Result<(), CursorError> {
+        let options = crate::prepare(&self.config, options.unwrap_or_default(), None);
+        crate::validate(&options, Some(cursor), &self.config)?;
+        cursor.inner().verify_round_trip(&options)
+    }
+
+    /// Builds the links of the pages next to the page of `page_info` from `base_url`, with the
+    /// cursors encoded by the configured [`CursorCodec`], see [`PageLinks`].
+    pub fn page_links(
+        &self,
+        base_url: &str,
+        page_info: &PageInfo,
+    ) -> Result<PageLinks, CursorError> {
+        PageLinks::with_codec(base_url, page_info, &self.config.codec)
+    }
+
+    /// Decodes a cursor received from a client with the configured [`CursorCodec`], reporting
+    /// failures to the configured metrics.
+    pub fn decode_cursor(&self, cursor: &str) -> Result<Edge, CursorError> {
+        self.config.codec.decode(cursor).inspect_err(|_| {
+            if let Some(metrics) = &self.config.metrics {
+                metrics.cursor_decode_failure(self.collection.name());
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl<I: Send + Sync> Pagination for Paginator<I> {
+    async fn find_paginated<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        cursor: Option<DirectedCursor>,
+    ) -> Result<FindResult<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+    {
+        crate::find(
+            &self.collection.clone_with_type(),
+            &self.config,
+            filter,
+            options,
+            cursor,
+            None,
+        )
+        .await
+    }
+
+    async fn estimated_count(&self, options: Option<FindOptions>) -> Result<u64, CursorError> {
+        crate::estimated_count(&self.collection, &self.config, options).await
+    }
+
+    async fn collect_all<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        max_items: usize,
+    ) -> Result<Vec<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+    {
+        crate::collect_all(
+            &self.collection.clone_with_type(),
+            &self.config,
+            filter,
+            options,
+            max_items,
+        )
+        .await
+    }
+
+    async fn find_first_pages_by_parent<T>(
+        &self,
+        parent_key: &str,
+        parents: Vec<Bson>,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> Result<GroupedPages<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+    {
+        grouped::find_first_pages_by_parent(
+            &self.collection.clone_with_type(),
+            &self.config,
+            parent_key,
+            parents,
+            filter,
+            options,
+        )
+        .await
+    }
+
+    async fn find_paginated_grouped<T>(
+        &self,
+        parent_key: &str,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> Result<GroupedPages<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+    {
+        grouped::find_paginated_grouped(
+            &self.collection.clone_with_type(),
+            &self.config,
+            parent_key,
+            filter,
+            options,
+        )
+        .await
+    }
+
+    async fn partition_scan<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        workers: usize,
+    ) -> Result<Vec<PageStream<T>>, CursorError>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        partition::partition_scan(
+            self.collection.clone_with_type(),
+            self.config.clone(),
+            filter,
+            options,
+            workers,
+        )
+        .await
+    }
+
+    fn plan(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        cursor: Option<DirectedCursor>,
+    ) -> Result<QueryPlan, CursorError> {
+        crate::plan(&self.config, filter, options, cursor)
+    }
+
+    async fn count_up_to(
+        &self,
+        filter: Option<Document>,
+        bound: u64,
+        options: Option<FindOptions>,
+    ) -> Result<Count, CursorError> {
+        crate::count_up_to(&self.collection, &self.config, filter, bound, options, None).await
+    }
+
+    async fn update_paginated(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        update: Document,
+    ) -> Result<u64, CursorError> {
+        write::update_paginated(
+            &self.collection.clone_with_type(),
+            &self.config,
+            filter,
+            options,
+            update,
+        )
+        .await
+    }
+
+    async fn delete_paginated(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> Result<u64, CursorError> {
+        write::delete_paginated(
+            &self.collection.clone_with_type(),
+            &self.config,
+            filter,
+            options,
+        )
+        .await
+    }
+
+    async fn for_each_page_in_transaction<T, F, Fut, E>(
+        &self,
+        session: &mut ClientSession,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        callback: F,
+    ) -> Result<(), E>
+    where
+        T: DeserializeOwned + Send,
+        F: FnMut(FindResult<T>) -> Fut + Send,
+        Fut: Future<Output = Result<(), E>> + Send,
+        E: From<CursorError> + Send,
+    {
+        session::for_each_page_in_transaction(
+            &self.collection.clone_with_type(),
+            &self.config,
+            session,
+            filter,
+            options,
+            callback,
+        )
+        .await
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "async-graphql")]
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+#[cfg(feature = "graphql")]
+use juniper::{GraphQLScalar, InputValue, ScalarValue};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CursorError;
+use crate::model::{DirectedCursor, Edge};
+
+/// The `Cursor` scalar of a GraphQL schema, holding an [`Edge`] encoded like its `Display`.
+///
+/// Cursors are validated when the query is parsed, so a resolver receives either a valid
+/// [`Edge`] or no call at all. The direction comes from the argument, see [`Cursor::after`]
+/// and [`Cursor::before`]. The scalar only reads cursors without envelope, a
+/// [`Paginator`](crate::Paginator) whose [`CursorCodec`](crate::CursorCodec) signs, scopes or
+/// adds claims rejects them, take a `String` and decode it with
+/// [`Paginator::decode_cursor`](crate::Paginator::decode_cursor) there.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(transparent)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(GraphQLScalar),
+    graphql(
+        name = "Cursor",
+        description = "Opaque position of an item, passed back as `after` or `before`",
+        to_output_with = Self::to_output,
+        from_input_with = Self::from_input,
+        parse_token(String),
+    )
+)]
+pub struct Cursor(Edge);
+
+impl Cursor {
+    /// Returns the [`Edge`] of the cursor.
+    #[must_use]
+    pub fn into_inner(self) -> Edge {
+        self.0
+    }
+
+    /// Returns the cursor continuing with the items after this one, e.g. for `after`.
+    #[must_use]
+    pub fn after(self) -> DirectedCursor {
+        DirectedCursor::Forward(self.0)
+    }
+
+    /// Returns the cursor continuing with the items before this one, e.g. for `before`.
+    #[must_use]
+    pub fn before(self) -> DirectedCursor {
+        DirectedCursor::Backwards(self.0)
+    }
+
+    #[cfg(feature = "graphql")]
+    fn to_output<S: ScalarValue>(&self) -> juniper::Value<S> {
+        juniper::Value::scalar(self.to_string())
+    }
+
+    #[cfg(feature = "graphql")]
+    fn from_input<S: ScalarValue>(input: &InputValue<S>) -> Result<Self, String> {
+        input
+            .as_string_value()
+            .ok_or_else(|| format!("Expected a cursor string, found: {input}"))?
+            .parse()
+            .map_err(|error: CursorError| error.to_string())
+    }
+}
+
+impl From<Edge> for Cursor {
+    fn from(edge: Edge) -> Self {
+        Self(edge)
+    }
+}
+
+impl From<DirectedCursor> for Cursor {
+    fn from(cursor: DirectedCursor) -> Self {
+        Self(cursor.into_inner())
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = CursorError;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        str.parse().map(Self)
+    }
+}
+
+#[cfg(feature = "async-graphql")]
+#[Scalar(name = "Cursor")]
+impl ScalarType for Cursor {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        let Value::String(cursor) = &value else {
+            return Err(InputValueError::expected_type(value));
+        };
+        cursor.parse().map_err(InputValueError::custom)
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
@@ -0,0 +1,37 @@
+use bson::Bson;
+
+use crate::model::{DirectedCursor, FindResult};
+
+/// Flags `page` if it repeats an item walked before `cursor` and carries the `_id`s of the
+/// last `window` items walked in the cursor continuing the walk.
+pub(crate) fn track<T>(
+    page: &mut FindResult<T>,
+    cursor: Option<&DirectedCursor>,
+    window: usize,
+    reversed: bool,
+) {
+    let recent = cursor.map_or(&[][..], |cursor| cursor.inner().recent_ids());
+    let mut ids: Vec<&Bson> = page
+        .edges
+        .iter()
+        .filter_map(|edge| edge.get("_id"))
+        .collect();
+    page.drift_detected = ids.iter().any(|id| recent.contains(id));
+
+    let backwards = matches!(cursor, Some(DirectedCursor::Backwards(_)));
+    // The edges are in sort order, unless reversed, while a backwards walk goes against it.
+    if backwards != reversed {
+        ids.reverse();
+    }
+    let mut walked: Vec<Bson> = recent.iter().chain(ids).cloned().collect();
+    walked.drain(..walked.len().saturating_sub(window));
+
+    let continuing = if backwards {
+        &mut page.page_info.start_cursor
+    } else {
+        &mut page.page_info.end_cursor
+    };
+    *continuing = continuing
+        .take()
+        .map(|continuing| continuing.map_edge(|edge| edge.with_recent_ids(walked)));
+}
@@ -1,19 +1,169 @@
 #![allow(clippy::absolute_paths)]
+use std::time::Duration;
+
+use bson::spec::ElementType;
+use mongodb::error::{ErrorKind, RETRYABLE_WRITE_ERROR, TRANSIENT_TRANSACTION_ERROR};
 use thiserror::Error;
 
 /// Possible errors that can arise during parsing and creating a cursor.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum CursorError {
     #[error("Unable to deserialize to bson: {0}")]
     BsonDeError(#[from] bson::de::Error),
     #[error("Unable to deserialize to bson: {0}")]
     BsonSerError(#[from] bson::ser::Error),
+    #[error("Unable to read raw bson: {0}")]
+    BsonRawError(#[from] bson::raw::Error),
     #[error("Error while accessing Value {0}")]
     BsonValueAccessError(#[from] bson::document::ValueAccessError),
     #[error("Unable to parse str to ObjectID: {0}")]
     ParseError(#[from] bson::oid::Error),
+    #[error("Id `{value}` should be {expected}")]
+    InvalidId {
+        value: String,
+        expected: &'static str,
+    },
     #[error("Error while retrieving data: {0}")]
     MongoDBError(#[from] mongodb::error::Error),
     #[error("Invalid cursor")]
     InvalidCursor,
+    #[error("Cursor is missing the sort key `{0}`")]
+    MissingSortKey(String),
+    #[error("Cursor has the key `{0}`, which the sort doesn't")]
+    UnknownCursorKey(String),
+    #[error("Cursor has no high-water mark, it must come from a page with a change key")]
+    MissingHighWaterMark,
+    #[error("Cursor field `{field}` should be {expected} but is {found:?}")]
+    CursorFieldTypeMismatch {
+        field: String,
+        expected: &'static str,
+        found: ElementType,
+    },
+    #[error("Sort direction of `{field}` must be 1 or -1, got {value}")]
+    InvalidSortDirection { field: String, value: String },
+    #[error("Limit of 0 would return all items, leave it unset instead")]
+    EmptyLimit,
+    #[error("Limit is empty, set the page size")]
+    MissingLimit,
+    #[error("Limit of {0} is negative, pass the page size instead")]
+    NegativeLimit(i64),
+    #[error("Projection leaves out the sort key `{0}`, include it or one of its parents")]
+    ProjectionExcludesSortKey(String),
+    #[error("Invalid connection arguments: {0}")]
+    InvalidConnectionArgs(&'static str),
+    #[error("Cursor was created under another collation than the query uses")]
+    CollationMismatch,
+    #[error("Checkpoint was created for a different {0}")]
+    CheckpointMismatch(&'static str),
+    #[error("Page number {0} is invalid, pages are numbered from 1")]
+    InvalidPageNumber(u64),
+    #[error("Skip of {0} cannot be combined with a cursor")]
+    SkipWithCursor(u64),
+    #[error("Unsupported combination: {0}")]
+    InvalidCombination(&'static str),
+    #[error("`max_await_time` only applies to a `TailableAwait` cursor type")]
+    MaxAwaitTimeWithoutAwait,
+    #[error("Cursor was issued for a different scope")]
+    ScopeMismatch,
+    #[error("Cursor signature is missing or invalid")]
+    InvalidSignature,
+    #[error("Cursor has expired")]
+    CursorExpired,
+    #[error("Cursor claim `{0}` is missing or invalid")]
+    InvalidCursorClaim(&'static str),
+    #[error("Cursor of {len} bytes exceeds the maximum of {max}, sort on shorter keys")]
+    CursorTooLong { len: usize, max: usize },
+    #[error("Pagination did not finish within the deadline of {0:?}")]
+    DeadlineExceeded(Duration),
+    #[error("Items share the sort keys {0}, add a unique key to the sort")]
+    UnstableSort(String),
+    #[cfg(feature = "http")]
+    #[error("Link header is invalid: {0}")]
+    InvalidHeaderValue(#[from] http::header::InvalidHeaderValue),
+    #[error("Query needed more than {0} batches")]
+    TooManyBatches(u32),
+    #[error("More than {0} items match the query")]
+    TooManyItems(usize),
+}
+
+/// Server error codes `MongoDB` considers safe to retry for reads.
+const RETRYABLE_READ_CODES: [i32; 13] = [
+    11600, 11602, 10107, 13435, 13436, 189, 91, 7, 6, 89, 9001, 134, 262,
+];
+
+/// Server error codes caused by an invalid filter or invalid options.
+const CLIENT_ERROR_CODES: [i32; 3] = [2, 9, 14];
+
+impl CursorError {
+    /// Returns `true` if the error was caused by the input of the caller (the cursor, filter or
+    /// options) and should be reported as a client error, e.g. HTTP 400.
+    #[must_use]
+    pub fn is_client_error(&self) -> bool {
+        match self {
+            Self::InvalidCursor
+            | Self::MissingSortKey(_)
+            | Self::UnknownCursorKey(_)
+            | Self::MissingHighWaterMark
+            | Self::CheckpointMismatch(_)
+            | Self::CollationMismatch
+            | Self::InvalidPageNumber(_)
+            | Self::CursorFieldTypeMismatch { .. }
+            | Self::InvalidSortDirection { .. }
+            | Self::EmptyLimit
+            | Self::MissingLimit
+            | Self::NegativeLimit(_)
+            | Self::ProjectionExcludesSortKey(_)
+            | Self::SkipWithCursor(_)
+            | Self::InvalidCombination(_)
+            | Self::InvalidConnectionArgs(_)
+            | Self::ScopeMismatch
+            | Self::InvalidSignature
+            | Self::CursorExpired
+            | Self::InvalidCursorClaim(_)
+            | Self::InvalidId { .. }
+            | Self::ParseError(_) => true,
+            Self::MongoDBError(error) => {
+                matches!(error.kind.as_ref(), ErrorKind::InvalidArgument { .. })
+                    || matches!(
+                        error.kind.as_ref(),
+                        ErrorKind::Command(command) if CLIENT_ERROR_CODES.contains(&command.code)
+                    )
+            }
+            Self::BsonDeError(_)
+            | Self::BsonSerError(_)
+            | Self::BsonRawError(_)
+            | Self::BsonValueAccessError(_)
+            | Self::DeadlineExceeded(_)
+            | Self::TooManyItems(_)
+            | Self::TooManyBatches(_)
+            | Self::MaxAwaitTimeWithoutAwait
+            | Self::UnstableSort(_)
+            | Self::CursorTooLong { .. } => false,
+            #[cfg(feature = "http")]
+            Self::InvalidHeaderValue(_) => false,
+        }
+    }
+
+    /// Returns `true` if the same call may succeed when retried, e.g. after a network error,
+    /// a server selection timeout or a primary step down. Paginated reads are idempotent, so
+    /// these are always safe to retry.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        let Self::MongoDBError(error) = self else {
+            return false;
+        };
+        error.contains_label(RETRYABLE_WRITE_ERROR)
+            || error.contains_label(TRANSIENT_TRANSACTION_ERROR)
+            || matches!(
+                error.kind.as_ref(),
+                ErrorKind::Io(_)
+                    | ErrorKind::ConnectionPoolCleared { .. }
+                    | ErrorKind::ServerSelection { .. }
+            )
+            || matches!(
+                error.kind.as_ref(),
+                ErrorKind::Command(command) if RETRYABLE_READ_CODES.contains(&command.code)
+            )
+    }
 }
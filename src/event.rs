@@ -0,0 +1,102 @@
+use std::fmt;
+
+/// A soft problem noticed while paginating. It does not fail the query, but usually means
+/// the pages are not what the caller expects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PaginationEvent {
+    /// Some items of a page do not contain the sort key `key`, their cursors treat it as `null`.
+    ItemsMissingSortKey {
+        collection: String,
+        key: String,
+        items: usize,
+    },
+    /// The cursor passed to the query does not contain the sort key `key`, `null` is used instead.
+    CursorMissingSortKey { collection: String, key: String },
+    /// Items of a page share all their sort keys `keys`, pages may skip or repeat them.
+    UnstableSort {
+        collection: String,
+        keys: String,
+        items: usize,
+    },
+    /// A page sorts on `key`, which isn't declared immutable with
+    /// [`PaginationConfig::immutable_fields`](crate::PaginationConfig::immutable_fields).
+    MutableSortKey { collection: String, key: String },
+    /// A query failed with a retryable error and is retried according to the [`RetryPolicy`](crate::RetryPolicy).
+    Retrying {
+        collection: String,
+        attempt: u32,
+        error: String,
+    },
+    /// A transaction of [`Pagination::for_each_page_in_transaction`](crate::Pagination::for_each_page_in_transaction)
+    /// failed and could not be aborted either, the server aborts it once it times out.
+    AbortFailed { collection: String, error: String },
+}
+
+impl fmt::Display for PaginationEvent {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ItemsMissingSortKey {
+                collection,
+                key,
+                items,
+            } => write!(
+                fmt,
+                "{items} items of a page from {collection} have no sort key `{key}`"
+            ),
+            Self::CursorMissingSortKey { collection, key } => write!(
+                fmt,
+                "cursor for {collection} has no sort key `{key}`, resuming at null"
+            ),
+            Self::UnstableSort {
+                collection,
+                keys,
+                items,
+            } => write!(
+                fmt,
+                "{items} items of a page from {collection} share the sort keys {keys}, add a unique key to the sort"
+            ),
+            Self::MutableSortKey { collection, key } => write!(
+                fmt,
+                "page of {collection} sorts on the mutable field `{key}`, updated items may be skipped or repeated"
+            ),
+            Self::Retrying {
+                collection,
+                attempt,
+                error,
+            } => write!(
+                fmt,
+                "attempt {attempt} of a query on {collection} failed, retrying: {error}"
+            ),
+            Self::AbortFailed { collection, error } => write!(
+                fmt,
+                "failed to abort the pagination transaction on {collection}: {error}"
+            ),
+        }
+    }
+}
+
+/// Receives the [`PaginationEvent`]s of a [`Paginator`](crate::Paginator).
+///
+/// Implemented for every `Fn(&PaginationEvent)`, so a closure can be passed directly.
+pub trait PaginationObserver: Send + Sync {
+    /// Called for every event as it happens.
+    fn on_event(&self, event: &PaginationEvent);
+}
+
+impl<F: Fn(&PaginationEvent) + Send + Sync> PaginationObserver for F {
+    fn on_event(&self, event: &PaginationEvent) {
+        self(event);
+    }
+}
+
+/// The default [`PaginationObserver`], logging every event with `log::warn!`.
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct LogObserver;
+
+impl PaginationObserver for LogObserver {
+    fn on_event(&self, event: &PaginationEvent) {
+        log::warn!("{event}");
+    }
+}
@@ -0,0 +1,65 @@
+/// Declares the GraphQL connection type `$connection` with the edge type `$edge` for items of
+/// the GraphQL object `$node`, e.g. `graphql_connection!(FruitConnection, FruitEdge, Fruit);`.
+///
+/// The connection implements `From<FindResult<$node>>`, so a resolver can return
+/// `find_result.into()`. The crate using the macro needs `juniper` as a dependency.
+#[macro_export]
+macro_rules! graphql_connection {
+    ($connection:ident, $edge:ident, $node:ty) => {
+        /// An item of a page together with its cursor.
+        #[derive(Clone, Debug, ::juniper::GraphQLObject)]
+        pub struct $edge {
+            /// The item
+            pub node: $node,
+            /// Cursor of the item, pass it as `after` or `before` to continue from it
+            pub cursor: $crate::Cursor,
+        }
+
+        /// A page of items.
+        #[derive(Clone, Debug)]
+        pub struct $connection {
+            /// Current page
+            pub page_info: $crate::PageInfo,
+            /// Items of the page with their cursors
+            pub edges: Vec<$edge>,
+            /// Number of items matching the query
+            pub total_count: $crate::Count,
+        }
+
+        #[::juniper::graphql_object]
+        impl $connection {
+            fn page_info(&self) -> &$crate::PageInfo {
+                &self.page_info
+            }
+
+            fn edges(&self) -> &[$edge] {
+                &self.edges
+            }
+
+            fn total_count(&self) -> Option<i32> {
+                self.total_count
+                    .value()
+                    .and_then(|count| i32::try_from(count).ok())
+            }
+        }
+
+        impl From<$crate::FindResult<$node>> for $connection {
+            fn from(result: $crate::FindResult<$node>) -> Self {
+                let edges = result
+                    .items
+                    .into_iter()
+                    .zip(result.edges)
+                    .map(|(node, edge)| $edge {
+                        node,
+                        cursor: edge.into(),
+                    })
+                    .collect();
+                Self {
+                    page_info: result.page_info,
+                    edges,
+                    total_count: result.total_count,
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use bson::{doc, Bson, Document};
+use futures_util::TryStreamExt;
+use mongodb::options::{AggregateOptions, FindOptions};
+use mongodb::Collection;
+use serde::de::DeserializeOwned;
+
+use crate::codec;
+use crate::config::PaginationConfig;
+use crate::error::CursorError;
+use crate::model::{self, deserialize_page, Count, DirectedCursor, Edge, FindResult, PageInfo};
+use crate::option::CursorOptions;
+use crate::plan::EffectiveOptions;
+
+/// The first page of the items of every parent, in the order the parents were requested.
+pub type GroupedPages<T> = Vec<(Bson, FindResult<T>)>;
+
+/// Field holding the position of an item within the items of its parent.
+const POSITION: &str = "__position";
+
+/// Finds the first page of the items of every parent in `parents` with a single aggregation,
+/// which keeps the `limit + 1` first items of every parent with `$topN`.
+pub(crate) async fn find_first_pages_by_parent<T: DeserializeOwned>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    parent_key: &str,
+    parents: Vec<Bson>,
+    filter: Option<Document>,
+    options: Option<FindOptions>,
+) -> Result<GroupedPages<T>, CursorError> {
+    let requested_limit = options.as_ref().and_then(|options| options.limit);
+    let options = crate::prepare(config, options.unwrap_or_default(), None);
+    crate::validate(&options, None, config)?;
+    let parent_filter = doc! { parent_key: { "$in": parents.clone() } };
+    let filter = match config.scope(filter) {
+        Some(filter) if !filter.is_empty() => doc! { "$and": [filter, parent_filter] },
+        _ => parent_filter,
+    };
+    let sort = options.sort().cloned().unwrap_or_default();
+    let mut pipeline = vec![doc! { "$match": filter }];
+    match options.limit {
+        Some(limit) => pipeline.push(doc! { "$group": {
+            "_id": format!("${parent_key}"),
+            "items": { "$topN": {
+                "n": limit.saturating_add(1),
+                "sortBy": sort,
+                "output": "$$ROOT",
+            } },
+        } }),
+        None => pipeline.extend([
+            doc! { "$sort": sort },
+            doc! { "$group": { "_id": format!("${parent_key}"), "items": { "$push": "$$ROOT" } } },
+        ]),
+    }
+
+    let mut groups = aggregate(collection, config, &pipeline, &options)
+        .await?
+        .into_iter()
+        .map(|(parent, documents)| Ok((group_key(parent)?, documents)))
+        .collect::<Result<HashMap<_, _>, CursorError>>()?;
+    parents
+        .into_iter()
+        .map(|parent| {
+            let documents = groups
+                .remove(&group_key(parent.clone())?)
+                .unwrap_or_default();
+            let page = first_page(config, &options, documents)?;
+            Ok((
+                parent,
+                crate::check_unlimited(config, requested_limit, page)?,
+            ))
+        })
+        .collect()
+}
+
+/// Finds the first page of the items of every parent with a single aggregation, which numbers
+/// the items of every parent with `$setWindowFields` and keeps the `limit + 1` first ones.
+pub(crate) async fn find_paginated_grouped<T: DeserializeOwned>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    parent_key: &str,
+    filter: Option<Document>,
+    options: Option<FindOptions>,
+) -> Result<GroupedPages<T>, CursorError> {
+    let requested_limit = options.as_ref().and_then(|options| options.limit);
+    let options = crate::prepare(config, options.unwrap_or_default(), None);
+    crate::validate(&options, None, config)?;
+    let sort = options.sort().cloned().unwrap_or_default();
+    let mut pipeline = vec![doc! { "$match": config.scope(filter).unwrap_or_default() }];
+    if let Some(limit) = options.limit {
+        pipeline.extend([
+            doc! { "$setWindowFields": {
+                "partitionBy": format!("${parent_key}"),
+                "sortBy": sort.clone(),
+                "output": { POSITION: { "$documentNumber": {} } },
+            } },
+            doc! { "$match": { POSITION: { "$lte": limit.saturating_add(1) } } },
+        ]);
+    }
+    let mut grouped_sort = doc! { parent_key: 1_i32 };
+    grouped_sort.extend(sort);
+    pipeline.push(doc! { "$sort": grouped_sort });
+
+    let found: Vec<Document> = config
+        .run(collection.name(), || async {
+            Ok(collection
+                .aggregate(pipeline.clone(), AggregateOptions::from(&options))
+                .await?
+                .try_collect()
+                .await?)
+        })
+        .await?;
+    let mut groups: Vec<(Bson, Vec<Document>)> = Vec::new();
+    let mut last_parent = None;
+    for mut document in found {
+        document.remove(POSITION);
+        let parent = document.get(parent_key).cloned().unwrap_or(Bson::Null);
+        // `MongoDB` sorts and groups numbers by value, whatever their type.
+        let canonical_parent = model::canonical(parent.clone());
+        match groups.last_mut() {
+            Some((_, items)) if last_parent.as_ref() == Some(&canonical_parent) => {
+                items.push(document);
+            }
+            _ => groups.push((parent, vec![document])),
+        }
+        last_parent = Some(canonical_parent);
+    }
+    groups
+        .into_iter()
+        .map(|(parent, documents)| {
+            let page = first_page(config, &options, documents)?;
+            Ok((
+                parent,
+                crate::check_unlimited(config, requested_limit, page)?,
+            ))
+        })
+        .collect()
+}
+
+/// Returns the key `parent` is looked up by, the same for every value `MongoDB` groups together,
+/// like an `Int32` and an `Int64` holding the same number.
+fn group_key(parent: Bson) -> Result<Vec<u8>, CursorError> {
+    Ok(bson::to_vec(&doc! { "_id": model::canonical(parent) })?)
+}
+
+/// Runs the aggregation of grouped pages, returning the items of every group.
+async fn aggregate(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    pipeline: &[Document],
+    options: &CursorOptions,
+) -> Result<Vec<(Bson, Vec<Document>)>, CursorError> {
+    let groups: Vec<Document> = config
+        .run(collection.name(), || async {
+            Ok(collection
+                .aggregate(pipeline.to_vec(), AggregateOptions::from(options))
+                .await?
+                .try_collect()
+                .await?)
+        })
+        .await?;
+    groups
+        .into_iter()
+        .map(|mut group| {
+            let parent = group.remove("_id").unwrap_or(Bson::Null);
+            let documents = group
+                .get_array("items")?
+                .iter()
+                .filter_map(Bson::as_document)
+                .cloned()
+                .collect();
+            Ok((parent, documents))
+        })
+        .collect()
+}
+
+/// Builds the first page from its `documents` in sort order, one more than the limit if there
+/// is a next page.
+fn first_page<T: DeserializeOwned>(
+    config: &PaginationConfig,
+    options: &CursorOptions,
+    mut documents: Vec<Document>,
+) -> Result<FindResult<T>, CursorError> {
+    let limit = options.limit.and_then(|limit| usize::try_from(limit).ok());
+    let more = limit.is_some_and(|limit| documents.len() > limit);
+    if let Some(limit) = limit {
+        documents.truncate(limit);
+    }
+    let edges: Vec<Edge> = documents
+        .iter()
+        .map(|document| Edge::new(document, options))
+        .collect();
+    let end_cursor = edges.last().cloned().map(DirectedCursor::Forward);
+    let start_cursor = edges.first().cloned().map(DirectedCursor::Backwards);
+    let (items, edges, mismatched) =
+        deserialize_page::<T>(documents, edges, config.collect_mismatched)?;
+    let mut page = FindResult {
+        page_info: PageInfo {
+            has_previous_page: Some(false),
+            has_next_page: Some(more),
+            start_cursor_forward: start_cursor.clone().map(DirectedCursor::reverse),
+            end_cursor_backward: end_cursor.clone().map(DirectedCursor::reverse),
+            start_cursor,
+            end_cursor,
+            ..PageInfo::default()
+        },
+        edges,
+        total_count: Count::Unknown,
+        items,
+        stats: None,
+        etag: None,
+        drift_detected: false,
+        mismatched,
+        effective_options: EffectiveOptions::default(),
+    };
+    codec::attach(&mut page, &config.codec)?;
+    Ok(page)
+}
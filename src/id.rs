@@ -0,0 +1,66 @@
+use bson::oid::ObjectId;
+use bson::Uuid;
+
+use crate::error::CursorError;
+
+/// A type of `_id` that can be parsed from a string, e.g. from a path segment of a URL.
+pub trait ParseId: Sized {
+    /// Describes the type in [`CursorError::InvalidId`], e.g. `an ObjectId`.
+    const EXPECTED: &'static str;
+
+    /// Parses `value`, returning `None` if it is not a valid id of this type.
+    fn parse(value: &str) -> Option<Self>;
+}
+
+impl ParseId for ObjectId {
+    const EXPECTED: &'static str = "an ObjectId";
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::parse_str(value).ok()
+    }
+}
+
+impl ParseId for Uuid {
+    const EXPECTED: &'static str = "a UUID";
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::parse_str(value).ok()
+    }
+}
+
+impl ParseId for i32 {
+    const EXPECTED: &'static str = "a 32-bit integer";
+
+    fn parse(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl ParseId for i64 {
+    const EXPECTED: &'static str = "a 64-bit integer";
+
+    fn parse(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl ParseId for String {
+    const EXPECTED: &'static str = "a string";
+
+    fn parse(value: &str) -> Option<Self> {
+        Some(value.to_owned())
+    }
+}
+
+/// Parses the `_id` of an item from `value`, e.g. to pass it to
+/// [`Pagination::find_paginated_after_id`](crate::Pagination::find_paginated_after_id).
+///
+/// # Errors
+///
+/// Returns [`CursorError::InvalidId`] if `value` is not a valid `T`.
+pub fn parse_id<T: ParseId>(value: &str) -> Result<T, CursorError> {
+    T::parse(value).ok_or_else(|| CursorError::InvalidId {
+        value: value.to_owned(),
+        expected: T::EXPECTED,
+    })
+}
@@ -0,0 +1,106 @@
+use std::mem;
+
+use bson::{doc, Bson, Document};
+use mongodb::options::FindOneOptions;
+use mongodb::Collection;
+
+use crate::config::PaginationConfig;
+use crate::error::CursorError;
+use crate::event::PaginationEvent;
+use crate::model::{DirectedCursor, FindResult, PageInfo};
+use crate::option::{cursor_key, sort_value, CursorOptions};
+use crate::session::SessionLock;
+
+/// Returns the insertion bound of the walk `cursor` continues, or for a cursor without one the
+/// latest value of `key` among the items matching `filter`, read with the collation and in the
+/// session of the page.
+pub(crate) async fn insertion_bound(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    key: &str,
+    filter: &Document,
+    options: &CursorOptions,
+    cursor: Option<&DirectedCursor>,
+    session: Option<&SessionLock<'_>>,
+) -> Result<Option<Bson>, CursorError> {
+    if let Some(bound) = cursor.and_then(|cursor| cursor.inner().insertion_bound()) {
+        return Ok(Some(bound.clone()));
+    }
+    let find_options = FindOneOptions::builder()
+        .sort(doc! { key: -1_i32 })
+        .projection(doc! { key: 1_i32 })
+        .collation(options.collation.clone())
+        .build();
+    let latest = config
+        .run(collection.name(), || async {
+            Ok(match session {
+                Some(lock) => {
+                    let mut guard = lock.lock().await;
+                    collection
+                        .find_one_with_session(filter.clone(), find_options.clone(), &mut guard)
+                        .await?
+                }
+                None => {
+                    collection
+                        .find_one(filter.clone(), find_options.clone())
+                        .await?
+                }
+            })
+        })
+        .await?;
+    Ok(latest.and_then(|latest| sort_value(&latest, key).cloned()))
+}
+
+/// Leaves the items with a later value of `key` than `bound` out of `filter`. Items without
+/// `key` or with a `null` one stay, a range query on `key` alone would hide them.
+pub(crate) fn bounded(filter: Document, key: &str, bound: &Bson) -> Document {
+    let inserted_before = doc! {
+        "$or": [{ key: { "$lte": bound.clone() } }, { key: Bson::Null }],
+    };
+    if filter.is_empty() {
+        inserted_before
+    } else {
+        doc! { "$and": [filter, inserted_before] }
+    }
+}
+
+/// Carries `bound` in every cursor of `page`.
+pub(crate) fn attach_bound<T>(page: &mut FindResult<T>, bound: &Bson) {
+    page.edges = mem::take(&mut page.edges)
+        .into_iter()
+        .map(|edge| edge.with_insertion_bound(Some(bound.clone())))
+        .collect();
+    let PageInfo {
+        start_cursor,
+        end_cursor,
+        start_cursor_forward,
+        end_cursor_backward,
+        ..
+    } = &mut page.page_info;
+    for cursor in [
+        start_cursor,
+        end_cursor,
+        start_cursor_forward,
+        end_cursor_backward,
+    ] {
+        *cursor = cursor
+            .take()
+            .map(|cursor| cursor.map_edge(|edge| edge.with_insertion_bound(Some(bound.clone()))));
+    }
+}
+
+/// Reports the sort keys of `options` that aren't declared immutable.
+pub(crate) fn check_sort(collection: &str, config: &PaginationConfig, options: &CursorOptions) {
+    let Some(immutable) = &config.immutable_fields else {
+        return;
+    };
+    for key in options.sort().iter().flat_map(|sort| sort.keys()) {
+        let field = cursor_key(key);
+        if field != "_id" && !immutable.iter().any(|immutable| immutable == field) {
+            config.emit(&PaginationEvent::MutableSortKey {
+                collection: collection.to_owned(),
+                key: field.to_owned(),
+            });
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+
+use bson::Bson;
+
+use crate::model::Edge;
+
+/// Writes a decoded [`Edge`] for people, e.g. to debug a cursor a customer sent in. Every sort
+/// key is written on its own line with the BSON type of its value, followed by the
+/// annotations the edge carries:
+///
+/// ```text
+/// name: String "apple"
+/// _id: ObjectId ObjectId("65f1c0a1e4b0a1b2c3d4e5f6")
+/// $offset: 20
+/// $collation: { "locale": "en" }
+/// ```
+///
+/// Parse the string with [`Edge::from_str`](std::str::FromStr::from_str), or with
+/// [`Paginator::decode_cursor`](crate::Paginator::decode_cursor) if the cursor was encoded by a
+/// [`CursorCodec`](crate::CursorCodec) that signs, scopes or adds claims to it.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct CursorInspector<'edge> {
+    /// The edge to write
+    pub edge: &'edge Edge,
+}
+
+impl<'edge> CursorInspector<'edge> {
+    /// Creates an inspector writing `edge`.
+    #[must_use]
+    pub const fn new(edge: &'edge Edge) -> Self {
+        Self { edge }
+    }
+}
+
+/// Writes a line of `key` with `value` and its BSON type.
+#[allow(clippy::use_debug)] // The names of the element types are their variants
+fn typed(fmt: &mut fmt::Formatter<'_>, key: &str, value: &Bson) -> fmt::Result {
+    writeln!(fmt, "{key}: {:?} {value}", value.element_type())
+}
+
+impl fmt::Display for CursorInspector<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let edge = self.edge;
+        if edge.is_empty() {
+            writeln!(fmt, "(end of the result set)")?;
+        }
+        for (key, value) in edge.iter() {
+            typed(fmt, key, value)?;
+        }
+        if let Some(offset) = edge.offset() {
+            writeln!(fmt, "$offset: {offset}")?;
+        }
+        if let Some(high_water) = edge.high_water_mark() {
+            typed(fmt, "$highWater", high_water)?;
+        }
+        if let Some(insertion_bound) = edge.insertion_bound() {
+            typed(fmt, "$insertionBound", insertion_bound)?;
+        }
+        if !edge.recent_ids().is_empty() {
+            writeln!(fmt, "$recentIds: {} ids", edge.recent_ids().len())?;
+        }
+        if let Some(collation) = edge.collation() {
+            writeln!(fmt, "$collation: {collation}")?;
+        }
+        if edge.is_unverified() {
+            writeln!(fmt, "(not verified by a codec)")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::doc;
+
+    use super::CursorInspector;
+    use crate::model::Edge;
+
+    #[test]
+    fn writes_sort_keys_with_their_types() {
+        let edge = Edge::from_document(doc! { "name": "apple", "_id": 1_i32, "$offset": 20_i64 });
+        assert_eq!(
+            CursorInspector::new(&edge).to_string(),
+            "name: String \"apple\"\n_id: Int64 1\n$offset: 20\n"
+        );
+        let parsed: Option<Edge> = edge.to_string().parse().ok();
+        assert_eq!(
+            parsed.map(|parsed_edge| CursorInspector::new(&parsed_edge).to_string()),
+            Some(
+                "name: String \"apple\"\n_id: Int64 1\n$offset: 20\n(not verified by a codec)\n"
+                    .to_owned()
+            )
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use bson::{Bson, Document};
+use futures_util::TryStreamExt;
+use mongodb::{options::FindOptions, Collection};
+use thiserror::Error;
+
+use crate::error::CursorError;
+use crate::option::CursorOptions;
+use crate::{DirectedCursor, FindResult, Pagination};
+
+/// The walk in which an [`InvariantViolation`] was detected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(clippy::exhaustive_enums)]
+pub enum Walk {
+    /// Following `end_cursor` from the first page.
+    Forward,
+    /// Following `start_cursor` back from the last page.
+    Backward,
+    /// Following [`Pagination::find_paginated_back`] from the end.
+    Back,
+}
+
+impl fmt::Display for Walk {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Forward => fmt.write_str("forward"),
+            Self::Backward => fmt.write_str("backward"),
+            Self::Back => fmt.write_str("back"),
+        }
+    }
+}
+
+/// A broken pagination invariant found by [`check_invariants`].
+#[derive(Clone, Debug, Error, PartialEq)]
+#[non_exhaustive]
+pub enum InvariantViolation {
+    #[error("{walk} walk returned {id} more than once")]
+    Duplicate { walk: Walk, id: Bson },
+    #[error("{walk} walk never returned {id}")]
+    Gap { walk: Walk, id: Bson },
+    #[error("{walk} walk returned {found} at position {position}, expected {expected}")]
+    OutOfOrder {
+        walk: Walk,
+        position: usize,
+        expected: Bson,
+        found: Bson,
+    },
+    #[error("{walk} page {page} reported has_next_page = {found:?}, expected {expected}")]
+    HasNextPage {
+        walk: Walk,
+        page: usize,
+        expected: bool,
+        found: Option<bool>,
+    },
+    #[error("{walk} page {page} reported has_previous_page = {found:?}, expected {expected}")]
+    HasPreviousPage {
+        walk: Walk,
+        page: usize,
+        expected: bool,
+        found: Option<bool>,
+    },
+    #[error("{walk} page {page} returned {found:?}, expected the forward page {expected:?}")]
+    PageMismatch {
+        walk: Walk,
+        page: usize,
+        expected: Vec<Bson>,
+        found: Vec<Bson>,
+    },
+    #[error("{walk} walk stopped making progress at page {page}")]
+    NoProgress { walk: Walk, page: usize },
+}
+
+/// Walks every page of `collection` forward and backward and reports each broken invariant.
+///
+/// The pages are compared against a single unpaginated find with the same filter and sort.
+/// Both walks must return every matching document exactly once and in sort order, and the
+/// `has_next_page` / `has_previous_page` flags must match what is actually left to walk.
+/// Walking back with [`Pagination::find_paginated_back`] must return the forward pages in
+/// reverse.
+///
+/// # Arguments
+/// * `collection`: The collection to paginate through
+/// * `filter`: Optional filter to restrict the result set of the query.
+/// * `sort`: The sort to paginate with, `_id` is appended like in [`Pagination::find_paginated`]
+/// * `limit`: The page size
+pub async fn check_invariants<I: Send + Sync>(
+    collection: &Collection<I>,
+    filter: Option<Document>,
+    sort: Document,
+    limit: i64,
+) -> Result<Vec<InvariantViolation>, CursorError> {
+    let collection = collection.clone_with_type::<Document>();
+    let options = FindOptions::builder().sort(sort).limit(limit).build();
+    let normalized_sort = CursorOptions::new(options.clone(), None)
+        .sort
+        .clone()
+        .unwrap_or_default();
+
+    let expected = expected_ids(&collection, filter.clone(), normalized_sort).await?;
+
+    let mut violations = Vec::new();
+
+    // Forward: start without a cursor and follow `end_cursor`.
+    let mut forward = Vec::new();
+    let mut forward_pages = Vec::new();
+    let mut last_page = Vec::new();
+    let mut last_start = None;
+    let mut cursor = None;
+    for page in 0..=expected.len() {
+        let result: FindResult<Document> = collection
+            .find_paginated(filter.clone(), Some(options.clone()), cursor)
+            .await?;
+        let ids = result.items.iter().map(id_of).collect::<Vec<Bson>>();
+        forward.extend(ids.iter().cloned());
+        forward_pages.push(ids.clone());
+        check_flags(
+            &mut violations,
+            Walk::Forward,
+            page,
+            &result,
+            forward.len() < expected.len(),
+            page > 0,
+        );
+        if result.page_info.is_last_page() {
+            last_page = ids;
+            last_start = result.page_info.start_cursor;
+            break;
+        }
+        if ids.is_empty() || page == expected.len() {
+            violations.push(InvariantViolation::NoProgress {
+                walk: Walk::Forward,
+                page,
+            });
+            break;
+        }
+        cursor = result.page_info.end_cursor;
+    }
+    check_sequence(&mut violations, Walk::Forward, &expected, &forward);
+    check_back_walk(
+        &mut violations,
+        &collection,
+        filter.as_ref(),
+        &options,
+        &forward_pages,
+    )
+    .await?;
+
+    // Backward: start at the first item of the last page and follow `start_cursor`.
+    let Some(anchor) = last_page.first() else {
+        return Ok(violations);
+    };
+    let before = expected
+        .iter()
+        .position(|id| id == anchor)
+        .map_or(&[][..], |position| expected.get(..position).unwrap_or(&[]));
+    let mut backward: Vec<Bson> = Vec::new();
+    let mut start_cursor = last_start;
+    for page in 0..=before.len() {
+        let Some(start) = start_cursor.take() else {
+            break;
+        };
+        let result: FindResult<Document> = collection
+            .find_paginated(filter.clone(), Some(options.clone()), Some(start))
+            .await?;
+        let ids = result.items.iter().map(id_of).collect::<Vec<Bson>>();
+        backward.splice(0..0, ids.iter().cloned());
+        check_flags(
+            &mut violations,
+            Walk::Backward,
+            page,
+            &result,
+            true,
+            backward.len() < before.len(),
+        );
+        if result.page_info.is_first_page() {
+            break;
+        }
+        if ids.is_empty() || page == before.len() {
+            violations.push(InvariantViolation::NoProgress {
+                walk: Walk::Backward,
+                page,
+            });
+            break;
+        }
+        start_cursor = result.page_info.start_cursor;
+    }
+    check_sequence(&mut violations, Walk::Backward, before, &backward);
+
+    Ok(violations)
+}
+
+/// Runs [`check_invariants`] and panics with every violation found.
+///
+/// # Panics
+/// If any invariant is violated or the pages could not be fetched.
+pub async fn assert_invariants<I: Send + Sync>(
+    collection: &Collection<I>,
+    filter: Option<Document>,
+    sort: Document,
+    limit: i64,
+) {
+    let violations = check_invariants(collection, filter, sort, limit).await;
+    assert!(
+        matches!(&violations, Ok(found) if found.is_empty()),
+        "pagination invariants violated: {violations:#?}"
+    );
+}
+
+/// Returns the `_id`s of the items matching `filter` from a single unpaginated find.
+async fn expected_ids(
+    collection: &Collection<Document>,
+    filter: Option<Document>,
+    sort: Document,
+) -> Result<Vec<Bson>, CursorError> {
+    Ok(collection
+        .find(filter, FindOptions::builder().sort(sort).build())
+        .await?
+        .try_collect::<Vec<Document>>()
+        .await?
+        .iter()
+        .map(id_of)
+        .collect())
+}
+
+/// Walks back from the end with [`Pagination::find_paginated_back`] and compares every page to
+/// the page of the forward walk.
+async fn check_back_walk(
+    violations: &mut Vec<InvariantViolation>,
+    collection: &Collection<Document>,
+    filter: Option<&Document>,
+    options: &FindOptions,
+    forward_pages: &[Vec<Bson>],
+) -> Result<(), CursorError> {
+    let mut before = None;
+    for (page, expected) in forward_pages.iter().rev().enumerate() {
+        let result: FindResult<Document> = collection
+            .find_paginated_back(filter.cloned(), Some(options.clone()), before)
+            .await?;
+        let ids = result.items.iter().map(id_of).collect::<Vec<Bson>>();
+        if ids != *expected {
+            violations.push(InvariantViolation::PageMismatch {
+                walk: Walk::Back,
+                page,
+                expected: expected.clone(),
+                found: ids,
+            });
+            return Ok(());
+        }
+        before = result
+            .page_info
+            .start_cursor
+            .map(DirectedCursor::into_inner);
+    }
+    Ok(())
+}
+
+fn id_of(document: &Document) -> Bson {
+    document.get("_id").cloned().unwrap_or(Bson::Null)
+}
+
+fn check_flags(
+    violations: &mut Vec<InvariantViolation>,
+    walk: Walk,
+    page: usize,
+    result: &FindResult<Document>,
+    has_next_page: bool,
+    has_previous_page: bool,
+) {
+    if result.page_info.has_next_page != Some(has_next_page) {
+        violations.push(InvariantViolation::HasNextPage {
+            walk,
+            page,
+            expected: has_next_page,
+            found: result.page_info.has_next_page,
+        });
+    }
+    if result.page_info.has_previous_page != Some(has_previous_page) {
+        violations.push(InvariantViolation::HasPreviousPage {
+            walk,
+            page,
+            expected: has_previous_page,
+            found: result.page_info.has_previous_page,
+        });
+    }
+}
+
+fn check_sequence(
+    violations: &mut Vec<InvariantViolation>,
+    walk: Walk,
+    expected: &[Bson],
+    found: &[Bson],
+) {
+    let mut seen = HashSet::new();
+    for id in found {
+        if !seen.insert(id.to_string()) {
+            violations.push(InvariantViolation::Duplicate {
+                walk,
+                id: id.clone(),
+            });
+        }
+    }
+    for id in expected {
+        if !seen.contains(&id.to_string()) {
+            violations.push(InvariantViolation::Gap {
+                walk,
+                id: id.clone(),
+            });
+        }
+    }
+    if let Some((position, (expected, found))) = expected
+        .iter()
+        .zip(found)
+        .enumerate()
+        .find(|(_, (expected, found))| expected != found)
+    {
+        violations.push(InvariantViolation::OutOfOrder {
+            walk,
+            position,
+            expected: expected.clone(),
+            found: found.clone(),
+        });
+    }
+}
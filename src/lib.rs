@@ -1,4 +1,4 @@
-#![doc = include_str!("../README.md")]
+#![cfg_attr(doc, doc = include_str!("../README.md"))]
 
 //! ### Usage:
 //! The usage is a bit different than the node version. See the examples for more details and a working example.
@@ -82,9 +82,10 @@
 //! ### Response
 //! The response `FindResult<T>` contains page info, cursors and edges (cursors for all of the items in the response).
 //! ```rust
+//! # use mongodb_cursor_pagination::Count;
 //! pub struct PageInfo {
-//!     pub has_next_page: bool,
-//!     pub has_previous_page: bool,
+//!     pub has_next_page: Option<bool>,
+//!     pub has_previous_page: Option<bool>,
 //!     pub start_cursor: Option<String>,
 //!     pub end_cursor: Option<String>,
 //! }
@@ -96,158 +97,2233 @@
 //! pub struct FindResult<T> {
 //!     pub page_info: PageInfo,
 //!     pub edges: Vec<Edge>,
-//!     pub total_count: i64,
+//!     pub total_count: Count,
 //!     pub items: Vec<T>,
 //! }
 //! ```
 //!
 //! ## Features
-//! It has support for graphql (using [juniper](https://github.com/graphql-rust/juniper)) if you enable the `graphql` flag. You can use it by just including the `PageInfo` into your code.
+//! It has support for graphql (using [juniper](https://github.com/graphql-rust/juniper) 0.16) if you enable the `graphql` flag.
+//! `PageInfo` and `Edge` are GraphQL objects, and `graphql_connection!` declares a connection type for your items, which
+//! a resolver returns straight from a `FindResult`.
+//!
+//! Cursors are exposed as the `Cursor` scalar, which is validated when the query is parsed. The `async-graphql` flag
+//! implements the same scalar for [async-graphql](https://github.com/async-graphql/async-graphql).
 //!
 //! ```ignore
-//! use mongodb_cursor_pagination::{PageInfo, Edge};
+//! use mongodb_cursor_pagination::{graphql_connection, FindResult, Pagination};
 //!
-//! #[derive(Serialize, Deserialize)]
-//! struct MyDataConnection {
-//!     page_info: PageInfo,
-//!     edges: Vec<Edge>,
-//!     data: Vec<MyData>,
-//!     total_count: i64,
+//! #[derive(Clone, Debug, Deserialize, juniper::GraphQLObject)]
+//! struct MyData {
+//!     name: String,
 //! }
 //!
-//! [juniper::object]
-//! impl MyDataConnection {
-//!     fn page_info(&self) -> &PageInfo {
-//!         self.page_info
-//!     }
+//! graphql_connection!(MyDataConnection, MyDataEdge, MyData);
 //!
-//!     fn edges(&self) -> &Vec<Edge> {
-//!         &self.edges
+//! #[juniper::graphql_object(context = Context)]
+//! impl Query {
+//!     async fn my_data(context: &Context) -> FieldResult<MyDataConnection> {
+//!         let result: FindResult<MyData> = context.collection.find_paginated(None, None, None).await?;
+//!         Ok(result.into())
 //!     }
 //! }
 //! ```
+//!
+//! The `invariants` flag adds `check_invariants` and `assert_invariants`, which walk every page of a query forward and
+//! backward and report duplicates, gaps and wrong `has_*` flags. Run them in CI against your own schemas and sorts.
+//!
+//! ```ignore
+//! mongodb_cursor_pagination::assert_invariants(&fruits, None, doc! { "how_many": 1, "name": -1 }, 3).await;
+//! ```
+//!
+//! The `tracing` flag instruments `find_paginated`, the count query and the `has_page` peek queries with
+//! [tracing](https://github.com/tokio-rs/tracing) spans. They record the collection name, limit, direction, a hash of
+//! the filter's shape (keys and value types, never values) and the elapsed time.
+//!
+//! The `metrics` flag adds `MetricsRecorder`, a [`PaginationMetrics`] implementation reporting pages served, items per
+//! page, count durations and cursor decode failures to the [metrics](https://docs.rs/metrics) facade. Any other
+//! backend can be hooked up by implementing [`PaginationMetrics`] and passing it to a [`Paginator`]:
+//!
+//! ```ignore
+//! use mongodb_cursor_pagination::{MetricsRecorder, PaginationConfig, Paginator};
+//!
+//! let fruits = Paginator::new(fruits, PaginationConfig::default().metrics(MetricsRecorder::default()));
+//! ```
+//!
+//! The `tokio` flag adds `Prefetcher`, which fetches the next page in a background task on the Tokio runtime while the
+//! current one is processed. Without it the crate spawns no tasks.
+//!
+//! The `compression` flag adds `CursorCodec::compress`, which deflates cursors of sorts on many or long keys. Compressed
+//! cursors are detected on decode, so a codec can start compressing while older cursors are still in circulation.
+//!
+//! The `http` flag adds `FindResult::link_header`, which renders the standard `Link` header of a page for REST APIs:
+//! ```ignore
+//! let page: FindResult<MyFruit> = fruits.find_paginated(None, Some(options), cursor).await?;
+//! if let Some(link) = page.link_header("https://example.com/fruits?limit=10")? {
+//!     response.headers_mut().insert(http::header::LINK, link);
+//! }
+//! ```
+
+mod adaptive;
+mod align;
+mod binary;
+mod cache;
+mod changes;
+mod checkpoint;
+mod codec;
+mod compare;
+mod composite;
+mod config;
+mod cursor;
+mod drift;
+mod error;
+mod event;
+#[cfg(feature = "graphql")]
+mod graphql;
+mod grouped;
+mod id;
+mod insertion;
+mod inspect;
+#[cfg(feature = "invariants")]
+mod invariant;
+mod link;
+mod merge;
+mod metrics;
+mod model;
+mod option;
+mod partition;
+mod plan;
+mod position;
+#[cfg(feature = "tokio")]
+mod prefetch;
+mod projection;
+mod relay;
+mod retry;
+mod session;
+mod sort;
+#[cfg(feature = "structured-cursors")]
+pub mod structured;
+mod trace;
+mod write;
+pub use adaptive::AdaptiveLimit;
+pub use cache::{LruPageCache, PageCache, PageKey};
+pub use checkpoint::Checkpoint;
+pub use codec::{CursorCodec, CursorEncoding};
+pub use composite::CompositeCursor;
+pub use config::*;
+pub use cursor::Cursor;
+pub use error::CursorError;
+pub use event::*;
+pub use grouped::GroupedPages;
+pub use id::{parse_id, ParseId};
+pub use inspect::CursorInspector;
+#[cfg(feature = "invariants")]
+pub use invariant::*;
+pub use link::PageLinks;
+pub use merge::{merge_pages, MergedPage};
+pub use metrics::*;
+pub use model::*;
+pub use option::{CursorOptions, CursorOptionsBuilder};
+pub use partition::PageStream;
+pub use plan::{EffectiveOptions, QueryPlan};
+pub use position::InsertPosition;
+#[cfg(feature = "tokio")]
+pub use prefetch::Prefetcher;
+pub use projection::{projection_of, FindResultProjector};
+pub use relay::{Connection, ConnectionEdge, RelayPageInfo};
+pub use retry::RetryPolicy;
+pub use sort::{Sort, SortDirection};
+
+use crate::option::{cursor_key, sort_value};
+use crate::session::SessionLock;
+use crate::trace::instrumented;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bson::{doc, Bson, Document, RawDocumentBuf};
+use futures_timer::Delay;
+use futures_util::future;
+use futures_util::stream::StreamExt;
+use futures_util::{TryStream, TryStreamExt};
+use mongodb::error::Error as MongoError;
+use mongodb::options::{CountOptions, CursorType, EstimatedDocumentCountOptions};
+use mongodb::{options::FindOptions, ClientSession, Collection};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+#[async_trait]
+/// Used to paginate through a collection.
+pub trait Pagination {
+    /// Finds the items in the collection matching `filter` based on the `cursor`.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options that you would like to perform any searches with
+    /// * `cursor`: An optional existing cursor in base64. This would have come from a previous `FindResult<T>`
+    async fn find_paginated<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        cursor: Option<DirectedCursor>,
+    ) -> Result<FindResult<T>, CursorError>
+    where
+        T: DeserializeOwned + Send;
+
+    /// Estimates the number of documents in the whole collection from its metadata, which is
+    /// much cheaper than the exact `total_count` but ignores any filter.
+    ///
+    /// # Arguments
+    /// * `options`: Optional find options, only `max_time`, `selection_criteria`, `read_concern` and `comment` are used
+    async fn estimated_count(&self, options: Option<FindOptions>) -> Result<u64, CursorError>;
+
+    /// Counts the items matching `filter`, but stops once more than `bound` are found. Cheaper
+    /// than an exact count on large collections when only "500+" is shown anyway.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the counted items, merged with the ambient filter
+    /// * `bound`: Largest count reported exactly, [`Count::AtLeast`] is returned above it
+    /// * `options`: Optional find options, the sort, limit, skip and projection are ignored
+    async fn count_up_to(
+        &self,
+        filter: Option<Document>,
+        bound: u64,
+        options: Option<FindOptions>,
+    ) -> Result<Count, CursorError>;
+
+    /// Applies `update` to all items matching `filter`, walking them page by page in sort order
+    /// and updating one page at a time. Returns the number of modified items.
+    ///
+    /// Updating the sort keys can move items behind the cursor, so they may be skipped or updated twice.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the updated items
+    /// * `options`: Optional find options, the limit is used as batch size and defaults to 1000
+    /// * `update`: The update document applied to every batch, e.g. `{ "$set": { "archived": true } }`
+    async fn update_paginated(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        update: Document,
+    ) -> Result<u64, CursorError>;
+
+    /// Pages through all items matching `filter` inside one transaction on `session` and hands
+    /// every page to `callback`, so all pages share the same consistent view. If the transaction
+    /// fails with a transient error, it is retried from the first page, so the callback has to
+    /// cope with seeing pages again. A callback error aborts the transaction.
+    ///
+    /// The transaction uses the default transaction options of `session`. Instead of retrying
+    /// single queries, the [`RetryPolicy`] limits the attempts of the whole transaction.
+    ///
+    /// # Arguments
+    /// * `session`: The session to run the transaction on
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options, the limit is used as page size
+    /// * `callback`: Called with every page
+    async fn for_each_page_in_transaction<T, F, Fut, E>(
+        &self,
+        session: &mut ClientSession,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        callback: F,
+    ) -> Result<(), E>
+    where
+        T: DeserializeOwned + Send,
+        F: FnMut(FindResult<T>) -> Fut + Send,
+        Fut: Future<Output = Result<(), E>> + Send,
+        E: From<CursorError> + Send;
+
+    /// Deletes all items matching `filter`, walking them page by page in sort order and deleting
+    /// one page at a time. Returns the number of deleted items.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the deleted items
+    /// * `options`: Optional find options, the limit is used as batch size and defaults to 1000
+    async fn delete_paginated(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> Result<u64, CursorError>;
+
+    /// Finds the first page of the items of every parent in `parents` with a single query, e.g.
+    /// to resolve the comments of many posts at once in a GraphQL dataloader. The pages are
+    /// returned in the order of `parents` and continue like any other page, with
+    /// `{ parent_key: parent }` added to the filter. They aren't counted, and the skip and
+    /// projection of the options are ignored. Requires `MongoDB` 5.2 or newer for a limit.
+    ///
+    /// # Arguments
+    /// * `parent_key`: The field holding the parent of an item, e.g. `post_id`
+    /// * `parents`: The parents to find the items of
+    /// * `filter`: Optional filter to restrict the items of every parent
+    /// * `options`: Optional find options, the limit is used as page size of every parent
+    async fn find_first_pages_by_parent<T>(
+        &self,
+        parent_key: &str,
+        parents: Vec<Bson>,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> Result<GroupedPages<T>, CursorError>
+    where
+        T: DeserializeOwned + Send;
+
+    /// Finds the first page of the items of every parent matching `filter` with a single query,
+    /// e.g. for a list of lists. The pages are returned in ascending order of their parent and
+    /// continue like [`Pagination::find_first_pages_by_parent`]. Requires `MongoDB` 5.0 or newer
+    /// for a limit.
+    ///
+    /// # Arguments
+    /// * `parent_key`: The field holding the parent of an item, e.g. `post_id`
+    /// * `filter`: Optional filter to restrict the items
+    /// * `options`: Optional find options, the limit is used as page size of every parent
+    async fn find_paginated_grouped<T>(
+        &self,
+        parent_key: &str,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> Result<GroupedPages<T>, CursorError>
+    where
+        T: DeserializeOwned + Send;
+
+    /// Splits the items matching `filter` into up to `workers` disjoint ranges of the first sort
+    /// key and returns the pages of every range as an independent stream, e.g. to process a
+    /// large collection in parallel. Every stream is in sort order, and so are the ranges among
+    /// each other. The split points are found with `$bucketAuto`, with the collation, read
+    /// concern and hint of `options`. The items whose first sort key is `null`, missing or of
+    /// another type than the split points come in one more stream, first for an ascending sort.
+    /// That stream is only in order with the others if the key has a single type besides
+    /// `null`: other types sort before or after the ranges by their BSON type, e.g. strings
+    /// after numbers.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the items
+    /// * `options`: Optional find options, the limit is used as page size, a skip is ignored
+    /// * `workers`: The number of ranges to split into, fewer for few distinct values
+    async fn partition_scan<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        workers: usize,
+    ) -> Result<Vec<PageStream<T>>, CursorError>
+    where
+        T: DeserializeOwned + Send + 'static;
+
+    /// Normalizes and validates the options and builds the filter of the page `find_paginated`
+    /// would fetch, without running any query, e.g. to log it or to reject a request early.
+    /// The projection derived from the item type with `minimize_projection` isn't applied.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options that you would like to perform any searches with
+    /// * `cursor`: An optional existing cursor, as passed to `find_paginated`
+    fn plan(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        cursor: Option<DirectedCursor>,
+    ) -> Result<QueryPlan, CursorError>;
+
+    /// Like [`Pagination::find_paginated`], but takes a typed `filter` that is serialized into a
+    /// [`Document`], e.g. a query struct or the output of a filter builder.
+    ///
+    /// # Arguments
+    /// * `filter`: The filter to restrict the result set of the query, serializing into a document
+    /// * `options`: Optional find options that you would like to perform any searches with
+    /// * `cursor`: An optional existing cursor. This would have come from a previous `FindResult<T>`
+    async fn find_paginated_with_filter<T, F>(
+        &self,
+        filter: &F,
+        options: Option<FindOptions>,
+        cursor: Option<DirectedCursor>,
+    ) -> Result<FindResult<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+        F: Serialize + Sync + ?Sized,
+        Self: Sync,
+    {
+        let filter = bson::to_document(filter)?;
+        self.find_paginated(Some(filter), options, cursor).await
+    }
+
+    /// Finds the first page of the items matching `filter`.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options, the limit is used as page size
+    async fn find_first_page<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> Result<FindResult<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+        Self: Sync,
+    {
+        self.find_paginated(filter, options, None).await
+    }
+
+    /// Finds the last page of the items matching `filter`, still in sort order. Its
+    /// `start_cursor` continues with the pages before it.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options, the limit is used as page size
+    async fn find_last_page<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> Result<FindResult<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+        Self: Sync,
+    {
+        self.find_paginated(filter, options, Some(DirectedCursor::from_end()))
+            .await
+    }
+
+    /// Finds the page ending right before `before`, with the same items as the page of the
+    /// forward walk ending there, so walking back from the end returns the pages of the forward
+    /// walk in reverse. Paging backwards with `find_paginated` instead fills every page and
+    /// leaves a partial page at the start. The items before the page are counted to align it.
+    ///
+    /// Pass the edge of the `start_cursor` of a page to get the page before it, `None` for the
+    /// last page.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options, the limit is used as page size and the skip leaves
+    ///   out the first items just like on the first page of the forward walk
+    /// * `before`: The edge of the item after the page, `None` for the last page
+    async fn find_paginated_back<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        before: Option<Edge>,
+    ) -> Result<FindResult<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+        Self: Sync,
+    {
+        let mut options = options.unwrap_or_default();
+        let skip = options.skip.take().unwrap_or_default();
+        let has_next_page = before.is_some();
+        let cursor = before.map_or_else(DirectedCursor::from_end, DirectedCursor::Backwards);
+        let plan = self.plan(filter.clone(), Some(options.clone()), Some(cursor.clone()))?;
+        // Counted with the collation, hint and read concern of the find, so both see the same items.
+        let preceding = self
+            .count_up_to(
+                Some(plan.effective_options.filter),
+                u64::MAX,
+                Some(options.clone()),
+            )
+            .await?
+            .value()
+            .unwrap_or_default();
+        let walked = preceding.saturating_sub(skip);
+        if walked == 0 {
+            return Ok(model::empty_page(PageInfo {
+                has_previous_page: Some(false),
+                has_next_page: Some(has_next_page),
+                ..PageInfo::default()
+            }));
+        }
+        // The page nearest the end cursor holds the remainder, so the pages before it line up
+        // with the pages of the forward walk.
+        let size = match options
+            .limit
+            .and_then(|limit| u64::try_from(limit).ok())
+            .and_then(|limit| walked.checked_rem(limit).map(|rest| (limit, rest)))
+        {
+            Some((limit, 0)) => limit,
+            Some((_limit, rest)) => rest,
+            None => walked,
+        };
+        options.limit = Some(i64::try_from(size).unwrap_or(i64::MAX));
+        let mut page = self
+            .find_paginated(filter, Some(options), Some(cursor))
+            .await?;
+        page.page_info.has_previous_page = Some(walked > size);
+        Ok(page)
+    }
+
+    /// Finds the page following `checkpoint`, e.g. one stored by an export before the process
+    /// restarted. Returns an empty last page once the checkpoint is finished.
+    ///
+    /// # Arguments
+    /// * `filter`: The filter the walk was started with
+    /// * `options`: The find options the walk was started with, the skip only applies once
+    /// * `checkpoint`: The progress of the walk, see [`Checkpoint`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CursorError::CheckpointMismatch`] if the filter or sort differ from the ones
+    /// the walk was started with.
+    async fn resume_from<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        checkpoint: &Checkpoint,
+    ) -> Result<FindResult<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+        Self: Sync,
+    {
+        checkpoint.check(filter.as_ref(), options.as_ref())?;
+        if checkpoint.is_finished() {
+            return Ok(checkpoint::finished_page());
+        }
+        let mut options = options;
+        if checkpoint.cursor().is_some() {
+            skip_once(&mut options);
+        }
+        let cursor = checkpoint.cursor().cloned().map(DirectedCursor::Forward);
+        self.find_paginated(filter, options, cursor).await
+    }
+
+    /// Finds the page following the item with the `_id` `id`, for sorts on `_id` only, without
+    /// building an [`Edge`] first. Fails with [`CursorError::InvalidCombination`] for other sorts.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options, sorted by `_id` or not at all
+    /// * `id`: The `_id` of the item before the page, e.g. an `ObjectId`
+    async fn find_paginated_after_id<T, I>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        id: I,
+    ) -> Result<FindResult<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+        I: Into<Bson> + Send,
+        Self: Sync,
+    {
+        let options = options.unwrap_or_default();
+        if options
+            .sort
+            .iter()
+            .flat_map(Document::keys)
+            .any(|key| key.as_str() != "_id")
+        {
+            return Err(CursorError::InvalidCombination(
+                "`find_paginated_after_id` requires a sort on `_id` only",
+            ));
+        }
+        let edge = Edge::new(
+            &doc! { "_id": id.into() },
+            &CursorOptions::new(options.clone(), None),
+        );
+        self.find_paginated(filter, Some(options), Some(DirectedCursor::Forward(edge)))
+            .await
+    }
+
+    /// Finds the page described by the arguments of a GraphQL connection, including `last: N`
+    /// without a cursor, which returns the last page of the result set.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options, the limit is replaced by `first` or `last`
+    /// * `args`: The connection arguments, see [`ConnectionArgs`]
+    async fn find_connection<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        args: ConnectionArgs,
+    ) -> Result<FindResult<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+        Self: Sync,
+    {
+        let (limit, cursor) = args.into_request();
+        let mut options = options.unwrap_or_default();
+        if limit.is_some() {
+            options.limit = limit;
+        }
+        self.find_paginated(filter, Some(options), cursor).await
+    }
+
+    /// Like [`Pagination::find_connection`], but strictly follows the Relay specification: the
+    /// arguments are validated with [`ConnectionArgs::validate`] and the page is returned as a
+    /// [`Connection`] with a `node` and a `cursor` per edge. If the flag in the direction of the
+    /// page is unknown, e.g. with [`HasPageStrategy::Skip`], one more item is peeked for it.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options, the limit is replaced by `first` or `last`
+    /// * `args`: The connection arguments, see [`ConnectionArgs`]
+    async fn find_relay_connection<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        args: ConnectionArgs,
+    ) -> Result<Connection<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+        Self: Sync,
+    {
+        args.validate()?;
+        let backwards = args.last.is_some() || args.before.is_some();
+        let mut page = self
+            .find_connection(filter.clone(), options.clone(), args.clone())
+            .await?;
+        let info = &page.page_info;
+        let (flag, next) = if backwards {
+            (info.has_previous_page, info.previous_request())
+        } else {
+            (info.has_next_page, info.next_request())
+        };
+        if flag.is_none() {
+            let more = match next {
+                Some(next) => {
+                    let mut peek = options.unwrap_or_default();
+                    peek.limit = Some(1);
+                    peek.skip = None;
+                    let peeked: FindResult<Document> =
+                        self.find_paginated(filter, Some(peek), Some(next)).await?;
+                    !peeked.items.is_empty()
+                }
+                None => false,
+            };
+            if backwards {
+                page.page_info.has_previous_page = Some(more);
+            } else {
+                page.page_info.has_next_page = Some(more);
+            }
+        }
+        Ok(Connection::relay(page, &args))
+    }
+
+    /// Pages through all items matching `filter` and returns them in sort order. Fails with
+    /// [`CursorError::TooManyItems`] as soon as more than `max_items` are found. The pages are
+    /// walked with [`CountMode::Skip`], whatever the configured count mode.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options, the limit is used as page size and defaults to `max_items + 1`
+    /// * `max_items`: Maximum number of items to collect
+    async fn collect_all<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        max_items: usize,
+    ) -> Result<Vec<T>, CursorError>
+    where
+        T: DeserializeOwned + Send;
+
+    /// Finds the pages with the given numbers concurrently by skipping to them, e.g. for a report
+    /// that needs several specific pages at once. The pages are returned in the order of
+    /// `page_numbers`, each with its own cursors. Skipping is slow for pages far from the start.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options, their limit and skip are replaced
+    /// * `page_numbers`: The numbers of the pages to find, starting at 1
+    /// * `page_size`: The number of items per page
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CursorError::InvalidPageNumber`] for page number 0 and the first error of any
+    /// of the queries.
+    async fn find_pages_parallel<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        page_numbers: &[u64],
+        page_size: i64,
+    ) -> Result<Vec<FindResult<T>>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+        Self: Sync,
+    {
+        let size =
+            u64::try_from(page_size).map_err(|_negative| CursorError::NegativeLimit(page_size))?;
+        let mut pages = Vec::with_capacity(page_numbers.len());
+        for page_number in page_numbers {
+            let skip = page_number
+                .checked_sub(1)
+                .ok_or(CursorError::InvalidPageNumber(*page_number))?
+                .saturating_mul(size);
+            let mut options = options.clone().unwrap_or_default();
+            options.limit = Some(page_size);
+            options.skip = Some(skip);
+            pages.push(self.find_paginated(filter.clone(), Some(options), None));
+        }
+        future::try_join_all(pages).await
+    }
+
+    /// Pages through all items matching `filter` in sort order and hands every page to `callback`.
+    /// The next page is only fetched once the callback finished, and the first error of either
+    /// the callback or a query stops the iteration.
+    ///
+    /// # Arguments
+    /// * `filter`: Optional filter to restrict the result set of the query.
+    /// * `options`: Optional find options, the limit is used as page size
+    /// * `callback`: Called with every page, e.g. to migrate or export its items
+    async fn for_each_page<T, F, Fut, E>(
+        &self,
+        filter: Option<Document>,
+        mut options: Option<FindOptions>,
+        mut callback: F,
+    ) -> Result<(), E>
+    where
+        T: DeserializeOwned + Send,
+        F: FnMut(FindResult<T>) -> Fut + Send,
+        Fut: Future<Output = Result<(), E>> + Send,
+        E: From<CursorError> + Send,
+        Self: Sync,
+    {
+        let mut cursor = None;
+        loop {
+            let page: FindResult<T> = self
+                .find_paginated(filter.clone(), options.clone(), cursor)
+                .await?;
+            cursor = page.page_info.next_request();
+            skip_once(&mut options);
+            callback(page).await?;
+            if cursor.is_none() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<I: Send + Sync> Pagination for Collection<I> {
+    async fn find_paginated<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        cursor: Option<DirectedCursor>,
+    ) -> Result<FindResult<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+    {
+        find(
+            &self.clone_with_type(),
+            &PaginationConfig::default(),
+            filter,
+            options,
+            cursor,
+            None,
+        )
+        .await
+    }
+
+    async fn estimated_count(&self, options: Option<FindOptions>) -> Result<u64, CursorError> {
+        estimated_count(self, &PaginationConfig::default(), options).await
+    }
+
+    async fn collect_all<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        max_items: usize,
+    ) -> Result<Vec<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+    {
+        collect_all(
+            &self.clone_with_type(),
+            &PaginationConfig::default(),
+            filter,
+            options,
+            max_items,
+        )
+        .await
+    }
+
+    async fn find_first_pages_by_parent<T>(
+        &self,
+        parent_key: &str,
+        parents: Vec<Bson>,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> Result<GroupedPages<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+    {
+        grouped::find_first_pages_by_parent(
+            &self.clone_with_type(),
+            &PaginationConfig::default(),
+            parent_key,
+            parents,
+            filter,
+            options,
+        )
+        .await
+    }
+
+    async fn find_paginated_grouped<T>(
+        &self,
+        parent_key: &str,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> Result<GroupedPages<T>, CursorError>
+    where
+        T: DeserializeOwned + Send,
+    {
+        grouped::find_paginated_grouped(
+            &self.clone_with_type(),
+            &PaginationConfig::default(),
+            parent_key,
+            filter,
+            options,
+        )
+        .await
+    }
+
+    async fn partition_scan<T>(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        workers: usize,
+    ) -> Result<Vec<PageStream<T>>, CursorError>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        partition::partition_scan(
+            self.clone_with_type(),
+            PaginationConfig::default(),
+            filter,
+            options,
+            workers,
+        )
+        .await
+    }
+
+    fn plan(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        cursor: Option<DirectedCursor>,
+    ) -> Result<QueryPlan, CursorError> {
+        plan(&PaginationConfig::default(), filter, options, cursor)
+    }
+
+    async fn count_up_to(
+        &self,
+        filter: Option<Document>,
+        bound: u64,
+        options: Option<FindOptions>,
+    ) -> Result<Count, CursorError> {
+        count_up_to(
+            self,
+            &PaginationConfig::default(),
+            filter,
+            bound,
+            options,
+            None,
+        )
+        .await
+    }
+
+    async fn update_paginated(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        update: Document,
+    ) -> Result<u64, CursorError> {
+        write::update_paginated(
+            &self.clone_with_type(),
+            &PaginationConfig::default(),
+            filter,
+            options,
+            update,
+        )
+        .await
+    }
+
+    async fn delete_paginated(
+        &self,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> Result<u64, CursorError> {
+        write::delete_paginated(
+            &self.clone_with_type(),
+            &PaginationConfig::default(),
+            filter,
+            options,
+        )
+        .await
+    }
+
+    async fn for_each_page_in_transaction<T, F, Fut, E>(
+        &self,
+        session: &mut ClientSession,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+        callback: F,
+    ) -> Result<(), E>
+    where
+        T: DeserializeOwned + Send,
+        F: FnMut(FindResult<T>) -> Fut + Send,
+        Fut: Future<Output = Result<(), E>> + Send,
+        E: From<CursorError> + Send,
+    {
+        session::for_each_page_in_transaction(
+            &self.clone_with_type(),
+            &PaginationConfig::default(),
+            session,
+            filter,
+            options,
+            callback,
+        )
+        .await
+    }
+}
+
+/// Finds a page, moving on to the following page while it comes back empty although more items
+/// are known to follow, at most [`PaginationConfig::skip_empty_pages`] times.
+async fn find<T: DeserializeOwned>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Option<Document>,
+    mut find_options: Option<FindOptions>,
+    cursor: Option<DirectedCursor>,
+    session: Option<&SessionLock<'_>>,
+) -> Result<FindResult<T>, CursorError> {
+    let backwards = matches!(cursor, Some(DirectedCursor::Backwards(_)));
+    let mut page = Box::pin(find_refilled(
+        collection,
+        config,
+        filter.clone(),
+        find_options.clone(),
+        cursor,
+        session,
+    ))
+    .await?;
+    for _ in 0..config.empty_page_retries {
+        let (more, following) = if backwards {
+            let info = &page.page_info;
+            (info.has_previous_page, info.previous_request())
+        } else {
+            (page.page_info.has_next_page, page.page_info.next_request())
+        };
+        let Some(following) = following.filter(|_| page.items.is_empty() && more == Some(true))
+        else {
+            break;
+        };
+        skip_once(&mut find_options);
+        page = Box::pin(find_refilled(
+            collection,
+            config,
+            filter.clone(),
+            find_options.clone(),
+            Some(following),
+            session,
+        ))
+        .await?;
+    }
+    Ok(page)
+}
+
+/// Finds a page, replacing a short page at the start of a backwards walk with the first page
+/// according to [`BackwardStart`].
+async fn find_refilled<T: DeserializeOwned>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Option<Document>,
+    find_options: Option<FindOptions>,
+    cursor: Option<DirectedCursor>,
+    session: Option<&SessionLock<'_>>,
+) -> Result<FindResult<T>, CursorError> {
+    let cursor = cursor.map(|cursor| binary::coerce(&config.binary_subtypes, cursor));
+    let refill = (config.backward_start == BackwardStart::Refill
+        && matches!(cursor, Some(DirectedCursor::Backwards(_))))
+    .then(|| (filter.clone(), find_options.clone()));
+    let page_size = find_options.as_ref().and_then(|options| options.limit);
+    let (find_options, cursor) = if config.align_pages {
+        Box::pin(align::align(
+            collection,
+            config,
+            filter.as_ref(),
+            find_options,
+            cursor,
+            session,
+        ))
+        .await?
+    } else {
+        (find_options, cursor)
+    };
+    let mut page = Box::pin(find_page(
+        collection,
+        config,
+        filter,
+        find_options,
+        cursor,
+        session,
+    ))
+    .await?;
+    if config.align_pages && config.page_numbers {
+        set_page_numbers(&mut page.page_info, page_size, page.total_count);
+    }
+    let Some((first_filter, first_options)) = refill else {
+        return check_unlimited(config, page_size, page);
+    };
+    let limit = first_options
+        .as_ref()
+        .and_then(|options| options.limit)
+        .or_else(|| config.missing_limit.limit());
+    let short = limit
+        .and_then(|limit| usize::try_from(limit).ok())
+        .is_some_and(|limit| page.items.len() < limit);
+    // A page can also be short because of `max_page_bytes` or mismatched items, only a page
+    // known to reach the start is refilled.
+    if !short || page.page_info.has_previous_page != Some(false) {
+        return check_unlimited(config, page_size, page);
+    }
+    let first_page = Box::pin(find_page(
+        collection,
+        config,
+        first_filter,
+        first_options,
+        None,
+        session,
+    ))
+    .await?;
+    check_unlimited(config, page_size, first_page)
+}
+
+/// Fails a page requested without a limit with [`CursorError::TooManyItems`] if it holds more
+/// items than [`MissingLimit::UpTo`] allows. A page of an [`AdaptiveLimit`] always has a limit.
+pub(crate) fn check_unlimited<T>(
+    config: &PaginationConfig,
+    limit: Option<i64>,
+    page: FindResult<T>,
+) -> Result<FindResult<T>, CursorError> {
+    let (MissingLimit::UpTo(max_items), None, None) =
+        (config.missing_limit, limit, &config.adaptive_limit)
+    else {
+        return Ok(page);
+    };
+    let max_items = usize::try_from(max_items).unwrap_or(usize::MAX);
+    if page.items.len().saturating_add(page.mismatched.len()) > max_items {
+        return Err(CursorError::TooManyItems(max_items));
+    }
+    Ok(page)
+}
+
+/// Finds a single page, see [`find`].
+async fn find_page<T: DeserializeOwned>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Option<Document>,
+    find_options: Option<FindOptions>,
+    cursor: Option<DirectedCursor>,
+    session: Option<&SessionLock<'_>>,
+) -> Result<FindResult<T>, CursorError> {
+    let mut find_options = find_options.unwrap_or_default();
+    if config.minimize_projection && find_options.projection.is_none() {
+        find_options.projection = projection_of::<T>();
+    }
+    if let Some(adaptive) = &config.adaptive_limit {
+        find_options.limit = adaptive.limit(collection, config, find_options.limit).await;
+    }
+    let options = prepare(config, find_options.clone(), cursor.clone());
+    let mut filter = config.scope(filter).unwrap_or_default();
+    validate(&options, cursor.as_ref(), config)?;
+    insertion::check_sort(collection.name(), config, &options);
+    let bound = match &config.insertion_key {
+        Some(key) => {
+            let bound = insertion::insertion_bound(
+                collection,
+                config,
+                key,
+                &filter,
+                &options,
+                cursor.as_ref(),
+                session,
+            )
+            .await?;
+            if let Some(bound) = &bound {
+                filter = insertion::bounded(filter, key, bound);
+            }
+            bound
+        }
+        None => None,
+    };
+
+    let drift_cursor = cursor.clone().filter(|_| config.drift_window.is_some());
+    let mut result: Result<FindResult<T>, CursorError> = instrumented!(
+        tracing::info_span!(
+            "find_paginated",
+            collection = collection.name(),
+            limit = options.limit,
+            direction = trace::direction(cursor.as_ref()),
+            filter_shape = trace::filter_shape(&filter),
+            elapsed = trace::Empty,
+        ),
+        async {
+            let page = Box::pin(fetch(
+                collection,
+                config,
+                filter,
+                find_options,
+                options,
+                cursor,
+                session,
+            ));
+            match config.deadline {
+                Some(deadline) => match future::select(page, Delay::new(deadline)).await {
+                    future::Either::Left((page, _delay)) => page,
+                    future::Either::Right(((), _page)) => {
+                        Err(CursorError::DeadlineExceeded(deadline))
+                    }
+                },
+                None => page.await,
+            }
+        }
+    );
+
+    if let (Ok(page), Some(bound)) = (&mut result, &bound) {
+        insertion::attach_bound(page, bound);
+    }
+    if let (Ok(page), Some(window)) = (&mut result, config.drift_window) {
+        drift::track(page, drift_cursor.as_ref(), window, config.reverse_results);
+    }
+    if let Ok(page) = &mut result {
+        if let Err(error) = codec::attach(page, &config.codec) {
+            result = Err(error);
+        }
+    }
+    if let Some(metrics) = &config.metrics {
+        match &result {
+            Ok(found) => metrics.page_served(collection.name(), found.items.len()),
+            Err(
+                CursorError::InvalidCursor
+                | CursorError::MissingSortKey(_)
+                | CursorError::UnknownCursorKey(_)
+                | CursorError::CursorFieldTypeMismatch { .. }
+                | CursorError::CollationMismatch,
+            ) => metrics.cursor_decode_failure(collection.name()),
+            Err(_) => {}
+        }
+    }
+    result
+}
+
+/// Builds the [`QueryPlan`] of a page without running it.
+fn plan(
+    config: &PaginationConfig,
+    filter: Option<Document>,
+    find_options: Option<FindOptions>,
+    cursor: Option<DirectedCursor>,
+) -> Result<QueryPlan, CursorError> {
+    let cursor = cursor.map(|cursor| binary::coerce(&config.binary_subtypes, cursor));
+    let options = prepare(config, find_options.unwrap_or_default(), cursor.clone());
+    let mut filter = config.scope(filter).unwrap_or_default();
+    validate(&options, cursor.as_ref(), config)?;
+    if let (Some(key), Some(bound)) = (
+        &config.insertion_key,
+        cursor
+            .as_ref()
+            .and_then(|cursor| cursor.inner().insertion_bound()),
+    ) {
+        filter = insertion::bounded(filter, key, bound);
+    }
+    let query = get_query(filter, &options, cursor.as_ref(), config.unique_sort)?;
+    let overfetch = config.has_page == HasPageStrategy::OverFetch;
+    Ok(QueryPlan {
+        effective_options: EffectiveOptions::new(query, &options, overfetch),
+        find_options: page_find_options(&options, overfetch),
+        cursor,
+    })
+}
+
+/// Drops the skip of `options` after the first page of a walk over all pages, which continues
+/// right after the previous page.
+pub(crate) fn skip_once(options: &mut Option<FindOptions>) {
+    if let Some(find_options) = options.as_mut() {
+        find_options.skip = None;
+    }
+}
+
+/// Pages through all items matching `filter` for [`Pagination::collect_all`]. The pages are
+/// not counted, the walk only needs their flags.
+async fn collect_all<T: DeserializeOwned>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Option<Document>,
+    options: Option<FindOptions>,
+    max_items: usize,
+) -> Result<Vec<T>, CursorError> {
+    let config = config.clone().count_mode(CountMode::Skip);
+    let mut options = options.unwrap_or_default();
+    if options.limit.is_none() {
+        options.limit = Some(i64::try_from(max_items.saturating_add(1)).unwrap_or(i64::MAX));
+    }
+    let mut options = Some(options);
+    let mut items = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page: FindResult<T> = Box::pin(find(
+            collection,
+            &config,
+            filter.clone(),
+            options.clone(),
+            cursor,
+            None,
+        ))
+        .await?;
+        if items.len().saturating_add(page.items.len()) > max_items {
+            return Err(CursorError::TooManyItems(max_items));
+        }
+        cursor = page.page_info.next_request();
+        skip_once(&mut options);
+        items.extend(page.items);
+        if cursor.is_none() {
+            return Ok(items);
+        }
+    }
+}
+
+/// Applies the `max_time` and `comment` of `config` to the options of any query, falling back to
+/// `default_comment` unless the options carry a comment of their own.
+fn apply_query_settings(
+    config: &PaginationConfig,
+    options: &mut FindOptions,
+    default_comment: impl FnOnce() -> String,
+) {
+    if let Some(max_time) = config.max_time {
+        options.max_time = Some(max_time);
+    }
+    if options.comment.is_none() && options.comment_bson.is_none() {
+        options.comment = Some(config.comment.clone().unwrap_or_else(default_comment));
+    }
+}
+
+/// Applies the settings of `config` to the find options of a single page.
+fn prepare(
+    config: &PaginationConfig,
+    mut options: FindOptions,
+    cursor: Option<DirectedCursor>,
+) -> CursorOptions {
+    if options.limit.is_none() {
+        options.limit = config.missing_limit.limit();
+    }
+    let page_limit = options.limit;
+    apply_query_settings(config, &mut options, || {
+        query_comment(cursor.as_ref(), page_limit)
+    });
+    if options.no_cursor_timeout.is_none() {
+        options.no_cursor_timeout = config.no_cursor_timeout;
+    }
+    if options.batch_size.is_none() && config.batch_size == BatchSizePolicy::FitPage {
+        options.batch_size = options
+            .limit
+            .and_then(|limit| u32::try_from(limit.unsigned_abs().saturating_add(1)).ok());
+    }
+    CursorOptions::new_with_tiebreak(options, cursor, !config.unique_sort)
+        .with_count_hint(config.count_hint.clone())
+}
+
+/// Serves the page from the [`PageCache`] or the `Prefetcher` if configured.
+async fn fetch<T: DeserializeOwned>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Document,
+    find_options: FindOptions,
+    options: CursorOptions,
+    cursor: Option<DirectedCursor>,
+    session: Option<&SessionLock<'_>>,
+) -> Result<FindResult<T>, CursorError> {
+    if session.is_some() || (config.cache.is_none() && !prefetches(config)) {
+        return Box::pin(paginate(
+            collection, config, filter, options, cursor, session,
+        ))
+        .await;
+    }
+    let cache_key = page_key(collection, &filter, &options, cursor.as_ref())?;
+    if let Some(page) = config
+        .cache
+        .as_ref()
+        .and_then(|cache| cache.get(&cache_key))
+    {
+        return page.deserialize_items(config.collect_mismatched);
+    }
+    let page = Box::pin(fetch_page(
+        collection,
+        config,
+        filter,
+        find_options,
+        options,
+        cursor,
+    ))
+    .await?;
+    if let Some(cache) = &config.cache {
+        cache.insert(cache_key, page.clone());
+    }
+    page.deserialize_items(config.collect_mismatched)
+}
+
+/// Whether `config` prefetches the next pages.
+#[cfg(feature = "tokio")]
+const fn prefetches(config: &PaginationConfig) -> bool {
+    config.prefetcher.is_some()
+}
+
+#[cfg(not(feature = "tokio"))]
+const fn prefetches(_config: &PaginationConfig) -> bool {
+    false
+}
+
+/// Fetches a page outside of a session, from the [`Prefetcher`] if configured.
+#[cfg(feature = "tokio")]
+async fn fetch_page(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Document,
+    find_options: FindOptions,
+    options: CursorOptions,
+    cursor: Option<DirectedCursor>,
+) -> Result<FindResult<Document>, CursorError> {
+    match &config.prefetcher {
+        Some(prefetcher) => {
+            Box::pin(prefetch(
+                prefetcher,
+                collection,
+                config,
+                filter,
+                find_options,
+                options,
+                cursor,
+            ))
+            .await
+        }
+        None => Box::pin(paginate(collection, config, filter, options, cursor, None)).await,
+    }
+}
 
-mod error;
-mod model;
-mod option;
-pub use model::*;
+/// Fetches a page outside of a session. Only prefetching needs the unprepared `find_options`.
+#[cfg(not(feature = "tokio"))]
+async fn fetch_page(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Document,
+    _find_options: FindOptions,
+    options: CursorOptions,
+    cursor: Option<DirectedCursor>,
+) -> Result<FindResult<Document>, CursorError> {
+    Box::pin(paginate(collection, config, filter, options, cursor, None)).await
+}
 
-use crate::option::CursorOptions;
-use bson::{doc, Bson, Document};
-use error::CursorError;
-use futures_util::stream::StreamExt;
-use futures_util::TryStreamExt;
-use mongodb::options::CountOptions;
-use mongodb::{options::FindOptions, Collection};
-use serde::de::DeserializeOwned;
+/// Serves the page from `prefetcher` if it was prefetched and starts prefetching the next one.
+#[cfg(feature = "tokio")]
+async fn prefetch(
+    prefetcher: &Prefetcher,
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Document,
+    mut find_options: FindOptions,
+    options: CursorOptions,
+    cursor: Option<DirectedCursor>,
+) -> Result<FindResult<Document>, CursorError> {
+    let key = prefetch_key(collection, &filter, &options);
+    let prefetched = match prefetcher.take(&key) {
+        Some(task) => task.await.ok().and_then(Result::ok),
+        None => None,
+    };
+    let page = match prefetched {
+        Some(page) => page,
+        None => {
+            Box::pin(paginate::<Document>(
+                collection,
+                config,
+                filter.clone(),
+                options,
+                cursor,
+                None,
+            ))
+            .await?
+        }
+    };
 
-use async_trait::async_trait;
+    if let Some(next) = page.page_info.next_request() {
+        // The next page continues right after this one, a skip only applies once.
+        find_options.skip = None;
+        let next_options = prepare(config, find_options, Some(next.clone()));
+        let next_key = prefetch_key(collection, &filter, &next_options);
+        let (task_collection, task_config) = (collection.clone(), config.clone());
+        prefetcher.spawn(collection, next_key, async move {
+            Box::pin(paginate(
+                &task_collection,
+                &task_config,
+                filter,
+                next_options,
+                Some(next),
+                None,
+            ))
+            .await
+        });
+    }
+    Ok(page)
+}
 
-#[async_trait]
-/// Used to paginate through a collection.
-pub trait Pagination {
-    /// Finds the items in the collection matching `filter` based on the `cursor`.
-    ///
-    /// # Arguments
-    /// * `filter`: Optional filter to restrict the result set of the query.
-    /// * `options`: Optional find options that you would like to perform any searches with
-    /// * `cursor`: An optional existing cursor in base64. This would have come from a previous `FindResult<T>`
-    async fn find_paginated<T>(
-        &self,
-        filter: Option<Document>,
-        options: Option<FindOptions>,
-        cursor: Option<DirectedCursor>,
-    ) -> Result<FindResult<T>, CursorError>
-    where
-        T: DeserializeOwned + Send;
+/// Identifies a cached page by its collection, query, cursor and limit.
+fn page_key(
+    collection: &Collection<Document>,
+    filter: &Document,
+    options: &CursorOptions,
+    cursor: Option<&DirectedCursor>,
+) -> Result<PageKey, CursorError> {
+    Ok(PageKey {
+        namespace: collection.namespace().to_string(),
+        filter_hash: query_hash(filter, &FindOptions::from(options.clone()))?,
+        cursor: cursor.map(|cursor| match cursor {
+            DirectedCursor::Forward(edge) => format!("next:{edge}"),
+            DirectedCursor::Backwards(edge) => format!("previous:{edge}"),
+        }),
+        limit: options.limit,
+    })
 }
 
-#[async_trait]
-impl<I: Send + Sync> Pagination for Collection<I> {
-    async fn find_paginated<T>(
-        &self,
-        filter: Option<Document>,
-        options: Option<FindOptions>,
-        cursor: Option<DirectedCursor>,
-    ) -> Result<FindResult<T>, CursorError>
-    where
-        T: DeserializeOwned + Send,
-    {
-        let options = CursorOptions::new(options.unwrap_or_default(), cursor.clone());
+/// Hashes the canonical BSON of `filter` and `find_options` with SHA-256, stable across
+/// processes, so a cache shared between them finds the pages of the same query only.
+fn query_hash(filter: &Document, find_options: &FindOptions) -> Result<String, CursorError> {
+    let mut hasher = Sha256::new();
+    // Every BSON document starts with its length, so the filter can't run into the options.
+    hasher.update(bson::to_vec(filter)?);
+    hasher.update(bson::to_vec(find_options)?);
+    // The options the driver doesn't serialize, but which change the page.
+    hasher.update(
+        format!(
+            "{:?} {:?} {:?} {:?} {:?}",
+            find_options.comment,
+            find_options.cursor_type,
+            find_options.max_await_time,
+            find_options.read_concern,
+            find_options.selection_criteria,
+        )
+        .as_bytes(),
+    );
+    Ok(URL_SAFE_NO_PAD.encode(hasher.finalize()))
+}
+
+/// Identifies a page by everything that influences its content.
+#[cfg(feature = "tokio")]
+fn prefetch_key(
+    collection: &Collection<Document>,
+    filter: &Document,
+    options: &CursorOptions,
+) -> String {
+    format!("{} {filter} {options:?}", collection.namespace())
+}
+
+async fn paginate<T: DeserializeOwned>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Document,
+    options: CursorOptions,
+    cursor: Option<DirectedCursor>,
+    session: Option<&SessionLock<'_>>,
+) -> Result<FindResult<T>, CursorError> {
+    let sort = options.sort.clone().unwrap_or_default();
+    report_cursor_missing_sort_keys(collection, config, &sort, cursor.as_ref());
 
-        let filter = filter.unwrap_or_default();
+    let query = get_query(
+        filter.clone(),
+        &options,
+        cursor.as_ref(),
+        config.unique_sort,
+    )?;
 
-        let query = get_query(filter.clone(), &options, cursor.as_ref())?;
+    let find_start = Instant::now();
+    let overfetch = config.has_page == HasPageStrategy::OverFetch;
+    let mut raw_documents = find_documents(
+        collection, config, &filter, &query, &options, overfetch, session,
+    )
+    .await?;
+    let more = truncate_page(
+        &mut raw_documents,
+        options.limit,
+        overfetch,
+        config.max_page_bytes,
+    );
+    let effective_options = EffectiveOptions::new(query, &options, overfetch);
+    let find_duration = find_start.elapsed();
+    let bytes_received = raw_documents
+        .iter()
+        .map(|doc| doc.as_bytes().len())
+        .sum::<usize>();
+    let mut documents = raw_documents
+        .iter()
+        .map(RawDocumentBuf::to_document)
+        .collect::<Result<Vec<Document>, _>>()?;
 
-        let mut documents = self
-            .clone_with_type::<Document>()
-            .find(query.clone(), Some(options.clone().into()))
-            .await?
-            .try_collect::<Vec<Document>>()
-            .await?;
+    if matches!(cursor, Some(DirectedCursor::Backwards(_))) {
+        documents.reverse();
+    }
 
-        if matches!(cursor, Some(DirectedCursor::Backwards(_))) {
-            documents.reverse();
+    report_items_missing_sort_keys(collection, config, &sort, &documents);
+    audit_sort(collection, config, &options, &documents)?;
+
+    let edges = edges(config, &options, cursor.as_ref(), &documents);
+    let end_cursor = edges.last().cloned().map(DirectedCursor::Forward);
+    let start_cursor = edges.first().cloned().map(DirectedCursor::Backwards);
+    let mut stripped = documents.clone();
+    for document in &mut stripped {
+        options.strip(document);
+    }
+    let (mut items, mut edges, mismatched) =
+        deserialize_page::<T>(stripped, edges, config.collect_mismatched)?;
+
+    let (has_previous_page, has_next_page) = match config.has_page {
+        HasPageStrategy::ExtraQuery => {
+            let bounds = (start_cursor.as_ref(), end_cursor.as_ref());
+            peeked_flags(collection, config, &filter, &options, bounds, session).await?
         }
+        HasPageStrategy::OverFetch => overfetched_flags(cursor.as_ref(), more),
+        HasPageStrategy::Skip => (None, None),
+    };
+
+    let mut page_info = PageInfo {
+        has_previous_page,
+        has_next_page,
+        start_cursor_forward: start_cursor.clone().map(DirectedCursor::reverse),
+        end_cursor_backward: end_cursor.clone().map(DirectedCursor::reverse),
+        start_cursor,
+        end_cursor,
+        ..PageInfo::default()
+    };
+
+    let count_start = Instant::now();
+    let total_count = total_count(collection, config, &filter, &options, session).await?;
+    let count_duration = count_start.elapsed();
+    if config.page_numbers {
+        set_page_numbers(&mut page_info, options.limit, total_count);
+    }
+
+    let stats = config.collect_stats.then(|| PageStats {
+        find_duration,
+        count_duration,
+        batches: estimate_batches(&raw_documents, options.batch_size),
+        bytes_received: u64::try_from(bytes_received).unwrap_or(u64::MAX),
+    });
+
+    if config.reverse_results {
+        items.reverse();
+        edges.reverse();
+    }
+
+    let backwards = matches!(cursor, Some(DirectedCursor::Backwards(_)));
+    Ok(FindResult {
+        etag: config
+            .etags
+            .then(|| page_etag(&raw_documents, backwards, &page_info, total_count)),
+        page_info,
+        edges,
+        total_count,
+        items,
+        stats,
+        mismatched,
+        effective_options,
+        drift_detected: false,
+    })
+}
+
+/// Hashes the fetched documents of a page in page order together with its flags and count into
+/// a strong `ETag`.
+fn page_etag(
+    raw_documents: &[RawDocumentBuf],
+    backwards: bool,
+    page_info: &PageInfo,
+    total_count: Count,
+) -> String {
+    let mut hasher = Sha256::new();
+    let mut hash = |document: &RawDocumentBuf| hasher.update(document.as_bytes());
+    if backwards {
+        raw_documents.iter().rev().for_each(&mut hash);
+    } else {
+        raw_documents.iter().for_each(&mut hash);
+    }
+    let flag = |flag: Option<bool>| flag.map_or(2_u8, u8::from);
+    hasher.update([
+        flag(page_info.has_previous_page),
+        flag(page_info.has_next_page),
+    ]);
+    hasher.update(format!("{:?}", total_count.value()));
+    let digest = hasher.finalize();
+    format!(
+        "\"{}\"",
+        URL_SAFE_NO_PAD.encode(digest.get(..16).unwrap_or_default())
+    )
+}
+
+/// Peeks past the `start` and `end` cursor of a page for the flags of
+/// [`HasPageStrategy::ExtraQuery`].
+async fn peeked_flags(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: &Document,
+    options: &CursorOptions,
+    (start, end): (Option<&DirectedCursor>, Option<&DirectedCursor>),
+    session: Option<&SessionLock<'_>>,
+) -> Result<(Option<bool>, Option<bool>), CursorError> {
+    let has_previous_page = has_page(
+        collection,
+        config,
+        filter.clone(),
+        options.clone(),
+        start,
+        session,
+    )
+    .await?;
+    let has_next_page = has_page(
+        collection,
+        config,
+        filter.clone(),
+        options.clone(),
+        end,
+        session,
+    )
+    .await?;
+    Ok((Some(has_previous_page), Some(has_next_page)))
+}
+
+/// Emits an event for every sort key the `cursor` of a page doesn't have.
+fn report_cursor_missing_sort_keys(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    sort: &Document,
+    cursor: Option<&DirectedCursor>,
+) {
+    let Some(cursor) = cursor.filter(|cursor| !cursor.inner().is_empty()) else {
+        return;
+    };
+    let keys = sort.keys().map(|key| cursor_key(key));
+    for key in keys.filter(|key| !cursor.inner().contains_key(key)) {
+        config.emit(&PaginationEvent::CursorMissingSortKey {
+            collection: collection.name().to_owned(),
+            key: key.to_owned(),
+        });
+    }
+}
 
+/// Emits an event for every sort key some of the `documents` of a page don't have.
+fn report_items_missing_sort_keys(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    sort: &Document,
+    documents: &[Document],
+) {
+    for key in sort.keys().map(|key| cursor_key(key)) {
         let items = documents
-            .clone()
-            .into_iter()
-            .map(|doc| bson::from_bson(Bson::Document(doc)))
-            .collect::<Result<Vec<T>, _>>()?;
+            .iter()
+            .filter(|doc| sort_value(doc, key).is_none())
+            .count();
+        if items > 0 {
+            config.emit(&PaginationEvent::ItemsMissingSortKey {
+                collection: collection.name().to_owned(),
+                key: key.to_owned(),
+                items,
+            });
+        }
+    }
+}
+
+/// Looks for items of a page sharing all their sort keys according to the sort audit of `config`.
+fn audit_sort(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    options: &CursorOptions,
+    documents: &[Document],
+) -> Result<(), CursorError> {
+    if config.sort_audit == SortAudit::Off
+        || options
+            .sort
+            .as_ref()
+            .is_some_and(|sort| sort.contains_key("_id"))
+    {
+        return Ok(());
+    }
+    // Equal sort keys are adjacent in a sorted page.
+    let edges: Vec<Edge> = documents
+        .iter()
+        .map(|document| Edge::new(document, options))
+        .collect();
+    let Some(shared) = edges.windows(2).find_map(|pair| match pair {
+        [first, second] if first == second => Some(first),
+        _ => None,
+    }) else {
+        return Ok(());
+    };
+    let items = edges.iter().filter(|edge| *edge == shared).count();
+    let keys = Document::clone(shared).to_string();
+    match config.sort_audit {
+        SortAudit::Deny => Err(CursorError::UnstableSort(keys)),
+        SortAudit::Warn | SortAudit::Off => {
+            config.emit(&PaginationEvent::UnstableSort {
+                collection: collection.name().to_owned(),
+                keys,
+                items,
+            });
+            Ok(())
+        }
+    }
+}
 
-        let edges = documents
-            .clone()
-            .into_iter()
-            .map(|doc| Edge::new(&doc, &options))
-            .collect::<Vec<Edge>>();
+/// Creates the edges of a page, annotated with their offsets and high-water mark if enabled in
+/// `config`.
+fn edges(
+    config: &PaginationConfig,
+    options: &CursorOptions,
+    cursor: Option<&DirectedCursor>,
+    documents: &[Document],
+) -> Vec<Edge> {
+    let first_offset = if config.offsets || config.page_numbers || config.align_pages {
+        first_offset(options, cursor, documents.len())
+    } else {
+        None
+    };
+    let high_water = config
+        .change_key
+        .as_deref()
+        .and_then(|key| changes::high_water_mark(key, cursor, documents));
+    documents
+        .iter()
+        .zip(0_u64..)
+        .map(|(doc, index)| {
+            Edge::new(doc, options)
+                .with_offset(first_offset.map(|offset| offset.saturating_add(index)))
+                .with_high_water(high_water.clone())
+        })
+        .collect()
+}
 
-        let end_cursor = edges.last().cloned().map(DirectedCursor::Forward);
-        let start_cursor = edges.first().cloned().map(DirectedCursor::Backwards);
+/// Derives the page number from the offset of the start cursor and the number of pages from
+/// `total_count`, both only for a positive limit.
+fn set_page_numbers(page_info: &mut PageInfo, limit: Option<i64>, total_count: Count) {
+    let Some(limit) = limit
+        .and_then(|limit| u64::try_from(limit).ok())
+        .filter(|limit| *limit > 0)
+    else {
+        return;
+    };
+    page_info.approx_page_number = page_info
+        .start_cursor
+        .as_ref()
+        .and_then(|cursor| cursor.inner().offset())
+        .and_then(|offset| offset.checked_div(limit))
+        .map(|page| page.saturating_add(1));
+    page_info.approx_total_pages = total_count.value().map(|count| count.div_ceil(limit));
+}
 
-        let has_next_page = has_page(
-            &self.clone_with_type(),
-            filter.clone(),
-            options.clone(),
-            end_cursor.as_ref(),
-        )
-        .await?;
+/// Returns the offset of the first item of a page with `items` items, derived from the offset of
+/// the cursor and the skip.
+fn first_offset(
+    options: &CursorOptions,
+    cursor: Option<&DirectedCursor>,
+    items: usize,
+) -> Option<u64> {
+    let skip = options.skip.unwrap_or_default();
+    match cursor {
+        None => Some(skip),
+        Some(DirectedCursor::Forward(edge)) => {
+            Some(edge.offset()?.saturating_add(1).saturating_add(skip))
+        }
+        Some(DirectedCursor::Backwards(edge)) => edge
+            .offset()?
+            .checked_sub(u64::try_from(items).ok()?)?
+            .checked_sub(skip),
+    }
+}
 
-        let has_previous_page = has_page(
-            &self.clone_with_type(),
-            filter.clone(),
-            options.clone(),
-            start_cursor.as_ref(),
-        )
-        .await?;
+/// Fetches the documents of a page in the order of the directed sort, one more than the limit if
+/// `overfetch` is set. With [`PaginationConfig::chunk_size`] they are fetched in chunks, which
+/// continue from the last document of the previous chunk with the keyset filter on `filter`.
+async fn find_documents(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: &Document,
+    query: &Document,
+    options: &CursorOptions,
+    overfetch: bool,
+    session: Option<&SessionLock<'_>>,
+) -> Result<Vec<RawDocumentBuf>, CursorError> {
+    let find_options = page_find_options(options, overfetch);
+    // A tailable cursor waits for new documents, it is never split.
+    let documents = match config
+        .chunk_size
+        .filter(|_| find_options.cursor_type.is_none())
+    {
+        Some(chunk_size) => {
+            let page = (filter, query, options);
+            find_chunked(collection, config, page, find_options, chunk_size, session).await?
+        }
+        None => find_chunk(collection, config, query, find_options, session).await?,
+    };
+    if let Some(adaptive) = &config.adaptive_limit {
+        adaptive.record(&documents);
+    }
+    Ok(documents)
+}
+
+/// Fetches the documents of a single find query, failing once it needs more than
+/// [`PaginationConfig::max_batches`] batches.
+async fn find_chunk(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    query: &Document,
+    find_options: FindOptions,
+    session: Option<&SessionLock<'_>>,
+) -> Result<Vec<RawDocumentBuf>, CursorError> {
+    config
+        .run(collection.name(), || async {
+            let raw_collection = collection.clone_with_type::<RawDocumentBuf>();
+            let mut find_options = find_options.clone();
+            let cap = config.max_batches.map(|max_batches| {
+                // Later batches of the server are only bounded by size, so every batch gets the
+                // size of the first one to be counted.
+                let batch_size = *find_options.batch_size.get_or_insert(FIRST_BATCH_SIZE);
+                batch_cap(max_batches, batch_size)
+            });
+            let documents = if let Some(lock) = session {
+                let mut guard = lock.lock().await;
+                let mut cursor = raw_collection
+                    .find_with_session(query.clone(), find_options, &mut guard)
+                    .await?;
+                collect_capped(cursor.stream(&mut guard), cap).await?
+            } else {
+                let cursor = raw_collection.find(query.clone(), find_options).await?;
+                collect_capped(cursor, cap).await?
+            };
+            if let (Some(max_batches), Some(cap)) = (config.max_batches, cap) {
+                if documents.len() > cap {
+                    return Err(CursorError::TooManyBatches(max_batches));
+                }
+            }
+            Ok(documents)
+        })
+        .await
+}
 
-        let page_info = PageInfo {
-            has_previous_page,
-            has_next_page,
-            start_cursor,
-            end_cursor,
+/// Fetches the documents of a page with one find query per `chunk_size` documents. Every chunk
+/// after the first continues right after the last document of the previous one, without the
+/// skip. The chunks stop early once the page is complete or exceeds
+/// [`PaginationConfig::max_page_bytes`]. The documents of all chunks are kept until the page is
+/// deserialized, so this bounds the size of each query, not the memory of the page.
+async fn find_chunked(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    (filter, query, options): (&Document, &Document, &CursorOptions),
+    mut find_options: FindOptions,
+    chunk_size: u32,
+    session: Option<&SessionLock<'_>>,
+) -> Result<Vec<RawDocumentBuf>, CursorError> {
+    let limit = find_options.limit;
+    let mut query = query.clone();
+    let mut documents: Vec<RawDocumentBuf> = Vec::new();
+    let mut bytes: usize = 0;
+    loop {
+        let wanted = chunk_limit(limit, documents.len(), chunk_size);
+        if wanted <= 0 {
+            return Ok(documents);
+        }
+        find_options.limit = Some(wanted);
+        let chunk = find_chunk(collection, config, &query, find_options.clone(), session).await?;
+        let received = chunk.len();
+        bytes = chunk.iter().fold(bytes, |bytes, document| {
+            bytes.saturating_add(document.as_bytes().len())
+        });
+        documents.extend(chunk);
+        let last = is_last_chunk(config.max_page_bytes, wanted, received, bytes);
+        let Some(last_document) = documents.last().filter(|_| !last) else {
+            return Ok(documents);
         };
+        find_options.skip = None;
+        // The keyset filter follows the directed sort, whatever the direction of the cursor.
+        let after = DirectedCursor::Forward(Edge::new(&last_document.to_document()?, options));
+        query = get_query(filter.clone(), options, Some(&after), config.unique_sort)?;
+    }
+}
+
+/// Returns the limit of the next chunk of a page after `fetched` documents, at most `chunk_size`
+/// and 0 once the `limit` of the page is reached.
+fn chunk_limit(limit: Option<i64>, fetched: usize, chunk_size: u32) -> i64 {
+    let chunk_size = i64::from(chunk_size.max(1));
+    let fetched = i64::try_from(fetched).unwrap_or(i64::MAX);
+    limit.map_or(chunk_size, |limit| {
+        limit.saturating_sub(fetched).min(chunk_size)
+    })
+}
+
+/// Whether a chunk of `received` out of `wanted` documents ends the page: a short chunk has no
+/// documents left after it, and the page stops once its `bytes` exceed `max_page_bytes`.
+fn is_last_chunk(
+    max_page_bytes: Option<usize>,
+    wanted: i64,
+    received: usize,
+    bytes: usize,
+) -> bool {
+    i64::try_from(received).unwrap_or(i64::MAX) < wanted
+        || max_page_bytes.is_some_and(|max_bytes| bytes > max_bytes)
+}
+
+/// Returns the options of the find query of a page, with one item more if `overfetch` is set.
+fn page_find_options(options: &CursorOptions, overfetch: bool) -> FindOptions {
+    let mut find_options = FindOptions::from(options.clone());
+    if overfetch {
+        find_options.limit = find_options.limit.map(|limit| limit.saturating_add(1));
+    }
+    find_options
+}
+
+/// Drops the item fetched past the `limit` of an over-fetched page and the items past the byte
+/// budget `max_bytes`, returning whether there are more items in the direction of the page.
+fn truncate_page(
+    documents: &mut Vec<RawDocumentBuf>,
+    limit: Option<i64>,
+    overfetch: bool,
+    max_bytes: Option<usize>,
+) -> bool {
+    let overfetched = overfetch && truncate_overfetched(documents, limit);
+    let over_budget = max_bytes.is_some_and(|max_bytes| truncate_to_budget(documents, max_bytes));
+    overfetched || over_budget
+}
+
+/// Keeps the leading items whose sizes add up to at most `max_bytes`, but at least one so the
+/// pages make progress. Returns whether any item was dropped.
+fn truncate_to_budget(documents: &mut Vec<RawDocumentBuf>, max_bytes: usize) -> bool {
+    let mut bytes: usize = 0;
+    let fitting = documents
+        .iter()
+        .take_while(|document| {
+            bytes = bytes.saturating_add(document.as_bytes().len());
+            bytes <= max_bytes
+        })
+        .count()
+        .max(1);
+    let dropped = documents.len() > fitting;
+    documents.truncate(fitting);
+    dropped
+}
+
+/// Drops the item fetched past the `limit` of the page, returning whether there was one.
+fn truncate_overfetched(documents: &mut Vec<RawDocumentBuf>, limit: Option<i64>) -> bool {
+    let Some(limit) = limit.and_then(|limit| usize::try_from(limit).ok()) else {
+        return false;
+    };
+    let more = documents.len() > limit;
+    documents.truncate(limit);
+    more
+}
+
+/// Returns `has_previous_page` and `has_next_page` of an over-fetched page. `more` tells if
+/// there is a page in the direction of the `cursor`, behind a non-empty cursor it is unknown.
+fn overfetched_flags(cursor: Option<&DirectedCursor>, more: bool) -> (Option<bool>, Option<bool>) {
+    let behind = |edge: &Edge| (edge.is_empty()).then_some(false);
+    match cursor {
+        None => (Some(false), Some(more)),
+        Some(DirectedCursor::Forward(edge)) => (behind(edge), Some(more)),
+        Some(DirectedCursor::Backwards(edge)) => (Some(more), behind(edge)),
+    }
+}
+
+/// Number of documents the server returns in the first batch of a find without a batch size.
+const FIRST_BATCH_SIZE: u32 = 101;
+
+/// Largest batch the server returns without a batch size after the first one.
+const MAX_BATCH_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Returns how many documents arrive in `max_batches` batches of `batch_size`.
+fn batch_cap(max_batches: u32, batch_size: u32) -> usize {
+    let documents = u64::from(batch_size.max(1)).saturating_mul(u64::from(max_batches));
+    usize::try_from(documents).unwrap_or(usize::MAX)
+}
+
+/// Collects the documents of a find, but at most one more than `cap` so a query past the cap
+/// stops after a single further batch.
+async fn collect_capped<S>(
+    documents: S,
+    cap: Option<usize>,
+) -> Result<Vec<RawDocumentBuf>, MongoError>
+where
+    S: TryStream<Ok = RawDocumentBuf, Error = MongoError>,
+{
+    match cap {
+        Some(cap) => {
+            documents
+                .into_stream()
+                .take(cap.saturating_add(1))
+                .try_collect()
+                .await
+        }
+        None => documents.try_collect().await,
+    }
+}
+
+/// The driver does not expose batch boundaries, so they are derived from the batch size.
+/// Without a batch size the server returns 101 documents first and the rest in batches of up
+/// to 16 MiB, which are derived from the size of the documents.
+fn estimate_batches(documents: &[RawDocumentBuf], batch_size: Option<u32>) -> u64 {
+    let count = u64::try_from(documents.len()).unwrap_or(u64::MAX);
+    if let Some(size) = batch_size.filter(|size| *size > 0) {
+        return count.div_ceil(u64::from(size)).max(1);
+    }
+    let first = usize::try_from(FIRST_BATCH_SIZE).unwrap_or(usize::MAX);
+    let rest_bytes = documents
+        .get(first..)
+        .unwrap_or_default()
+        .iter()
+        .map(|document| u64::try_from(document.as_bytes().len()).unwrap_or(u64::MAX))
+        .fold(0_u64, u64::saturating_add);
+    rest_bytes.div_ceil(MAX_BATCH_BYTES).saturating_add(1)
+}
+
+/// Tags the queries of a page so they can be told apart in the profiler and `currentOp`.
+fn query_comment(cursor: Option<&DirectedCursor>, limit: Option<i64>) -> String {
+    let page = match cursor {
+        None => "first",
+        Some(DirectedCursor::Forward(_)) => "next",
+        Some(DirectedCursor::Backwards(_)) => "previous",
+    };
+    match limit {
+        Some(limit) => format!("mongodb-cursor-pagination page={page} limit={limit}"),
+        None => format!("mongodb-cursor-pagination page={page}"),
+    }
+}
+
+/// Rejects option and cursor combinations that can't be paginated before running any query.
+fn validate(
+    options: &CursorOptions,
+    cursor: Option<&DirectedCursor>,
+    config: &PaginationConfig,
+) -> Result<(), CursorError> {
+    if options.limit == Some(0) {
+        return Err(CursorError::EmptyLimit);
+    }
+    if options.limit.is_none() && config.missing_limit == MissingLimit::Reject {
+        return Err(CursorError::MissingLimit);
+    }
+    if let Some(limit) = options.limit.filter(|limit| *limit < 0) {
+        return Err(CursorError::NegativeLimit(limit));
+    }
+    if let Some(key) = options.unprojected_sort_key() {
+        return Err(CursorError::ProjectionExcludesSortKey(key.to_owned()));
+    }
+    if let (Some(skip), Some(_), SkipPolicy::Reject) =
+        (options.skip.filter(|skip| *skip > 0), cursor, config.skip)
+    {
+        return Err(CursorError::SkipWithCursor(skip));
+    }
+    // Aligned pages, refilled first pages and the peeked page flags don't know of the items a
+    // skip jumps over before a backwards cursor, so its pages would disagree with each other.
+    if options.skip.is_some_and(|skip| skip > 0)
+        && matches!(cursor, Some(DirectedCursor::Backwards(_)))
+    {
+        return Err(CursorError::InvalidCombination(
+            "`skip` only applies to forward pages, page backwards without it",
+        ));
+    }
+    if options.max_await_time.is_some()
+        && !matches!(options.cursor_type, Some(CursorType::TailableAwait))
+    {
+        return Err(CursorError::MaxAwaitTimeWithoutAwait);
+    }
+    let Some(cursor) = cursor else {
+        return Ok(());
+    };
+    config.codec.check_verified(cursor.inner())?;
+    if cursor.inner().collation() != options.collation_document().as_ref() {
+        return Err(CursorError::CollationMismatch);
+    }
+    cursor
+        .inner()
+        .iter()
+        .try_for_each(|(key, value)| model::check_cursor_value(key, value))
+}
+
+/// Returns the direction of `key` in `sort`, `1` for ascending and `-1` for descending.
+fn sort_direction(sort: &Document, key: &str) -> Result<i64, CursorError> {
+    match sort.get(key) {
+        Some(Bson::Int32(direction @ (1_i32 | -1_i32))) => Ok(i64::from(*direction)),
+        Some(Bson::Int64(direction @ (1_i64 | -1_i64))) => Ok(*direction),
+        value => Err(CursorError::InvalidSortDirection {
+            field: key.to_owned(),
+            value: value.map_or_else(|| "nothing".to_owned(), ToString::to_string),
+        }),
+    }
+}
+
+/// Counts the items matching `filter` according to the [`CountMode`] of `config`.
+async fn total_count(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: &Document,
+    options: &CursorOptions,
+    session: Option<&SessionLock<'_>>,
+) -> Result<Count, CursorError> {
+    Ok(match config.count_mode {
+        CountMode::Exact => Count::Exact(
+            count_documents(
+                options.clone().try_into()?,
+                collection,
+                config,
+                Some(filter),
+                session,
+            )
+            .await?,
+        ),
+        CountMode::Estimate => Count::Estimate(
+            estimated_count(collection, config, Some(options.clone().into())).await?,
+        ),
+        CountMode::UpTo(bound) => {
+            count_up_to(
+                collection,
+                config,
+                Some(filter.clone()),
+                bound,
+                Some(options.clone().into()),
+                session,
+            )
+            .await?
+        }
+        CountMode::Skip => Count::Unknown,
+    })
+}
+
+async fn estimated_count<T: Sync>(
+    collection: &Collection<T>,
+    config: &PaginationConfig,
+    options: Option<FindOptions>,
+) -> Result<u64, CursorError> {
+    let mut options = options.unwrap_or_default();
+    apply_query_settings(config, &mut options, || {
+        "mongodb-cursor-pagination count=estimated".to_owned()
+    });
+    let options = EstimatedDocumentCountOptions::from(CursorOptions::new(options, None));
+    instrumented!(
+        tracing::info_span!(
+            "estimated_count",
+            collection = collection.name(),
+            elapsed = trace::Empty,
+        ),
+        config.run(collection.name(), || async {
+            Ok(collection
+                .estimated_document_count(Some(options.clone()))
+                .await?)
+        })
+    )
+}
+
+async fn count_up_to<T: Sync>(
+    collection: &Collection<T>,
+    config: &PaginationConfig,
+    filter: Option<Document>,
+    bound: u64,
+    options: Option<FindOptions>,
+    session: Option<&SessionLock<'_>>,
+) -> Result<Count, CursorError> {
+    let mut options = options.unwrap_or_default();
+    apply_query_settings(config, &mut options, || {
+        format!("mongodb-cursor-pagination count<={bound}")
+    });
+    let options = CursorOptions::new(options, None).with_count_hint(config.count_hint.clone());
+    let filter = config.scope(filter).unwrap_or_default();
+    let pipeline = [
+        doc! { "$match": filter.clone() },
+        doc! { "$limit": i64::try_from(bound.saturating_add(1)).unwrap_or(i64::MAX) },
+        doc! { "$count": "count" },
+    ];
+    let aggregate_options = options.count_aggregate_options();
 
-        Ok(FindResult {
-            page_info,
-            edges,
-            total_count: count_documents(options.clone().into(), self, Some(&filter)).await?,
-            items,
+    let start = Instant::now();
+    let count = instrumented!(
+        tracing::info_span!(
+            "count_up_to",
+            collection = collection.name(),
+            bound,
+            filter_shape = trace::filter_shape(&filter),
+            elapsed = trace::Empty,
+        ),
+        config.run(collection.name(), || async {
+            let result = match session {
+                Some(lock) => {
+                    let mut guard = lock.lock().await;
+                    collection
+                        .aggregate_with_session(
+                            pipeline.clone(),
+                            aggregate_options.clone(),
+                            &mut guard,
+                        )
+                        .await?
+                        .next(&mut guard)
+                        .await
+                        .transpose()?
+                }
+                None => {
+                    collection
+                        .aggregate(pipeline.clone(), aggregate_options.clone())
+                        .await?
+                        .try_next()
+                        .await?
+                }
+            };
+            let count = match result {
+                Some(result) => match result.get("count") {
+                    Some(Bson::Int32(count)) => u64::try_from(*count).unwrap_or(0),
+                    Some(Bson::Int64(count)) => u64::try_from(*count).unwrap_or(0),
+                    _ => 0,
+                },
+                None => 0,
+            };
+            Ok(count)
         })
+    )?;
+    if let Some(metrics) = &config.metrics {
+        metrics.count_duration(collection.name(), start.elapsed());
     }
+    Ok(if count > bound {
+        Count::AtLeast(bound)
+    } else {
+        Count::Exact(count)
+    })
 }
 
 async fn count_documents<T: Sync>(
     mut options: CountOptions,
     collection: &Collection<T>,
+    config: &PaginationConfig,
     filter: Option<&Document>,
+    session: Option<&SessionLock<'_>>,
 ) -> Result<u64, CursorError> {
     options.limit = None;
     options.skip = None;
     let count_query = filter.map_or_else(Document::new, Clone::clone);
-    Ok(collection
-        .count_documents(count_query, Some(options))
-        .await?)
+    let start = Instant::now();
+    let count = instrumented!(
+        tracing::info_span!(
+            "count_documents",
+            collection = collection.name(),
+            filter_shape = trace::filter_shape(&count_query),
+            elapsed = trace::Empty,
+        ),
+        config.run(collection.name(), || async {
+            Ok(match session {
+                Some(lock) => {
+                    let mut guard = lock.lock().await;
+                    collection
+                        .count_documents_with_session(
+                            count_query.clone(),
+                            options.clone(),
+                            &mut guard,
+                        )
+                        .await?
+                }
+                None => {
+                    collection
+                        .count_documents(count_query.clone(), options.clone())
+                        .await?
+                }
+            })
+        })
+    )?;
+    if let Some(metrics) = &config.metrics {
+        metrics.count_duration(collection.name(), start.elapsed());
+    }
+    Ok(count)
 }
 
 /*
@@ -259,12 +2335,17 @@ $or: [{
 _id: { $lt: nextId }
 }]
 */
+/// Builds the keyset filter continuing after `cursor`. With `bounded` set, the sort keys are known
+/// to be unique together, and the first one is additionally bounded outside of the `$or`, so the
+/// planner can use a single tight index range instead of one scan per `$or` branch.
 fn get_query(
-    mut filter: Document,
+    filter: Document,
     options: &CursorOptions,
     cursor: Option<&DirectedCursor>,
+    bounded: bool,
 ) -> Result<Document, CursorError> {
-    let Some(cursor) = cursor else {
+    // An edge without keys, see `DirectedCursor::from_end`, has nothing to continue from.
+    let Some(cursor) = cursor.filter(|cursor| !cursor.inner().is_empty()) else {
         return Ok(filter);
     };
 
@@ -272,71 +2353,308 @@ fn get_query(
         return Ok(filter);
     };
 
-    // this is the simplest form, it's just a sort by _id
+    // this is the simplest form, it's just a sort by _id, the natural order or a single unique key
     if sort.len() <= 1 {
-        let object_id = cursor
-            .inner()
-            .get("_id")
-            .ok_or(CursorError::InvalidCursor)?
-            .clone();
-        let direction = if sort.get_i32("_id")? >= 0_i32 {
+        let sort_key = sort.keys().next().map_or("_id", String::as_str);
+        let key = cursor_key(sort_key);
+        // The edge may have been modified through `DerefMut` since it was normalized. A missing
+        // key resumes at `null` like below, see `PaginationEvent::CursorMissingSortKey`.
+        let value = model::canonical(
+            sort_value(cursor.inner(), key)
+                .cloned()
+                .unwrap_or(Bson::Null),
+        );
+        let direction = if sort_direction(&sort, sort_key)? > 0 {
             "$gt"
         } else {
             "$lt"
         };
-        filter.insert("_id", doc! { direction: object_id });
-        return Ok(filter);
+        return Ok(keyset_branch(&filter, doc! { key: { direction: value } }));
     }
 
     let mut queries: Vec<Document> = Vec::new();
     let mut previous_conditions: Vec<(String, Bson)> = Vec::new();
+    let mut bound: Option<Document> = None;
 
     // Add each sort condition with it's direction and all previous condition with fixed values
-    for key in sort.keys() {
-        let mut query = filter.clone();
-        query.extend(previous_conditions.clone().into_iter()); // Add previous conditions
+    for sort_key in sort.keys() {
+        let key = cursor_key(sort_key);
+        let mut query = Document::new();
+        query.extend(previous_conditions.clone()); // Add previous conditions
 
-        let value = cursor.inner().get(key).unwrap_or(&Bson::Null);
+        let value = model::canonical(
+            sort_value(cursor.inner(), key)
+                .cloned()
+                .unwrap_or(Bson::Null),
+        );
 
-        let direction = if sort.get_i32(key)? >= 0_i32 {
+        let direction = if sort_direction(&sort, sort_key)? > 0 {
             "$gt"
         } else {
             "$lt"
         };
 
         query.insert(key, doc! { direction: value.clone() });
-        previous_conditions.push((key.clone(), value.clone())); // Add self without direction to previous conditions
+        if bound.is_none() {
+            let inclusive = if direction == "$gt" { "$gte" } else { "$lte" };
+            bound = Some(doc! { key: { inclusive: value.clone() } });
+        }
+        previous_conditions.push((key.to_owned(), value)); // Add self without direction to previous conditions
 
-        queries.push(query);
+        queries.push(keyset_branch(&filter, query));
     }
 
-    filter = if queries.len() > 1 {
-        doc! { "$or": queries.iter().as_ref() }
+    Ok(if queries.len() > 1 {
+        let mut query = bound.filter(|_| bounded).unwrap_or_default();
+        query.insert("$or", queries);
+        query
     } else {
         queries.pop().unwrap_or_default()
-    };
-    Ok(filter)
+    })
+}
+
+/// Combines `filter` with a `branch` of the keyset filter. They are kept apart in an `$and`, as the
+/// sort keys of `branch` would overwrite the same keys of `filter`, e.g. of the ambient filter.
+fn keyset_branch(filter: &Document, branch: Document) -> Document {
+    if filter.is_empty() {
+        branch
+    } else {
+        doc! { "$and": [filter.clone(), branch] }
+    }
 }
 
 async fn has_page(
     collection: &Collection<Document>,
+    config: &PaginationConfig,
     filter: Document,
     mut options: CursorOptions,
     cursor: Option<&DirectedCursor>,
+    session: Option<&SessionLock<'_>>,
 ) -> Result<bool, CursorError> {
     let Some(cursor) = cursor else {
         return Ok(false);
     };
 
     options.set_cursor(cursor.clone());
-    options.skip = None;
-    let filter = get_query(filter, &options, Some(cursor))?;
+    options.set_skip(None);
+    let filter = get_query(filter, &options, Some(cursor), config.unique_sort)?;
 
-    Ok(collection
-        .find(Some(filter), Some(options.into()))
-        .await?
-        .next()
-        .await
-        .transpose()?
-        .is_some())
+    instrumented!(
+        tracing::info_span!(
+            "has_page",
+            collection = collection.name(),
+            limit = options.limit,
+            direction = trace::direction(Some(cursor)),
+            filter_shape = trace::filter_shape(&filter),
+            elapsed = trace::Empty,
+        ),
+        config.run(collection.name(), || async {
+            let find_options = FindOptions::from(options.clone());
+            Ok(match session {
+                Some(lock) => {
+                    let mut guard = lock.lock().await;
+                    collection
+                        .find_with_session(filter.clone(), find_options, &mut guard)
+                        .await?
+                        .next(&mut guard)
+                        .await
+                        .transpose()?
+                        .is_some()
+                }
+                None => collection
+                    .find(filter.clone(), find_options)
+                    .await?
+                    .next()
+                    .await
+                    .transpose()?
+                    .is_some(),
+            })
+        })
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::{doc, Document, RawDocumentBuf};
+    use mongodb::options::FindOptions;
+
+    use super::{
+        batch_cap, check_unlimited, chunk_limit, get_query, is_last_chunk, model, prepare,
+        query_hash, truncate_to_budget, validate, CursorError, CursorOptions, DirectedCursor, Edge,
+        FindResult, MissingLimit, PageInfo, PaginationConfig, SkipPolicy,
+    };
+
+    /// Three documents of 12 bytes each.
+    fn documents() -> Vec<RawDocumentBuf> {
+        [1_i32, 2, 3]
+            .iter()
+            .filter_map(|value| RawDocumentBuf::from_document(&doc! { "a": value }).ok())
+            .collect()
+    }
+
+    #[test]
+    fn truncates_to_the_byte_budget() {
+        let mut fitting = documents();
+        assert!(!truncate_to_budget(&mut fitting, 36));
+        assert_eq!(fitting.len(), 3);
+
+        let mut truncated = documents();
+        assert!(truncate_to_budget(&mut truncated, 35));
+        assert_eq!(
+            truncated,
+            documents().into_iter().take(2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn keeps_one_item_over_the_budget() {
+        let mut documents = documents();
+        assert!(truncate_to_budget(&mut documents, 0));
+        assert_eq!(documents.len(), 1);
+
+        let mut empty = Vec::new();
+        assert!(!truncate_to_budget(&mut empty, 0));
+    }
+
+    #[test]
+    fn rejects_a_skip_before_a_backwards_cursor() {
+        let config = PaginationConfig::default().skip_policy(SkipPolicy::AfterCursor);
+        let edge = Edge::from_document(doc! { "_id": 1_i64 });
+        let check = |cursor: DirectedCursor, skip: u64| {
+            let find_options = FindOptions::builder().skip(skip).build();
+            let options = CursorOptions::new(find_options, Some(cursor.clone()));
+            validate(&options, Some(&cursor), &config)
+        };
+        assert_eq!(
+            check(DirectedCursor::Forward(edge.clone()), 2).ok(),
+            Some(())
+        );
+        assert_eq!(
+            check(DirectedCursor::Backwards(edge.clone()), 0).ok(),
+            Some(())
+        );
+        assert!(matches!(
+            check(DirectedCursor::Backwards(edge), 2),
+            Err(CursorError::InvalidCombination(_))
+        ));
+        assert!(matches!(
+            check(DirectedCursor::from_end(), 2),
+            Err(CursorError::InvalidCombination(_))
+        ));
+    }
+
+    #[test]
+    fn applies_the_missing_limit_policy() {
+        let limit = |policy: MissingLimit, limit: Option<i64>| {
+            let config = PaginationConfig::default().missing_limit(policy);
+            let find_options = FindOptions::builder().limit(limit).build();
+            let options = prepare(&config, find_options, None);
+            validate(&options, None, &config).map(|()| options.limit)
+        };
+        assert_eq!(limit(MissingLimit::Unlimited, None).ok(), Some(None));
+        assert_eq!(
+            limit(MissingLimit::DefaultTo(25), None).ok(),
+            Some(Some(25))
+        );
+        assert_eq!(
+            limit(MissingLimit::DefaultTo(25), Some(3)).ok(),
+            Some(Some(3))
+        );
+        assert_eq!(limit(MissingLimit::UpTo(100), None).ok(), Some(Some(101)));
+        assert!(matches!(
+            limit(MissingLimit::Reject, None),
+            Err(CursorError::MissingLimit)
+        ));
+        assert_eq!(limit(MissingLimit::Reject, Some(3)).ok(), Some(Some(3)));
+    }
+
+    #[test]
+    fn fails_unlimited_pages_over_the_bound() {
+        let config = PaginationConfig::default().missing_limit(MissingLimit::UpTo(1));
+        let page = |items: usize| FindResult {
+            items: vec![(); items],
+            ..model::empty_page(PageInfo::default())
+        };
+        assert_eq!(
+            check_unlimited(&config, None, page(1))
+                .ok()
+                .map(|checked| checked.items.len()),
+            Some(1)
+        );
+        assert!(matches!(
+            check_unlimited(&config, None, page(2)),
+            Err(CursorError::TooManyItems(1))
+        ));
+        assert_eq!(
+            check_unlimited(&config, Some(2), page(2))
+                .ok()
+                .map(|checked| checked.items.len()),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn keeps_the_ambient_filter_apart_from_the_keyset() {
+        let config = PaginationConfig::default().ambient_filter(doc! { "tenant": "a" });
+        let cursor = DirectedCursor::Forward(Edge::from_document(
+            doc! { "tenant": "a", "created": 5_i64, "_id": 7_i64 },
+        ));
+        let find_options = FindOptions::builder()
+            .sort(doc! { "tenant": 1_i32, "created": 1_i32 })
+            .build();
+        let options = prepare(&config, find_options, Some(cursor.clone()));
+        let filter = config.scope(None).unwrap_or_default();
+        let scoped = |branch: Document| doc! { "$and": [{ "$and": [{ "tenant": "a" }] }, branch] };
+        assert_eq!(
+            get_query(filter, &options, Some(&cursor), false).ok(),
+            Some(doc! { "$or": [
+                scoped(doc! { "tenant": { "$gt": "a" } }),
+                scoped(doc! { "tenant": "a", "created": { "$gt": 5_i64 } }),
+                scoped(doc! { "tenant": "a", "created": 5_i64, "_id": { "$lt": 7_i64 } }),
+            ] })
+        );
+    }
+
+    #[test]
+    fn limits_chunks_to_the_rest_of_the_page() {
+        assert_eq!(chunk_limit(Some(5), 0, 2), 2);
+        assert_eq!(chunk_limit(Some(5), 4, 2), 1);
+        assert_eq!(chunk_limit(Some(5), 5, 2), 0);
+        assert_eq!(chunk_limit(None, 1_000, 2), 2);
+        assert_eq!(chunk_limit(Some(5), 0, 0), 1);
+    }
+
+    #[test]
+    fn stops_after_a_short_chunk_or_over_the_budget() {
+        assert!(!is_last_chunk(None, 2, 2, 100));
+        assert!(is_last_chunk(None, 2, 1, 100));
+        assert!(is_last_chunk(Some(99), 2, 2, 100));
+        assert!(!is_last_chunk(Some(100), 2, 2, 100));
+    }
+
+    #[test]
+    fn hashes_queries_by_their_bson() {
+        let options = FindOptions::builder().limit(2).build();
+        let hash = |filter: Document| query_hash(&filter, &options).ok();
+        // Both filters display as `{ "a": "x", "b": "y" }`.
+        let nested = hash(doc! { "a": "x\", \"b\": \"y" });
+        assert!(nested.is_some());
+        assert_ne!(nested, hash(doc! { "a": "x", "b": "y" }));
+        assert_eq!(
+            hash(doc! { "a": "x", "b": "y" }),
+            hash(doc! { "a": "x", "b": "y" })
+        );
+        let limited = FindOptions::builder().limit(3).build();
+        assert_ne!(
+            hash(doc! { "a": "x" }),
+            query_hash(&doc! { "a": "x" }, &limited).ok()
+        );
+    }
+
+    #[test]
+    fn caps_batches() {
+        assert_eq!(batch_cap(3, 10), 30);
+        assert_eq!(batch_cap(2, 0), 2);
+        assert_eq!(batch_cap(0, 10), 0);
+    }
 }
@@ -0,0 +1,113 @@
+#[cfg(feature = "http")]
+use http::HeaderValue;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::codec::CursorCodec;
+use crate::error::CursorError;
+#[cfg(feature = "http")]
+use crate::model::FindResult;
+use crate::model::{DirectedCursor, PageInfo};
+
+/// Characters left as they are in a query value, the unreserved characters of RFC 3986.
+const QUERY_VALUE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Query parameter holding the cursor of the next page.
+const AFTER: &str = "after";
+/// Query parameter holding the cursor of the previous page.
+const BEFORE: &str = "before";
+
+/// The URLs of the pages next to a page, e.g. for the `next` and `prev` fields of a REST
+/// response. The cursor is passed in the `after` parameter for the next page and in the
+/// `before` parameter for the previous page, replacing these parameters of the base URL.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PageLinks {
+    /// The URL of the next page, `None` if this is the last page.
+    pub next: Option<String>,
+    /// The URL of the previous page, `None` if this is the first page.
+    pub prev: Option<String>,
+}
+
+impl PageLinks {
+    /// Builds the links of the pages next to the page of `page_info` from `base_url`, usually
+    /// the URL of the current request, with the cursors encoded as plain [`Edge`](crate::Edge)s.
+    pub fn new(base_url: &str, page_info: &PageInfo) -> Result<Self, CursorError> {
+        Self::with_codec(base_url, page_info, &CursorCodec::default())
+    }
+
+    /// Builds the links like [`PageLinks::new`], with the cursors encoded by `codec`.
+    pub fn with_codec(
+        base_url: &str,
+        page_info: &PageInfo,
+        codec: &CursorCodec,
+    ) -> Result<Self, CursorError> {
+        let link = |parameter: &str, cursor: Option<DirectedCursor>| {
+            cursor
+                .map(|cursor| codec.encode(cursor.inner()))
+                .transpose()
+                .map(|cursor| cursor.map(|cursor| with_parameter(base_url, parameter, &cursor)))
+        };
+        Ok(Self {
+            next: link(AFTER, page_info.next_request())?,
+            prev: link(BEFORE, page_info.previous_request())?,
+        })
+    }
+
+    /// Renders the links as the value of an RFC 8288 `Link` header, e.g.
+    /// `<https://example.com/fruits?after=...>; rel="next"`. Returns `None` without links.
+    #[must_use]
+    pub fn link_header(&self) -> Option<String> {
+        let links: Vec<String> = [(&self.next, "next"), (&self.prev, "prev")]
+            .into_iter()
+            .filter_map(|(url, rel)| url.as_ref().map(|url| format!("<{url}>; rel=\"{rel}\"")))
+            .collect();
+        (!links.is_empty()).then(|| links.join(", "))
+    }
+
+    /// Renders the links as a ready to use `Link` header value, see [`PageLinks::link_header`].
+    #[cfg(feature = "http")]
+    pub fn header_value(&self) -> Result<Option<HeaderValue>, CursorError> {
+        Ok(self.link_header().map(HeaderValue::try_from).transpose()?)
+    }
+}
+
+#[cfg(feature = "http")]
+impl<T> FindResult<T> {
+    /// Renders the `Link` header of the pages next to this page from `base_url`, e.g.
+    /// `<https://example.com/fruits?after=...>; rel="next"`. Returns `None` for the only page.
+    /// Use [`Paginator::page_links`](crate::Paginator::page_links) for cursors of a custom
+    /// [`CursorCodec`].
+    pub fn link_header(&self, base_url: &str) -> Result<Option<HeaderValue>, CursorError> {
+        PageLinks::new(base_url, &self.page_info)?.header_value()
+    }
+}
+
+/// Sets the query `parameter` of `url` to `value`, dropping the cursor parameters it has.
+fn with_parameter(url: &str, parameter: &str, value: &str) -> String {
+    let (url, fragment) = url
+        .split_once('#')
+        .map_or((url, None), |(url, fragment)| (url, Some(fragment)));
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let mut pairs: Vec<String> = query
+        .split('&')
+        .filter(|pair| {
+            let name = pair.split_once('=').map_or(*pair, |(name, _value)| name);
+            !pair.is_empty() && name != AFTER && name != BEFORE
+        })
+        .map(ToOwned::to_owned)
+        .collect();
+    pairs.push(format!(
+        "{parameter}={}",
+        utf8_percent_encode(value, QUERY_VALUE)
+    ));
+    let mut link = format!("{path}?{}", pairs.join("&"));
+    if let Some(fragment) = fragment {
+        link.push('#');
+        link.push_str(fragment);
+    }
+    link
+}
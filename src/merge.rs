@@ -0,0 +1,216 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use bson::Document;
+
+use crate::error::CursorError;
+use crate::model::{DirectedCursor, Edge, FindResult};
+
+/// A page merged from the pages of several sources by [`merge_pages`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct MergedPage<T> {
+    /// Items of the page in the order of the sort
+    pub items: Vec<T>,
+    /// Edges of the items, each from the source of its item
+    pub edges: Vec<Edge>,
+    /// Index of the source of every item
+    pub sources: Vec<usize>,
+    /// Cursor to fetch the next page of every source with, the cursor the source was fetched
+    /// with if none of its items made it onto the page
+    pub cursors: Vec<Option<DirectedCursor>>,
+    /// True if any source has items after the page
+    pub has_next_page: bool,
+}
+
+/// Merges the forward pages of several sources holding disjoint items, e.g. mirrored
+/// collections of different clusters queried with the same filter and sort, into one page of
+/// at most `limit` items in the order of `sort`, e.g. the normalized sort of
+/// [`CursorOptions::sort`](crate::CursorOptions::sort).
+///
+/// `cursors` are the cursors the `pages` were fetched with, in the same order, all `None` for
+/// the first page. Fetch the next page of every source with [`MergedPage::cursors`] and the
+/// same limit and merge again, or hand out [`MergedPage::composite_cursor`] as the cursor of
+/// the merged page. Items with equal sort keys are taken from the first source first.
+///
+/// A source whose `has_next_page` is unknown, e.g. with
+/// [`HasPageStrategy::Skip`](crate::HasPageStrategy::Skip), counts as having more items only if
+/// its page holds `limit` items. The items and edges of every page have to be in the order of
+/// `sort`, so reverse pages fetched with
+/// [`PaginationConfig::reverse_results`](crate::PaginationConfig::reverse_results) before
+/// merging them.
+///
+/// # Errors
+///
+/// Returns [`CursorError::InvalidSortDirection`] if a direction of `sort` isn't 1 or -1.
+pub fn merge_pages<T>(
+    pages: Vec<FindResult<T>>,
+    cursors: &[Option<DirectedCursor>],
+    sort: &Document,
+    limit: usize,
+) -> Result<MergedPage<T>, CursorError> {
+    let more: Vec<bool> = pages
+        .iter()
+        .map(|page| {
+            page.page_info
+                .has_next_page
+                .unwrap_or(page.items.len() >= limit)
+        })
+        .collect();
+    let mut queues: Vec<VecDeque<(Edge, T)>> = pages
+        .into_iter()
+        .map(|page| page.edges.into_iter().zip(page.items).collect())
+        .collect();
+    let mut next: Vec<Option<DirectedCursor>> = (0..queues.len())
+        .map(|source| cursors.get(source).cloned().flatten())
+        .collect();
+
+    let mut merged = MergedPage {
+        items: Vec::new(),
+        edges: Vec::new(),
+        sources: Vec::new(),
+        cursors: Vec::new(),
+        has_next_page: false,
+    };
+    while merged.items.len() < limit {
+        // The unfetched items of a drained source could come before any item left.
+        if queues
+            .iter()
+            .zip(&more)
+            .any(|(queue, more)| queue.is_empty() && *more)
+        {
+            break;
+        }
+        let Some(source) = first_source(&queues, sort)? else {
+            break;
+        };
+        let Some((edge, item)) = queues.get_mut(source).and_then(VecDeque::pop_front) else {
+            break;
+        };
+        if let Some(cursor) = next.get_mut(source) {
+            *cursor = Some(DirectedCursor::Forward(edge.clone()));
+        }
+        merged.items.push(item);
+        merged.edges.push(edge);
+        merged.sources.push(source);
+    }
+    merged.has_next_page =
+        queues.iter().any(|queue| !queue.is_empty()) || more.iter().any(|more| *more);
+    merged.cursors = next;
+    Ok(merged)
+}
+
+/// Returns the source whose next item comes first under `sort`, `None` once all are drained.
+fn first_source<T>(
+    queues: &[VecDeque<(Edge, T)>],
+    sort: &Document,
+) -> Result<Option<usize>, CursorError> {
+    let mut first: Option<(usize, &Edge)> = None;
+    for (source, queue) in queues.iter().enumerate() {
+        let Some((edge, _item)) = queue.front() else {
+            continue;
+        };
+        let earlier = match first {
+            Some((_source, first_edge)) => edge.cmp_with(first_edge, sort)? == Ordering::Less,
+            None => true,
+        };
+        if earlier {
+            first = Some((source, edge));
+        }
+    }
+    Ok(first.map(|(source, _edge)| source))
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::doc;
+
+    use super::merge_pages;
+    use crate::error::CursorError;
+    use crate::model::{DirectedCursor, Edge, FindResult, PageInfo};
+
+    fn edge(id: i64) -> Edge {
+        Edge::from_document(doc! { "_id": id })
+    }
+
+    fn page(ids: &[i64], has_next_page: Option<bool>) -> FindResult<i64> {
+        FindResult {
+            edges: ids.iter().map(|id| edge(*id)).collect(),
+            items: ids.to_vec(),
+            page_info: PageInfo {
+                has_next_page,
+                ..PageInfo::default()
+            },
+            ..FindResult::default()
+        }
+    }
+
+    #[test]
+    fn merges_in_sort_order() {
+        let pages = vec![page(&[1, 4, 5], Some(false)), page(&[2, 3], Some(false))];
+        let merged = merge_pages(pages, &[None, None], &doc! { "_id": 1_i32 }, 3).ok();
+        assert_eq!(
+            merged.as_ref().map(|merged| merged.items.clone()),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(
+            merged.as_ref().map(|merged| merged.sources.clone()),
+            Some(vec![0, 1, 1])
+        );
+        assert_eq!(
+            merged.as_ref().map(|merged| merged.cursors.clone()),
+            Some(vec![
+                Some(DirectedCursor::Forward(edge(1))),
+                Some(DirectedCursor::Forward(edge(3))),
+            ])
+        );
+        assert_eq!(merged.map(|merged| merged.has_next_page), Some(true));
+    }
+
+    #[test]
+    fn merges_descending_sorts() {
+        let pages = vec![page(&[5, 1], Some(false)), page(&[4, 2], Some(false))];
+        let merged = merge_pages(pages, &[None, None], &doc! { "_id": -1_i32 }, 4).ok();
+        assert_eq!(
+            merged.as_ref().map(|merged| merged.items.clone()),
+            Some(vec![5, 4, 2, 1])
+        );
+        assert_eq!(merged.map(|merged| merged.has_next_page), Some(false));
+    }
+
+    #[test]
+    fn stops_at_a_drained_source_with_more_items() {
+        let before = Some(DirectedCursor::Forward(edge(0)));
+        let pages = vec![page(&[1, 2], Some(true)), page(&[3], Some(false))];
+        let merged = merge_pages(pages, &[None, before.clone()], &doc! { "_id": 1_i32 }, 4).ok();
+        assert_eq!(
+            merged.as_ref().map(|merged| merged.items.clone()),
+            Some(vec![1, 2])
+        );
+        assert_eq!(
+            merged.as_ref().map(|merged| merged.cursors.clone()),
+            Some(vec![Some(DirectedCursor::Forward(edge(2))), before])
+        );
+        assert_eq!(merged.map(|merged| merged.has_next_page), Some(true));
+    }
+
+    #[test]
+    fn counts_full_pages_with_unknown_flags_as_more() {
+        let pages = vec![page(&[1, 2], None), page(&[3], None)];
+        let merged = merge_pages(pages, &[None, None], &doc! { "_id": 1_i32 }, 2).ok();
+        assert_eq!(
+            merged.as_ref().map(|merged| merged.items.clone()),
+            Some(vec![1, 2])
+        );
+        assert_eq!(merged.map(|merged| merged.has_next_page), Some(true));
+    }
+
+    #[test]
+    fn rejects_invalid_sort_directions() {
+        let pages = vec![page(&[1], Some(false)), page(&[2], Some(false))];
+        assert!(matches!(
+            merge_pages(pages, &[None, None], &doc! { "_id": 2_i32 }, 2),
+            Err(CursorError::InvalidSortDirection { .. })
+        ));
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+/// Receives measurements about the pagination queries of a [`Paginator`](crate::Paginator).
+///
+/// Every method has an empty default implementation, so only the measurements of interest
+/// need to be implemented.
+pub trait PaginationMetrics: Send + Sync {
+    /// Called once for every page served, with the number of items in that page.
+    fn page_served(&self, _collection: &str, _items: usize) {}
+
+    /// Called with the duration of every count query.
+    fn count_duration(&self, _collection: &str, _duration: Duration) {}
+
+    /// Called every time a cursor could not be decoded or does not fit the query.
+    fn cursor_decode_failure(&self, _collection: &str) {}
+}
+
+/// [`PaginationMetrics`] implementation reporting to the [metrics](https://docs.rs/metrics) facade.
+///
+/// All series are labeled with `collection`:
+/// * `mongodb_cursor_pagination_pages_served_total` (counter)
+/// * `mongodb_cursor_pagination_items_per_page` (histogram)
+/// * `mongodb_cursor_pagination_count_duration_seconds` (histogram)
+/// * `mongodb_cursor_pagination_cursor_decode_failures_total` (counter)
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct MetricsRecorder;
+
+#[cfg(feature = "metrics")]
+impl PaginationMetrics for MetricsRecorder {
+    fn page_served(&self, collection: &str, items: usize) {
+        let labels = [("collection", collection.to_owned())];
+        metrics::counter!("mongodb_cursor_pagination_pages_served_total", &labels).increment(1);
+        metrics::histogram!("mongodb_cursor_pagination_items_per_page", &labels)
+            .record(u32::try_from(items).unwrap_or(u32::MAX));
+    }
+
+    fn count_duration(&self, collection: &str, duration: Duration) {
+        let labels = [("collection", collection.to_owned())];
+        metrics::histogram!("mongodb_cursor_pagination_count_duration_seconds", &labels)
+            .record(duration);
+    }
+
+    fn cursor_decode_failure(&self, collection: &str) {
+        let labels = [("collection", collection.to_owned())];
+        metrics::counter!(
+            "mongodb_cursor_pagination_cursor_decode_failures_total",
+            &labels
+        )
+        .increment(1);
+    }
+}
@@ -1,4 +1,8 @@
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::time::Duration;
 use std::{
     fmt::Display,
     ops::{Deref, DerefMut},
@@ -6,21 +10,66 @@ use std::{
 
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine;
-use bson::Document;
-use serde::de::{self, Visitor};
+use bson::document::{Keys, Values};
+use bson::{doc, Bson, Document};
+use serde::de::{self, DeserializeOwned, Visitor};
 use serde::{ser, Deserialize, Serialize};
 
-use crate::option::CursorOptions;
+use crate::codec::CursorCodec;
+use crate::compare::compare_keys;
+#[cfg(feature = "graphql")]
+use crate::cursor::Cursor;
+use crate::error::CursorError;
+use crate::option::{cursor_key, sort_value, CursorOptions};
+use crate::plan::EffectiveOptions;
 
 /// Represents a Cursor to an Item with no special direction.
 /// To Debug the contents, use `Debug`
-/// When serializing or converting to String, the [`Edge`] gets encoded as url-safe Base64 String.
-#[derive(Clone, Debug, PartialEq)]
-pub struct Edge(Document);
+/// When serializing or converting to String, the [`Edge`] gets encoded with the [`CursorCodec`]
+/// of the [`Paginator`](crate::Paginator) that returned it, once when the page is built, or as
+/// url-safe Base64 String. Edges are parsed from and deserialized as url-safe Base64 String
+/// only, which a paginator whose codec signs, scopes or adds claims to its cursors rejects.
+/// Decode the cursors of such a codec, or of another [`CursorEncoding`](crate::CursorEncoding),
+/// with [`Paginator::decode_cursor`](crate::Paginator::decode_cursor) instead.
+///
+/// The encoding is canonical: numbers are stored as `Int64` where `MongoDB` would compare them
+/// equal to an integer, so the same position yields the same string unless the codec stamps
+/// claims like the issue time into it. Equality and hashing follow the encoding of the sort keys
+/// only, not of annotations like the offset, so edges of the same position are equal and usable
+/// as cache keys, also across processes. Edges have no `Ord`, their order depends on the sort,
+/// see [`Edge::cmp_with`].
+///
+/// Sort keys of any type a range query can compare round trip unchanged, including
+/// `Decimal128`, `DateTime` and `Timestamp`. Arrays, regular expressions, `undefined`, `NaN` and
+/// documents with `$` keys are rejected with [`CursorError::CursorFieldTypeMismatch`] before any
+/// query runs.
+#[derive(Clone, Debug)]
+pub struct Edge {
+    document: Document,
+    offset: Option<u64>,
+    high_water: Option<Bson>,
+    insertion_bound: Option<Bson>,
+    recent_ids: Vec<Bson>,
+    collation: Option<Document>,
+    encoded: Option<String>,
+    unverified: bool,
+}
+
+/// Key of the offset inside an encoded [`Edge`]. Sort keys can't start with `$`, so it never clashes.
+const OFFSET: &str = "$offset";
+/// Key of the high-water mark inside an encoded [`Edge`].
+const HIGH_WATER: &str = "$highWater";
+/// Key of the insertion bound inside an encoded [`Edge`].
+const INSERTION_BOUND: &str = "$insertionBound";
+/// Key of the recently walked `_id`s inside an encoded [`Edge`].
+const RECENT_IDS: &str = "$recentIds";
+/// Key of the collation of the page inside an encoded [`Edge`].
+const COLLATION: &str = "$collation";
 
 impl Edge {
     /// Creates a new [`Edge`] using a value Document and the sorting keys.
     /// Only retains the values of the keys specified in the sort options to optimize storage.
+    /// The edge records the collation of `options`, see [`Edge::collation`].
     ///
     /// # Arguments
     /// * `document`: The Item to which the Edge will point to
@@ -33,21 +82,370 @@ impl Edge {
             .clone()
             .unwrap_or_default()
             .keys()
-            .filter_map(|key| document.get(key).map(|value| (key, value)))
+            .map(|key| cursor_key(key))
+            .filter_map(|key| sort_value(document, key).map(|value| (key, value)))
             .for_each(|(key, value)| {
                 cursor.insert(key, value);
             });
-        Self(cursor)
+        let mut edge = Self::from_document(cursor);
+        edge.collation = options.collation_document();
+        edge
+    }
+
+    /// Creates a new [`Edge`] from a typed item, e.g. one that was just inserted, without
+    /// fetching it again as a [`Document`].
+    ///
+    /// # Arguments
+    /// * `item`: The Item to which the Edge will point to, serialized to BSON
+    /// * `options`: Used to extract the sorting keys
+    pub fn from_item<T: Serialize>(item: &T, options: &CursorOptions) -> Result<Self, CursorError> {
+        Ok(Self::new(&bson::to_document(item)?, options))
+    }
+
+    /// Returns the sort keys the edge holds, in the order of the sort.
+    #[must_use]
+    pub fn keys(&self) -> Keys<'_> {
+        self.document.keys()
+    }
+
+    /// Returns the values of the sort keys, in the order of [`Edge::keys`].
+    #[must_use]
+    pub fn values(&self) -> Values<'_> {
+        self.document.values()
+    }
+
+    /// Deserializes the sort keys of the edge into `T`, e.g. to show or log where a page resumes.
+    pub fn decode_into<T: DeserializeOwned>(&self) -> Result<T, CursorError> {
+        Ok(bson::from_document(self.document.clone())?)
+    }
+
+    /// Deserializes the value of the sort key `key`, failing with
+    /// [`CursorError::MissingSortKey`] if the edge doesn't hold it.
+    pub fn get_typed<V: DeserializeOwned>(&self, key: &str) -> Result<V, CursorError> {
+        let value = self
+            .document
+            .get(key)
+            .ok_or_else(|| CursorError::MissingSortKey(key.to_owned()))?;
+        Ok(bson::from_bson(value.clone())?)
+    }
+
+    /// Checks that the edge fits the sort of `options` and survives being encoded, e.g. for a
+    /// cursor received from a client, so a mismatch is reported before running any query. Every
+    /// sort key must hold a single value and the edge must hold nothing else. An empty edge,
+    /// like the one of [`DirectedCursor::from_end`], always fits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CursorError::MissingSortKey`] or [`CursorError::UnknownCursorKey`] if the keys
+    /// differ from the sort keys, [`CursorError::CursorFieldTypeMismatch`] for a value no range
+    /// query can continue from, see [`Edge`], and [`CursorError::InvalidCursor`] if the edge
+    /// decodes differently.
+    pub fn verify_round_trip(&self, options: &CursorOptions) -> Result<(), CursorError> {
+        if self.document.is_empty() {
+            return Ok(());
+        }
+        let sort = options.sort().cloned().unwrap_or_default();
+        let keys: Vec<&str> = sort.keys().map(|key| cursor_key(key)).collect();
+        for key in &keys {
+            let value = self
+                .document
+                .get(key)
+                .ok_or_else(|| CursorError::MissingSortKey((*key).to_owned()))?;
+            check_cursor_value(key, value)?;
+        }
+        if let Some(key) = self
+            .document
+            .keys()
+            .find(|key| !keys.contains(&key.as_str()))
+        {
+            return Err(CursorError::UnknownCursorKey(key.clone()));
+        }
+        let decoded = Self::from_document(bson::from_slice(&self.to_bytes()?)?);
+        if decoded.to_document() != self.to_document() {
+            return Err(CursorError::InvalidCursor);
+        }
+        Ok(())
+    }
+
+    /// Compares the position of this edge to `other` under `sort`, e.g. the normalized sort of
+    /// [`CursorOptions::sort`], in the BSON comparison order of `MongoDB` without a collation.
+    /// Numbers compare by value across their types and a missing key compares like `null`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CursorError::InvalidSortDirection`] if a direction of `sort` isn't 1 or -1.
+    pub fn cmp_with(&self, other: &Self, sort: &Document) -> Result<Ordering, CursorError> {
+        compare_keys(sort, &self.document, &other.document)
+    }
+
+    /// Whether this edge comes before `other` under `sort`, see [`Edge::cmp_with`].
+    pub fn precedes(&self, other: &Self, sort: &Document) -> Result<bool, CursorError> {
+        Ok(self.cmp_with(other, sort)?.is_lt())
+    }
+
+    /// Returns the position of the item among all items matching the query, if the page was
+    /// fetched with [`PaginationConfig::offsets`](crate::PaginationConfig::offsets) enabled.
+    #[must_use]
+    pub const fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+
+    pub(crate) const fn with_offset(mut self, offset: Option<u64>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Returns the latest value of the change key among the items walked up to this edge, if
+    /// the page was fetched with [`PaginationConfig::change_key`](crate::PaginationConfig::change_key)
+    /// set. Pass the edge to [`Paginator::find_changes_since`](crate::Paginator::find_changes_since)
+    /// to find the items changed afterwards.
+    #[must_use]
+    pub const fn high_water_mark(&self) -> Option<&Bson> {
+        self.high_water.as_ref()
+    }
+
+    pub(crate) fn with_high_water(mut self, high_water: Option<Bson>) -> Self {
+        self.high_water = high_water;
+        self
+    }
+
+    /// Returns the latest value of the insertion key when the walk up to this edge started, if
+    /// the page was fetched with
+    /// [`PaginationConfig::insertion_key`](crate::PaginationConfig::insertion_key) set. Later
+    /// pages leave out the items with a later value.
+    #[must_use]
+    pub const fn insertion_bound(&self) -> Option<&Bson> {
+        self.insertion_bound.as_ref()
+    }
+
+    pub(crate) fn with_insertion_bound(mut self, insertion_bound: Option<Bson>) -> Self {
+        self.insertion_bound = insertion_bound;
+        self
+    }
+
+    /// Returns the `_id`s of the last items walked up to this cursor, if the page was fetched
+    /// with [`PaginationConfig::detect_drift`](crate::PaginationConfig::detect_drift) enabled.
+    #[must_use]
+    pub fn recent_ids(&self) -> &[Bson] {
+        &self.recent_ids
+    }
+
+    pub(crate) fn with_recent_ids(mut self, recent_ids: Vec<Bson>) -> Self {
+        self.recent_ids = recent_ids;
+        self
+    }
+
+    /// Returns the collation of the page the edge comes from, if its query had one. The string
+    /// keys of the edge only sort in place under that collation, so a query with another one, or
+    /// without one if it had one and the other way around, rejects the edge with
+    /// [`CursorError::CollationMismatch`].
+    #[must_use]
+    pub const fn collation(&self) -> Option<&Document> {
+        self.collation.as_ref()
+    }
+
+    pub(crate) fn from_document(mut document: Document) -> Self {
+        let offset = match document.remove(OFFSET) {
+            Some(Bson::Int64(offset)) => u64::try_from(offset).ok(),
+            _ => None,
+        };
+        let high_water = document.remove(HIGH_WATER).map(canonical);
+        let insertion_bound = document.remove(INSERTION_BOUND).map(canonical);
+        let recent_ids = match document.remove(RECENT_IDS) {
+            Some(Bson::Array(recent_ids)) => recent_ids,
+            _ => Vec::new(),
+        };
+        let collation = match document.remove(COLLATION) {
+            Some(Bson::Document(collation)) => Some(collation),
+            _ => None,
+        };
+        Self {
+            document: canonical_document(document),
+            offset,
+            high_water,
+            insertion_bound,
+            recent_ids,
+            collation,
+            encoded: None,
+            unverified: false,
+        }
+    }
+
+    /// Encodes the edge with `codec` once, so displaying or serializing it can't fail.
+    pub(crate) fn encoded_with(mut self, codec: &CursorCodec) -> Result<Self, CursorError> {
+        self.encoded = Some(codec.encode(&self)?);
+        Ok(self)
+    }
+
+    /// Whether the edge was parsed from a string without a [`CursorCodec`] verifying it.
+    pub(crate) const fn is_unverified(&self) -> bool {
+        self.unverified
+    }
+
+    /// Parses an edge encoded without envelope as url-safe Base64 String.
+    fn parse_unverified(str: &str) -> Result<Self, CursorError> {
+        let doc = URL_SAFE_NO_PAD
+            .decode(str)
+            .map_err(|_decode| CursorError::InvalidCursor)?;
+        Ok(Self::from_document(bson::from_slice(doc.as_slice())?).unverified())
+    }
+
+    /// Marks the edge as parsed without a [`CursorCodec`] verifying it.
+    pub(crate) const fn unverified(mut self) -> Self {
+        self.unverified = true;
+        self
+    }
+
+    /// Returns the edge encoded with the codec of its page, or as url-safe Base64 String.
+    fn encode(&self) -> Result<String, CursorError> {
+        match &self.encoded {
+            Some(encoded) => Ok(encoded.clone()),
+            None => Ok(URL_SAFE_NO_PAD.encode(self.to_bytes()?)),
+        }
+    }
+
+    /// Returns the canonical form of the edge including its offset, high-water mark, insertion
+    /// bound, recent ids and collation, even after it was modified through `DerefMut`. The
+    /// annotations are stored under keys starting with `$`, like `$offset`, which is the
+    /// document the edge gets encoded from.
+    #[must_use]
+    pub fn to_document(&self) -> Document {
+        let mut document = canonical_document(self.document.clone());
+        if let Some(offset) = self.offset {
+            document.insert(OFFSET, i64::try_from(offset).unwrap_or(i64::MAX));
+        }
+        if let Some(high_water) = &self.high_water {
+            document.insert(HIGH_WATER, high_water.clone());
+        }
+        if let Some(insertion_bound) = &self.insertion_bound {
+            document.insert(INSERTION_BOUND, insertion_bound.clone());
+        }
+        if !self.recent_ids.is_empty() {
+            document.insert(RECENT_IDS, self.recent_ids.clone());
+        }
+        if let Some(collation) = &self.collation {
+            document.insert(COLLATION, collation.clone());
+        }
+        document
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, CursorError> {
+        Ok(bson::to_vec(&self.to_document())?)
+    }
+
+    /// Encodes the canonical sort keys only, which identify the position of the edge.
+    fn key_bytes(&self) -> Result<Vec<u8>, CursorError> {
+        Ok(bson::to_vec(&canonical_document(self.document.clone()))?)
+    }
+}
+
+/// Checks that a range query can continue from `value`, the value of the sort key `key` in a
+/// cursor. `MongoDB` rejects regular expressions and `undefined` as bounds, `NaN` never is the
+/// bound of a range and arrays sort by one of their elements only. Documents with `$` keys would
+/// run as operators in the equalities of the keyset filter.
+pub(crate) fn check_cursor_value(key: &str, value: &Bson) -> Result<(), CursorError> {
+    let expected = if let Bson::Array(_) = value {
+        "a single value, arrays can't be paginated"
+    } else if has_operator(value) {
+        "a value without `$` keys"
+    } else if matches!(
+        value,
+        Bson::Undefined
+            | Bson::RegularExpression(_)
+            | Bson::DbPointer(_)
+            | Bson::JavaScriptCodeWithScope(_)
+    ) {
+        "a value range queries can compare"
+    } else if value.as_f64().is_some_and(f64::is_nan)
+        || matches!(value, Bson::Decimal128(decimal) if decimal.to_string().ends_with("NaN"))
+    {
+        "a number other than NaN"
+    } else {
+        return Ok(());
+    };
+    Err(CursorError::CursorFieldTypeMismatch {
+        field: key.to_owned(),
+        expected,
+        found: value.element_type(),
+    })
+}
+
+/// Whether `value` holds a document with a key starting with `$` at any depth.
+fn has_operator(value: &Bson) -> bool {
+    if let Some(document) = value.as_document() {
+        document
+            .iter()
+            .any(|(key, nested)| key.starts_with('$') || has_operator(nested))
+    } else {
+        value
+            .as_array()
+            .is_some_and(|values| values.iter().any(has_operator))
+    }
+}
+
+/// Normalizes the numbers of `document`, see [`Edge`].
+fn canonical_document(document: Document) -> Document {
+    document
+        .into_iter()
+        .map(|(key, value)| (key, canonical(value)))
+        .collect()
+}
+
+/// Largest integer a double holds exactly.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Returns the canonical form of a value of an [`Edge`]: `Int32` values and doubles holding an
+/// integer `MongoDB` compares equal to become `Int64`, also inside documents and arrays.
+pub(crate) fn canonical(value: Bson) -> Bson {
+    if let Bson::Int32(int) = value {
+        return Bson::Int64(i64::from(int));
+    }
+    if let Bson::Double(double) = value {
+        if double.fract() == 0.0_f64 && double.abs() <= MAX_SAFE_INTEGER {
+            #[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
+            // Checked to be an integer in range
+            return Bson::Int64(double as i64);
+        }
+    }
+    if let Bson::Document(document) = value {
+        return Bson::Document(canonical_document(document));
+    }
+    if let Bson::Array(array) = value {
+        return Bson::Array(array.into_iter().map(canonical).collect());
+    }
+    value
+}
+
+impl PartialEq for Edge {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.key_bytes(), other.key_bytes()) {
+            (Ok(bytes), Ok(other_bytes)) => bytes == other_bytes,
+            _ => self.document == other.document,
+        }
+    }
+}
+
+impl Eq for Edge {}
+
+impl Hash for Edge {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if let Ok(bytes) = self.key_bytes() {
+            bytes.hash(state);
+        }
     }
 }
 
 impl Display for Edge {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            fmt,
-            "{}",
-            &URL_SAFE_NO_PAD.encode(bson::to_vec(&self.0).map_err(ser::Error::custom)?)
-        )
+        fmt.write_str(&self.encode().map_err(|_error| fmt::Error)?)
+    }
+}
+
+impl FromStr for Edge {
+    type Err = CursorError;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        Self::parse_unverified(str)
     }
 }
 
@@ -56,9 +454,7 @@ impl Serialize for Edge {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(
-            &URL_SAFE_NO_PAD.encode(bson::to_vec(&self.0).map_err(ser::Error::custom)?),
-        )
+        serializer.serialize_str(&self.encode().map_err(ser::Error::custom)?)
     }
 }
 
@@ -76,10 +472,7 @@ impl<'de> Deserialize<'de> for Edge {
             }
 
             fn visit_str<E: de::Error>(self, str: &str) -> Result<Self::Value, E> {
-                let doc = URL_SAFE_NO_PAD.decode(str).map_err(de::Error::custom)?;
-                Ok(Edge(
-                    bson::from_slice(doc.as_slice()).map_err(de::Error::custom)?,
-                ))
+                Edge::parse_unverified(str).map_err(de::Error::custom)
             }
         }
         deserializer.deserialize_str(Vis)
@@ -90,8 +483,8 @@ impl<'de> Deserialize<'de> for Edge {
 #[juniper::graphql_object]
 #[allow(clippy::multiple_inherent_impl)]
 impl Edge {
-    fn cursor(&self) -> String {
-        self.0.to_string()
+    fn cursor(&self) -> Cursor {
+        Cursor::from(self.clone())
     }
 }
 
@@ -99,13 +492,16 @@ impl Deref for Edge {
     type Target = Document;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.document
     }
 }
 
 impl DerefMut for Edge {
+    /// Drops the string the codec of the page encoded, the edge is encoded as url-safe Base64
+    /// String after a change.
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        self.encoded = None;
+        &mut self.document
     }
 }
 
@@ -113,59 +509,391 @@ impl DerefMut for Edge {
 /// The cursor have the respecting direction to be used as is in an subsequent find:
 /// `start_cursor`: `Backwards`
 /// `end_cursor`: `Forward`
+/// `start_cursor_forward`: `Forward`
+/// `end_cursor_backward`: `Backwards`
 ///
-/// Note: `has_xxx` means if the next page has items, not if there is a next cursor
+/// Note: `has_xxx` means if the next page has items, not if there is a next cursor. It is `None`
+/// if that is unknown, e.g. with [`HasPageStrategy::Skip`](crate::HasPageStrategy::Skip).
+/// Before 2.0 the flags were plain `bool`s, use [`PageInfo::is_last_page`] or
+/// [`PageInfo::next_request`] instead of comparing them to `false`.
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 #[non_exhaustive]
 pub struct PageInfo {
-    /// True if there is a previous page which contains items
-    pub has_previous_page: bool,
-    /// True if there is a next page which contains items
-    pub has_next_page: bool,
+    /// True if there is a previous page which contains items, `None` if unknown
+    pub has_previous_page: Option<bool>,
+    /// True if there is a next page which contains items, `None` if unknown
+    pub has_next_page: Option<bool>,
     /// Cursor to the first item of the page. Is set even when there is no previous page.
     pub start_cursor: Option<DirectedCursor>,
     /// Cursor to the last item of the page. Is set even when there is no next page.
     pub end_cursor: Option<DirectedCursor>,
+    /// Forward cursor to the first item of the page, continuing with the items after it
+    #[serde(default)]
+    pub start_cursor_forward: Option<DirectedCursor>,
+    /// Backward cursor to the last item of the page, continuing with the items before it
+    #[serde(default)]
+    pub end_cursor_backward: Option<DirectedCursor>,
+    /// Number of the page starting at 1, only set with `PaginationConfig::page_numbers`
+    #[serde(default)]
+    pub approx_page_number: Option<u64>,
+    /// Number of pages of all matching items, only set with `PaginationConfig::page_numbers` and
+    /// a counted [`Count`]
+    #[serde(default)]
+    pub approx_total_pages: Option<u64>,
+}
+
+impl PageInfo {
+    /// Returns the cursor to pass to the next `find_paginated` call to get the next page, or
+    /// `None` if this is the last page. If that is unknown, the cursor is returned until a page
+    /// comes back empty.
+    #[must_use]
+    pub fn next_request(&self) -> Option<DirectedCursor> {
+        self.end_cursor
+            .clone()
+            .filter(|_| self.has_next_page != Some(false))
+    }
+
+    /// Returns the cursor to pass to the next `find_paginated` call to get the previous page, or
+    /// `None` if this is the first page. If that is unknown, the cursor is returned until a page
+    /// comes back empty.
+    #[must_use]
+    pub fn previous_request(&self) -> Option<DirectedCursor> {
+        self.start_cursor
+            .clone()
+            .filter(|_| self.has_previous_page != Some(false))
+    }
+
+    /// Returns `true` if there is known to be no page before this one.
+    #[must_use]
+    pub const fn is_first_page(&self) -> bool {
+        matches!(self.has_previous_page, Some(false))
+    }
+
+    /// Returns `true` if there is known to be no page after this one.
+    #[must_use]
+    pub const fn is_last_page(&self) -> bool {
+        matches!(self.has_next_page, Some(false))
+    }
+
+    /// Renders the page info as a JSON object with camel case keys, e.g. to log it. Cursors are
+    /// written as the string of their edge, unknown flags and unset fields as `null`.
+    pub fn to_json(&self) -> Result<String, CursorError> {
+        let cursor = |cursor: Option<&DirectedCursor>| {
+            cursor
+                .map(|cursor| cursor.inner().encode())
+                .transpose()
+                .map(|cursor| cursor.map_or(Bson::Null, Bson::String))
+        };
+        let number = |number: Option<u64>| {
+            number.map_or(Bson::Null, |number| {
+                Bson::Int64(i64::try_from(number).unwrap_or(i64::MAX))
+            })
+        };
+        let document = doc! {
+            "hasPreviousPage": self.has_previous_page,
+            "hasNextPage": self.has_next_page,
+            "startCursor": cursor(self.start_cursor.as_ref())?,
+            "endCursor": cursor(self.end_cursor.as_ref())?,
+            "startCursorForward": cursor(self.start_cursor_forward.as_ref())?,
+            "endCursorBackward": cursor(self.end_cursor_backward.as_ref())?,
+            "approxPageNumber": number(self.approx_page_number),
+            "approxTotalPages": number(self.approx_total_pages),
+        };
+        Ok(Bson::Document(document).into_relaxed_extjson().to_string())
+    }
+}
+
+/// Writes a page flag of [`PageInfo`] for its `Display`.
+const fn flag(flag: Option<bool>) -> &'static str {
+    match flag {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "unknown",
+    }
+}
+
+impl Display for PageInfo {
+    /// Writes a one-line summary, e.g.
+    /// `previous: no, next: yes, start: AQAAAA, end: AgAAAA, page: 1/4`. Unset cursors are
+    /// written as `-`, the page number only if it is set.
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cursor = |cursor: Option<&DirectedCursor>| {
+            cursor.map_or_else(|| Ok("-".to_owned()), |cursor| cursor.inner().encode())
+        };
+        write!(
+            fmt,
+            "previous: {}, next: {}, start: {}, end: {}",
+            flag(self.has_previous_page),
+            flag(self.has_next_page),
+            cursor(self.start_cursor.as_ref()).map_err(|_error| fmt::Error)?,
+            cursor(self.end_cursor.as_ref()).map_err(|_error| fmt::Error)?,
+        )?;
+        if let Some(page) = self.approx_page_number {
+            write!(fmt, ", page: {page}")?;
+            if let Some(pages) = self.approx_total_pages {
+                write!(fmt, "/{pages}")?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(feature = "graphql")]
 #[juniper::graphql_object]
+#[allow(clippy::multiple_inherent_impl)]
 impl PageInfo {
-    fn has_next_page(&self) -> bool {
+    fn has_next_page(&self) -> Option<bool> {
         self.has_next_page
     }
 
-    fn has_previous_page(&self) -> bool {
+    fn has_previous_page(&self) -> Option<bool> {
         self.has_previous_page
     }
 
-    fn start_cursor(&self) -> Option<String> {
-        self.start_cursor.as_ref().map(ToString::to_string)
+    fn start_cursor(&self) -> Option<Cursor> {
+        self.start_cursor.clone().map(Cursor::from)
     }
 
-    fn end_cursor(&self) -> Option<String> {
-        self.end_cursor.as_ref().map(ToString::to_string)
+    fn end_cursor(&self) -> Option<Cursor> {
+        self.end_cursor.clone().map(Cursor::from)
     }
 }
 
 /// The result of a find method with the items, edges, pagination info, and total count of objects
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[non_exhaustive]
 pub struct FindResult<T> {
     /// Current Page
     pub page_info: PageInfo,
     /// Edges to all items in the current Page, including start & end-cursor
     pub edges: Vec<Edge>,
-    /// Total count of items in the whole collection
-    pub total_count: u64,
+    /// Number of items matching the query, see [`Count`] for how accurate it is
+    pub total_count: Count,
     /// All items in the current Page
     pub items: Vec<T>,
+    /// Timings and sizes of the queries, only set if [`PaginationConfig::collect_stats`] is enabled
+    ///
+    /// [`PaginationConfig::collect_stats`]: crate::PaginationConfig::collect_stats
+    pub stats: Option<PageStats>,
+    /// Strong `ETag` of the page, e.g. `"3q2-7wHjyW7YhK0SXfbPgg"`, only set for pages of
+    /// `find_paginated` if [`PaginationConfig::etags`] is enabled. It changes whenever an item
+    /// or a page flag of the page changes.
+    ///
+    /// [`PaginationConfig::etags`]: crate::PaginationConfig::etags
+    pub etag: Option<String>,
+    /// True if an item of the page was already on a previous page of the walk, as an update of
+    /// a sort key moved it past the cursor, only checked if [`PaginationConfig::detect_drift`]
+    /// is enabled. Other items may have been skipped the same way, so refresh the pages.
+    ///
+    /// [`PaginationConfig::detect_drift`]: crate::PaginationConfig::detect_drift
+    pub drift_detected: bool,
+    /// Items that don't deserialize into `T`, only collected if
+    /// [`PaginationConfig::collect_mismatched`] is enabled. Their edges are left out as well.
+    ///
+    /// [`PaginationConfig::collect_mismatched`]: crate::PaginationConfig::collect_mismatched
+    pub mismatched: Vec<MismatchedItem>,
+    /// Filter and options the page was queried with
+    pub effective_options: EffectiveOptions,
+}
+
+/// A page without items with the flags of `page_info`.
+pub(crate) fn empty_page<T>(page_info: PageInfo) -> FindResult<T> {
+    FindResult {
+        page_info,
+        edges: Vec::new(),
+        total_count: Count::Unknown,
+        items: Vec::new(),
+        stats: None,
+        etag: None,
+        drift_detected: false,
+        mismatched: Vec::new(),
+        effective_options: EffectiveOptions::default(),
+    }
+}
+
+impl FindResult<Document> {
+    /// Deserializes the items of a page fetched as plain documents.
+    pub(crate) fn deserialize_items<T: DeserializeOwned>(
+        self,
+        collect_mismatched: bool,
+    ) -> Result<FindResult<T>, CursorError> {
+        let (items, edges, mismatched) =
+            deserialize_page(self.items, self.edges, collect_mismatched)?;
+        Ok(FindResult {
+            page_info: self.page_info,
+            edges,
+            total_count: self.total_count,
+            items,
+            stats: self.stats,
+            etag: self.etag,
+            drift_detected: self.drift_detected,
+            mismatched,
+            effective_options: self.effective_options,
+        })
+    }
+}
+
+#[allow(clippy::multiple_inherent_impl)]
+impl<T> FindResult<T> {
+    /// Returns a cursor after each `window` items of the page, e.g. for a virtualized list that
+    /// requests windows of that many items: the first cursor with a limit of `window` returns
+    /// the second window. The cursors continue forward in sort order, the last one is the
+    /// `end_cursor` if the page holds a multiple of `window` items. With
+    /// [`PaginationConfig::reverse_results`](crate::PaginationConfig::reverse_results) the items
+    /// are in the opposite order of the sort, so the cursors are `Backwards` instead and the last
+    /// one is the `start_cursor`. Returns no cursors for a `window` of 0.
+    #[must_use]
+    pub fn boundary_cursors(&self, window: usize) -> Vec<DirectedCursor> {
+        if window == 0 {
+            return Vec::new();
+        }
+        let reversed = self.is_reversed();
+        self.edges
+            .iter()
+            .skip(window.saturating_sub(1))
+            .step_by(window)
+            .cloned()
+            .map(|edge| {
+                if reversed {
+                    DirectedCursor::Backwards(edge)
+                } else {
+                    DirectedCursor::Forward(edge)
+                }
+            })
+            .collect()
+    }
+
+    /// Whether the edges are in the opposite order of the sort, as with `reverse_results`.
+    pub(crate) fn is_reversed(&self) -> bool {
+        self.edges.len() > 1
+            && self
+                .page_info
+                .end_cursor
+                .as_ref()
+                .is_some_and(|end| self.edges.first() == Some(end.inner()))
+    }
+}
+
+/// An item of a page that doesn't deserialize into the item type, e.g. a document of another
+/// shape in a collection holding several.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct MismatchedItem {
+    /// The `_id` of the item, `Null` if it has none
+    pub id: Bson,
+    /// Why the item doesn't deserialize
+    pub error: String,
+}
+
+/// The items of a page with their edges, and the items that didn't deserialize.
+type DeserializedPage<T> = (Vec<T>, Vec<Edge>, Vec<MismatchedItem>);
+
+/// Deserializes the `documents` of a page together with their `edges`. Unless
+/// `collect_mismatched` is set, the first document that doesn't deserialize fails the page.
+pub(crate) fn deserialize_page<T: DeserializeOwned>(
+    documents: Vec<Document>,
+    edges: Vec<Edge>,
+    collect_mismatched: bool,
+) -> Result<DeserializedPage<T>, CursorError> {
+    if !collect_mismatched {
+        let items = documents
+            .into_iter()
+            .map(bson::from_document)
+            .collect::<Result<_, _>>()?;
+        return Ok((items, edges, Vec::new()));
+    }
+    let mut items = Vec::with_capacity(documents.len());
+    let mut matched = Vec::with_capacity(edges.len());
+    let mut mismatched = Vec::new();
+    for (document, edge) in documents.into_iter().zip(edges) {
+        let id = document.get("_id").cloned().unwrap_or(Bson::Null);
+        match bson::from_document(document) {
+            Ok(item) => {
+                items.push(item);
+                matched.push(edge);
+            }
+            Err(error) => mismatched.push(MismatchedItem {
+                id,
+                error: error.to_string(),
+            }),
+        }
+    }
+    Ok((items, matched, mismatched))
+}
+
+/// Timings and sizes of the queries behind a [`FindResult`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PageStats {
+    /// Time spent running the find query and receiving all of its documents
+    pub find_duration: Duration,
+    /// Time spent running the count query
+    pub count_duration: Duration,
+    /// Number of batches the documents arrived in, estimated from the batch size
+    pub batches: u64,
+    /// Size of all received documents in bytes
+    pub bytes_received: u64,
+}
+
+/// How many items match the query of a [`FindResult`] and how trustworthy that number is.
+/// Choose how it is computed with [`CountMode`](crate::CountMode).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub enum Count {
+    /// The exact number of matching items
+    Exact(u64),
+    /// Estimated number of items in the whole collection, ignoring the filter
+    Estimate(u64),
+    /// There are more matching items than this bound, e.g. shown as "500+"
+    AtLeast(u64),
+    /// The items were not counted
+    #[default]
+    Unknown,
+}
+
+impl Count {
+    /// Returns the number regardless of its accuracy, or `None` if the items were not counted.
+    #[must_use]
+    pub const fn value(&self) -> Option<u64> {
+        match *self {
+            Self::Exact(count) | Self::Estimate(count) | Self::AtLeast(count) => Some(count),
+            Self::Unknown => None,
+        }
+    }
+
+    /// Returns `true` if the number is the exact count of matching items.
+    #[must_use]
+    pub const fn is_exact(&self) -> bool {
+        matches!(self, Self::Exact(_))
+    }
+}
+
+impl Display for Count {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact(count) => write!(fmt, "{count}"),
+            Self::Estimate(count) => write!(fmt, "~{count}"),
+            Self::AtLeast(count) => write!(fmt, "{count}+"),
+            Self::Unknown => write!(fmt, "unknown"),
+        }
+    }
+}
+
+/// Direction a [`DirectedCursor`] continues in, see [`DirectedCursor::direction`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(clippy::exhaustive_enums)] // Same variants as `DirectedCursor`
+pub enum CursorDirection {
+    /// Continues with the items before the cursor
+    Backwards,
+    /// Continues with the items after the cursor
+    Forward,
 }
 
 /// Cursor to an item with direction information.
 /// Serializing pertains the direction Information.
 /// To send only the Cursor use `to_string` which drops the direction information
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Cursors are equal if their directions and the sort keys of their edges are, see [`Edge`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[allow(clippy::exhaustive_enums)] // If there would ever be more Variants we would want the Code to break
 pub enum DirectedCursor {
     /// Use to invert the search e.g. go back a page
@@ -175,6 +903,43 @@ pub enum DirectedCursor {
 }
 
 impl DirectedCursor {
+    /// Returns a cursor to the end of the result set, which gets the last page when paging
+    /// backwards from it, like `last: N` without `before` in a GraphQL connection.
+    #[must_use]
+    pub fn from_end() -> Self {
+        Self::Backwards(Edge::from_document(Document::new()))
+    }
+
+    /// Parses a cursor continuing with the items after the encoded edge, e.g. from an `after`
+    /// query parameter. Like [`Edge::from_str`], it only reads url-safe Base64 String.
+    pub fn forward_from_str(str: &str) -> Result<Self, CursorError> {
+        str.parse().map(Self::Forward)
+    }
+
+    /// Parses a cursor continuing with the items before the encoded edge, e.g. from a `before`
+    /// query parameter. Like [`Edge::from_str`], it only reads url-safe Base64 String.
+    pub fn backward_from_str(str: &str) -> Result<Self, CursorError> {
+        str.parse().map(Self::Backwards)
+    }
+
+    /// Returns a cursor to `edge` continuing in `direction`.
+    #[must_use]
+    pub const fn with_direction(edge: Edge, direction: CursorDirection) -> Self {
+        match direction {
+            CursorDirection::Forward => Self::Forward(edge),
+            CursorDirection::Backwards => Self::Backwards(edge),
+        }
+    }
+
+    /// Returns the direction the cursor continues in.
+    #[must_use]
+    pub const fn direction(&self) -> CursorDirection {
+        match self {
+            Self::Forward(_) => CursorDirection::Forward,
+            Self::Backwards(_) => CursorDirection::Backwards,
+        }
+    }
+
     /// Reverses the direction of Cursor.
     #[must_use]
     pub fn reverse(self) -> Self {
@@ -191,6 +956,14 @@ impl DirectedCursor {
         }
     }
 
+    /// Replaces the edge of the cursor with `map` applied to it, keeping the direction.
+    pub(crate) fn map_edge<F: FnOnce(Edge) -> Edge>(self, map: F) -> Self {
+        match self {
+            Self::Forward(edge) => Self::Forward(map(edge)),
+            Self::Backwards(edge) => Self::Backwards(map(edge)),
+        }
+    }
+
     /// Removes the direction information and returns an Edge
     #[must_use]
     pub fn into_inner(self) -> Edge {
@@ -200,8 +973,246 @@ impl DirectedCursor {
     }
 }
 
+/// The arguments of a GraphQL connection as defined by the Relay specification, see
+/// [`Pagination::find_connection`](crate::Pagination::find_connection).
+///
+/// `last` and `before` take precedence over `first` and `after`: with either of them set the page
+/// ends right before `before`, or at the end of the result set without it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ConnectionArgs {
+    /// Number of items from the start of the page
+    pub first: Option<i64>,
+    /// Cursor the page starts after
+    pub after: Option<Edge>,
+    /// Number of items up to the end of the page
+    pub last: Option<i64>,
+    /// Cursor the page ends before
+    pub before: Option<Edge>,
+}
+
+impl ConnectionArgs {
+    /// Creates [`ConnectionArgs`] from the arguments of a connection field.
+    #[must_use]
+    pub const fn new(
+        first: Option<i64>,
+        after: Option<Edge>,
+        last: Option<i64>,
+        before: Option<Edge>,
+    ) -> Self {
+        Self {
+            first,
+            after,
+            last,
+            before,
+        }
+    }
+
+    /// Rejects combinations of arguments whose page the Relay specification leaves undefined
+    /// or discourages: `first` with `last`, `after` with `before`, `first` with `before`,
+    /// `last` with `after` and negative counts.
+    pub fn validate(&self) -> Result<(), CursorError> {
+        let reason = if self.first.is_some() && self.last.is_some() {
+            "`first` and `last` can't be combined"
+        } else if self.after.is_some() && self.before.is_some() {
+            "`after` and `before` can't be combined"
+        } else if self.first.is_some() && self.before.is_some() {
+            "`first` can't be combined with `before`"
+        } else if self.last.is_some() && self.after.is_some() {
+            "`last` can't be combined with `after`"
+        } else if self.first.or(self.last).is_some_and(|count| count < 0) {
+            "`first` and `last` must not be negative"
+        } else {
+            return Ok(());
+        };
+        Err(CursorError::InvalidConnectionArgs(reason))
+    }
+
+    /// Returns the limit and the cursor of the page.
+    pub(crate) fn into_request(self) -> (Option<i64>, Option<DirectedCursor>) {
+        if self.last.is_some() || self.before.is_some() {
+            let cursor = self
+                .before
+                .map_or_else(DirectedCursor::from_end, DirectedCursor::Backwards);
+            (self.last.or(self.first), Some(cursor))
+        } else {
+            (self.first, self.after.map(DirectedCursor::Forward))
+        }
+    }
+}
+
+impl From<Edge> for DirectedCursor {
+    /// Continues with the items after `edge`, the normal direction.
+    fn from(edge: Edge) -> Self {
+        Self::Forward(edge)
+    }
+}
+
 impl Display for DirectedCursor {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(fmt, "{}", self.inner())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use bson::doc;
+    use mongodb::options::{Collation, FindOptions};
+
+    use super::{CursorDirection, DirectedCursor, Edge, FindResult, PageInfo, COLLATION};
+    use crate::error::CursorError;
+    use crate::option::CursorOptions;
+
+    #[test]
+    fn compares_edges_under_a_sort() {
+        let sort = doc! { "count": -1_i32, "_id": 1_i32 };
+        let more = Edge::from_document(doc! { "count": 3.0_f64, "_id": 2_i32 });
+        let fewer = Edge::from_document(doc! { "count": 2_i64, "_id": 1_i32 });
+        let missing = Edge::from_document(doc! { "_id": 0_i32 });
+        assert_eq!(more.cmp_with(&fewer, &sort).ok(), Some(Ordering::Less));
+        assert_eq!(fewer.precedes(&missing, &sort).ok(), Some(true));
+        assert_eq!(missing.precedes(&missing, &sort).ok(), Some(false));
+        assert!(matches!(
+            more.cmp_with(&fewer, &doc! { "count": "desc" }),
+            Err(CursorError::InvalidSortDirection { .. })
+        ));
+    }
+
+    fn options(collation: Option<Collation>) -> CursorOptions {
+        let find_options = FindOptions::builder()
+            .sort(doc! { "name": 1_i32 })
+            .collation(collation)
+            .build();
+        CursorOptions::new(find_options, None)
+    }
+
+    #[test]
+    fn records_the_collation_only_if_set() {
+        let item = doc! { "name": "a", "_id": 1_i32 };
+        let binary = Edge::new(&item, &options(None));
+        assert!(!binary.to_document().contains_key(COLLATION));
+        assert_eq!(Edge::from_document(binary.to_document()).collation(), None);
+
+        let collated = Edge::new(
+            &item,
+            &options(Some(Collation::builder().locale("en").build())),
+        );
+        let decoded = Edge::from_document(collated.to_document());
+        assert!(decoded.collation().is_some());
+        assert_eq!(decoded.collation(), collated.collation());
+    }
+
+    #[test]
+    fn rejects_operators_in_cursor_values() {
+        let options = CursorOptions::new(
+            FindOptions::builder()
+                .sort(doc! { "created": 1_i32, "_id": 1_i32 })
+                .build(),
+            None,
+        );
+        let verify = |created: bson::Bson| {
+            Edge::from_document(doc! { "created": created, "_id": 7_i64 })
+                .verify_round_trip(&options)
+        };
+        assert!(matches!(
+            verify(doc! { "$exists": true }.into()),
+            Err(CursorError::CursorFieldTypeMismatch { .. })
+        ));
+        assert!(matches!(
+            verify(doc! { "at": { "$gt": 1_i64 } }.into()),
+            Err(CursorError::CursorFieldTypeMismatch { .. })
+        ));
+        assert_eq!(verify(doc! { "at": 1_i64 }.into()).ok(), Some(()));
+    }
+
+    #[test]
+    fn builds_directed_cursors_from_strings() {
+        let edge = Edge::from_document(doc! { "_id": 1_i64 });
+        let encoded = edge.to_string();
+        let forward = DirectedCursor::forward_from_str(&encoded).ok();
+        let backwards = DirectedCursor::backward_from_str(&encoded).ok();
+        assert_eq!(forward, Some(DirectedCursor::from(edge.clone())));
+        assert_eq!(
+            backwards,
+            Some(DirectedCursor::with_direction(
+                edge,
+                CursorDirection::Backwards
+            ))
+        );
+        assert_eq!(
+            forward.map(|cursor| cursor.direction()),
+            Some(CursorDirection::Forward)
+        );
+        assert!(matches!(
+            DirectedCursor::forward_from_str("not base64!"),
+            Err(CursorError::InvalidCursor)
+        ));
+    }
+
+    #[test]
+    fn summarizes_page_info() {
+        let edge = Edge::from_document(doc! { "_id": 1_i64 });
+        let info = PageInfo {
+            has_previous_page: Some(false),
+            has_next_page: None,
+            end_cursor: Some(DirectedCursor::Forward(edge.clone())),
+            approx_page_number: Some(1),
+            ..PageInfo::default()
+        };
+        assert_eq!(
+            info.to_string(),
+            format!("previous: no, next: unknown, start: -, end: {edge}, page: 1")
+        );
+        assert_eq!(
+            info.to_json().ok(),
+            Some(format!(
+                "{{\"hasPreviousPage\":false,\"hasNextPage\":null,\"startCursor\":null,\
+                 \"endCursor\":\"{edge}\",\"startCursorForward\":null,\
+                 \"endCursorBackward\":null,\"approxPageNumber\":1,\"approxTotalPages\":null}}"
+            ))
+        );
+    }
+
+    #[test]
+    fn returns_cursors_after_every_window() {
+        let page = FindResult {
+            edges: (1..=5_i64)
+                .map(|id| Edge::from_document(doc! { "_id": id }))
+                .collect(),
+            ..super::empty_page::<()>(PageInfo::default())
+        };
+        let ids = |window: usize| -> Vec<i64> {
+            page.boundary_cursors(window)
+                .iter()
+                .filter_map(|cursor| cursor.inner().get_i64("_id").ok())
+                .collect()
+        };
+        assert_eq!(ids(2), vec![2, 4]);
+        assert_eq!(ids(1), vec![1, 2, 3, 4, 5]);
+        assert_eq!(ids(5), vec![5]);
+        assert!(ids(6).is_empty());
+        assert!(ids(0).is_empty());
+        assert!(page
+            .boundary_cursors(2)
+            .iter()
+            .all(|cursor| matches!(cursor, DirectedCursor::Forward(_))));
+
+        let mut reversed = page.clone();
+        reversed.edges.reverse();
+        reversed.page_info.end_cursor =
+            reversed.edges.first().cloned().map(DirectedCursor::Forward);
+        let cursors = reversed.boundary_cursors(2);
+        assert_eq!(
+            cursors
+                .iter()
+                .filter_map(|cursor| cursor.inner().get_i64("_id").ok())
+                .collect::<Vec<_>>(),
+            vec![4, 2]
+        );
+        assert!(cursors
+            .iter()
+            .all(|cursor| matches!(cursor, DirectedCursor::Backwards(_))));
+    }
+}
@@ -1,44 +1,270 @@
-use bson::Bson;
-use mongodb::options::{CountOptions, EstimatedDocumentCountOptions, FindOptions};
+use bson::{Bson, Document};
+use mongodb::options::{
+    AggregateOptions, CountOptions, EstimatedDocumentCountOptions, FindOptions, Hint,
+};
 use serde::{Deserialize, Serialize};
-use std::ops::{Deref, DerefMut, Neg};
+use std::ops::{Deref, Neg};
+use std::time::Duration;
 
+use crate::error::CursorError;
 use crate::DirectedCursor;
 
+/// Sort key of the natural order, the order the documents are stored in.
+const NATURAL: &str = "$natural";
+
+/// Returns the key of the cursor holding the value of the sort key `key`. Documents have no
+/// `$natural` field, so natural order cursors hold the `_id`, assuming it grows with insertion
+/// order like an `ObjectId` from a single client or a capped collection written in order.
+pub(crate) fn cursor_key(key: &str) -> &str {
+    if key == NATURAL {
+        "_id"
+    } else {
+        key
+    }
+}
+
+/// Returns the value of the sort key `key` in `document`, following a dotted key like
+/// `author.name` into embedded documents. Edges hold dotted keys as they are, so they are looked
+/// up as a whole first.
+pub(crate) fn sort_value<'doc>(document: &'doc Document, key: &str) -> Option<&'doc Bson> {
+    document.get(key).or_else(|| {
+        let (parent, child) = key.split_once('.')?;
+        sort_value(document.get_document(parent).ok()?, child)
+    })
+}
+
+/// Whether `projection` only excludes fields, e.g. `{ "large": 0 }`.
+fn is_exclusion(projection: &Document) -> bool {
+    projection
+        .iter()
+        .filter(|(field, _value)| field.as_str() != "_id")
+        .all(|(_field, value)| is_excluded(value))
+}
+
+fn is_excluded(value: &Bson) -> bool {
+    matches!(
+        value,
+        Bson::Boolean(false) | Bson::Int32(0) | Bson::Int64(0)
+    ) || matches!(value, Bson::Double(double) if *double == 0.0_f64)
+}
+
+/// Removes the possibly nested field at the dotted `path` from `document`.
+fn remove_path(document: &mut Document, path: &str) {
+    match path.split_once('.') {
+        Some((head, rest)) => {
+            if let Some(Bson::Document(inner)) = document.get_mut(head) {
+                remove_path(inner, rest);
+            }
+        }
+        None => {
+            document.remove(path);
+        }
+    }
+}
+
+/// Whether the field `parent` contains the field `child`, e.g. `a` contains `a.b`.
+pub(crate) fn is_parent(parent: &str, child: &str) -> bool {
+    child
+        .strip_prefix(parent)
+        .is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// Whether the inclusion `projection` includes `key`, directly or through a parent.
+fn includes(projection: &Document, key: &str) -> bool {
+    projection
+        .keys()
+        .any(|field| field == key || is_parent(field, key))
+}
+
+/// Extends the projection of `options` so it returns every sort key, returning the fields
+/// added for that, which are stripped from the items again. An inclusion projection of only a
+/// child of a sort key can't be extended without returning fields the projection left out, it
+/// is rejected by [`CursorOptions::unprojected_sort_key`] instead.
+fn project_sort_keys(options: &mut FindOptions) -> Vec<String> {
+    let (Some(projection), Some(sort)) = (options.projection.as_mut(), options.sort.as_ref())
+    else {
+        return Vec::new();
+    };
+    let exclusion = is_exclusion(projection);
+    let mut stripped = Vec::new();
+    for key in sort.keys().map(|key| cursor_key(key)) {
+        if exclusion || key == "_id" {
+            let excluded: Vec<String> = projection
+                .iter()
+                .filter(|(field, value)| {
+                    is_excluded(value)
+                        && (field.as_str() == key || is_parent(field, key) || is_parent(key, field))
+                })
+                .map(|(field, _value)| field.clone())
+                .collect();
+            for field in excluded {
+                projection.remove(&field);
+                stripped.push(field);
+            }
+            continue;
+        }
+        if !includes(projection, key) && !projection.keys().any(|field| is_parent(key, field)) {
+            projection.insert(key, 1_i32);
+            stripped.push(key.to_owned());
+        }
+    }
+    stripped
+}
+
+/// The find options of a page, normalized for keyset pagination.
+///
+/// The sort always ends with a unique key, `_id` unless disabled, and is reversed in the
+/// directed options when paging backwards. Build it with [`CursorOptions::builder`], e.g. to
+/// create an [`Edge`](crate::Edge) of a document with [`Edge::new`](crate::Edge::new).
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CursorOptions {
     options: FindOptions,
     directed_options: FindOptions,
     cursor: Option<DirectedCursor>,
+    count_hint: Option<Hint>,
+    stripped: Vec<String>,
 }
 
 impl CursorOptions {
+    /// Returns a builder of the options, starting from default find options.
+    #[must_use]
+    pub fn builder() -> CursorOptionsBuilder {
+        CursorOptionsBuilder::default()
+    }
+
+    /// Creates the options of a page starting at `cursor`, appending `_id` to the sort.
     pub fn new<T: Into<FindOptions>>(options: T, cursor: Option<DirectedCursor>) -> Self {
+        Self::new_with_tiebreak(options, cursor, true)
+    }
+
+    /// Creates the options, appending `_id` to the sort as a tiebreak only if `tiebreak` is set
+    /// or the sort is empty. Without the tiebreak the sort has to be unique on its own. The
+    /// natural order can't be combined with other keys and never gets a tiebreak.
+    pub(crate) fn new_with_tiebreak<T: Into<FindOptions>>(
+        options: T,
+        cursor: Option<DirectedCursor>,
+        tiebreak: bool,
+    ) -> Self {
         let mut options = options.into();
 
         let mut sort = options.sort.unwrap_or_default();
-        if !sort.contains_key("_id") {
+        if (tiebreak || sort.is_empty()) && !sort.contains_key("_id") && !sort.contains_key(NATURAL)
+        {
             sort.insert("_id", -1_i32);
         }
         options.sort = Some(sort);
+        let stripped = project_sort_keys(&mut options);
         Self {
             directed_options: Self::get_directed(options.clone(), cursor.as_ref()),
             cursor,
             options,
+            count_hint: None,
+            stripped,
         }
     }
 
-    pub fn set_cursor(&mut self, cursor: DirectedCursor) {
+    /// Returns the normalized sort, including the tiebreak, in the order of the first page.
+    #[must_use]
+    pub fn sort(&self) -> Option<&Document> {
+        self.options.sort.as_ref()
+    }
+
+    /// Returns the options the query runs with, sorted in the direction of the cursor.
+    #[must_use]
+    pub const fn directed_options(&self) -> &FindOptions {
+        &self.directed_options
+    }
+
+    /// Returns the cursor the page starts at, `None` for the first page.
+    #[must_use]
+    pub const fn cursor(&self) -> Option<&DirectedCursor> {
+        self.cursor.as_ref()
+    }
+
+    /// Returns the first sort key the projection doesn't return.
+    pub(crate) fn unprojected_sort_key(&self) -> Option<&str> {
+        let projection = self.options.projection.as_ref()?;
+        if is_exclusion(projection) {
+            return None;
+        }
+        self.options
+            .sort
+            .iter()
+            .flat_map(Document::keys)
+            .map(|key| cursor_key(key))
+            .find(|key| *key != "_id" && !includes(projection, key))
+    }
+
+    /// Removes the fields only fetched to build the cursors from `document`.
+    pub(crate) fn strip(&self, document: &mut Document) {
+        for field in &self.stripped {
+            remove_path(document, field);
+        }
+    }
+
+    /// Uses `hint` for the count query instead of the hint of the find options.
+    #[must_use]
+    pub(crate) fn with_count_hint(mut self, hint: Option<Hint>) -> Self {
+        self.count_hint = hint;
+        self
+    }
+
+    /// Moves the page to start at `cursor`, redirecting the sort accordingly. Every other
+    /// option, e.g. the collation or read concern, is kept as is.
+    pub(crate) fn set_cursor(&mut self, cursor: DirectedCursor) {
         self.cursor = Some(cursor);
-        self.directed_options = Self::get_directed(self.options.clone(), self.cursor.as_ref());
+        self.directed_options.sort =
+            Self::directed_sort(self.options.sort.clone(), self.cursor.as_ref());
+    }
+
+    /// Sets the number of items skipped at the start of the page.
+    pub(crate) fn set_skip(&mut self, skip: Option<u64>) {
+        self.options.skip = skip;
+        self.directed_options.skip = skip;
+    }
+
+    /// Returns the hint for the count query, falling back to the hint of the find options.
+    #[must_use]
+    pub fn count_hint(&self) -> Option<Hint> {
+        self.count_hint.clone().or_else(|| self.hint.clone())
+    }
+
+    /// Returns the collation of the query as the cursors carry it.
+    pub(crate) fn collation_document(&self) -> Option<Document> {
+        bson::to_document(self.collation.as_ref()?).ok()
+    }
+
+    /// Returns the options of an aggregation counting the items, which uses the count hint.
+    #[must_use]
+    pub(crate) fn count_aggregate_options(&self) -> AggregateOptions {
+        let mut options = AggregateOptions::from(self);
+        options.hint = self.count_hint();
+        options
+    }
+
+    /// Returns the comment of the find options, preferring `comment_bson` over `comment`.
+    #[must_use]
+    pub fn comment(&self) -> Option<Bson> {
+        self.comment_bson
+            .clone()
+            .or_else(|| self.directed_options.comment.clone().map(Bson::String))
     }
 
     fn get_directed(mut options: FindOptions, cursor: Option<&DirectedCursor>) -> FindOptions {
+        options.sort = Self::directed_sort(options.sort, cursor);
+        options
+    }
+
+    /// Reverses every direction of `sort` for a backwards cursor.
+    fn directed_sort(
+        mut sort: Option<Document>,
+        cursor: Option<&DirectedCursor>,
+    ) -> Option<Document> {
         if !matches!(cursor, Some(&DirectedCursor::Backwards(_))) {
-            return options;
+            return sort;
         }
 
-        if let Some(sort) = options.sort.as_mut() {
+        if let Some(sort) = sort.as_mut() {
             sort.iter_mut().for_each(|(_key, value)| {
                 if let &mut Bson::Int32(num) = value {
                     *value = Bson::Int32(num.neg());
@@ -48,7 +274,80 @@ impl CursorOptions {
                 }
             });
         }
-        options
+        sort
+    }
+}
+
+/// Builds [`CursorOptions`], see [`CursorOptions::builder`].
+#[derive(Clone, Debug)]
+pub struct CursorOptionsBuilder {
+    options: FindOptions,
+    cursor: Option<DirectedCursor>,
+    tiebreak: bool,
+    count_hint: Option<Hint>,
+}
+
+impl Default for CursorOptionsBuilder {
+    fn default() -> Self {
+        Self {
+            options: FindOptions::default(),
+            cursor: None,
+            tiebreak: true,
+            count_hint: None,
+        }
+    }
+}
+
+impl CursorOptionsBuilder {
+    /// Sets the find options to normalize.
+    #[must_use]
+    pub fn options<T: Into<FindOptions>>(mut self, options: T) -> Self {
+        self.options = options.into();
+        self
+    }
+
+    /// Starts the page at `cursor` instead of at the first page.
+    #[must_use]
+    pub fn cursor(mut self, cursor: Option<DirectedCursor>) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// Whether `_id` is appended to a non-empty sort, enabled by default. Disable it only for
+    /// sorts that are unique on their own.
+    #[must_use]
+    pub const fn tiebreak(mut self, tiebreak: bool) -> Self {
+        self.tiebreak = tiebreak;
+        self
+    }
+
+    /// Uses `hint` for the count query instead of the hint of the find options.
+    #[must_use]
+    pub fn count_hint(mut self, hint: Option<Hint>) -> Self {
+        self.count_hint = hint;
+        self
+    }
+
+    /// Keeps the server from timing out the cursor of a page while it is idle between batches.
+    #[must_use]
+    pub fn no_cursor_timeout(mut self, no_cursor_timeout: Option<bool>) -> Self {
+        self.options.no_cursor_timeout = no_cursor_timeout;
+        self
+    }
+
+    /// Sets how long the server waits for new items of a `TailableAwait` cursor, which is
+    /// rejected for any other cursor type.
+    #[must_use]
+    pub fn max_await_time(mut self, max_await_time: Option<Duration>) -> Self {
+        self.options.max_await_time = max_await_time;
+        self
+    }
+
+    /// Normalizes the options.
+    #[must_use]
+    pub fn build(self) -> CursorOptions {
+        CursorOptions::new_with_tiebreak(self.options, self.cursor, self.tiebreak)
+            .with_count_hint(self.count_hint)
     }
 }
 
@@ -60,26 +359,30 @@ impl Deref for CursorOptions {
     }
 }
 
-impl DerefMut for CursorOptions {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.directed_options
-    }
-}
 impl From<CursorOptions> for FindOptions {
     fn from(value: CursorOptions) -> Self {
         value.directed_options
     }
 }
 
-impl From<CursorOptions> for CountOptions {
-    fn from(value: CursorOptions) -> Self {
-        Self::builder()
+impl TryFrom<CursorOptions> for CountOptions {
+    type Error = CursorError;
+
+    fn try_from(value: CursorOptions) -> Result<Self, Self::Error> {
+        let limit = value
+            .limit
+            .map(|limit| u64::try_from(limit).map_err(|_err| CursorError::NegativeLimit(limit)))
+            .transpose()?;
+        Ok(Self::builder()
             .collation(value.collation.clone())
-            .hint(value.hint.clone())
-            .limit(value.limit.map(i64::unsigned_abs))
+            .hint(value.count_hint())
+            .limit(limit)
             .max_time(value.max_time)
             .skip(value.skip)
-            .build()
+            .comment(value.comment())
+            .read_concern(value.read_concern.clone())
+            .selection_criteria(value.selection_criteria.clone())
+            .build())
     }
 }
 
@@ -89,6 +392,76 @@ impl From<CursorOptions> for EstimatedDocumentCountOptions {
             .max_time(options.max_time)
             .selection_criteria(options.selection_criteria.clone())
             .read_concern(options.read_concern.clone())
+            .comment(options.comment())
+            .build()
+    }
+}
+
+impl From<&CursorOptions> for AggregateOptions {
+    fn from(options: &CursorOptions) -> Self {
+        Self::builder()
+            .allow_disk_use(options.allow_disk_use)
+            .batch_size(options.batch_size)
+            .collation(options.collation.clone())
+            .comment_bson(options.comment())
+            .hint(options.hint.clone())
+            .max_time(options.max_time)
+            .max_await_time(options.max_await_time)
+            .read_concern(options.read_concern.clone())
+            .selection_criteria(options.selection_criteria.clone())
+            .let_vars(options.let_vars.clone())
             .build()
     }
 }
+
+impl From<CursorOptions> for AggregateOptions {
+    fn from(options: CursorOptions) -> Self {
+        Self::from(&options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::{doc, Bson};
+    use mongodb::options::{Collation, FindOptions, ReadConcern};
+
+    use super::{sort_value, CursorOptions};
+    use crate::{DirectedCursor, Edge};
+
+    #[test]
+    fn looks_up_dotted_sort_keys() {
+        let item = doc! { "author": { "name": "a" }, "_id": 1_i32 };
+        assert_eq!(sort_value(&item, "author.name"), Some(&Bson::from("a")));
+        assert_eq!(sort_value(&item, "author.age"), None);
+        let edge = doc! { "author.name": "b" };
+        assert_eq!(sort_value(&edge, "author.name"), Some(&Bson::from("b")));
+
+        let find_options = FindOptions::builder()
+            .sort(doc! { "author.name": 1_i32 })
+            .build();
+        let options = CursorOptions::new(find_options, None);
+        assert_eq!(
+            Edge::new(&item, &options).to_document(),
+            doc! { "author.name": "a", "_id": 1_i64 }
+        );
+    }
+
+    #[test]
+    fn set_cursor_only_redirects_the_sort() {
+        let find_options = FindOptions::builder()
+            .sort(doc! { "name": 1_i32 })
+            .collation(Collation::builder().locale("en").build())
+            .read_concern(ReadConcern::majority())
+            .limit(2)
+            .build();
+        let mut options = CursorOptions::new(find_options, None);
+        let edge = Edge::new(&doc! { "name": "a", "_id": 1_i32 }, &options);
+        options.set_cursor(DirectedCursor::Backwards(edge));
+
+        let directed = options.directed_options();
+        assert_eq!(directed.sort, Some(doc! { "name": -1_i32, "_id": 1_i32 }));
+        assert!(directed.collation.is_some());
+        assert!(directed.read_concern.is_some());
+        assert_eq!(directed.limit, Some(2));
+    }
+}
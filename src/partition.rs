@@ -0,0 +1,167 @@
+use bson::{doc, Bson, Document};
+use futures::stream::BoxStream;
+use futures_util::stream::{self, StreamExt};
+use futures_util::TryStreamExt;
+use mongodb::options::{AggregateOptions, FindOptions};
+use mongodb::Collection;
+use serde::de::DeserializeOwned;
+
+use crate::config::PaginationConfig;
+use crate::error::CursorError;
+use crate::model::FindResult;
+use crate::option::{cursor_key, CursorOptions};
+
+/// The pages of one partition of a [`partition_scan`](crate::Pagination::partition_scan), in
+/// sort order.
+pub type PageStream<T> = BoxStream<'static, Result<FindResult<T>, CursorError>>;
+
+/// Splits the items matching `filter` into up to `workers` disjoint ranges of the first sort key
+/// with `$bucketAuto` and returns the pages of every range as a stream of its own. The ranges
+/// are ordered like the sort. The items whose key is `null`, missing or of another type than the
+/// bounds of the ranges are returned by one more stream, first for an ascending sort and last for
+/// a descending one. Walking the streams one after another returns every item in order only if
+/// the key has no other type besides `null`, as other types sort before or after the ranges by
+/// their BSON type, e.g. strings after numbers.
+pub(crate) async fn partition_scan<T: DeserializeOwned + Send + 'static>(
+    collection: Collection<Document>,
+    config: PaginationConfig,
+    filter: Option<Document>,
+    options: Option<FindOptions>,
+    workers: usize,
+) -> Result<Vec<PageStream<T>>, CursorError> {
+    let mut options = options.unwrap_or_default();
+    // A skip can't be split between the ranges.
+    options.skip = None;
+    let sort = options.sort.clone().unwrap_or_default();
+    let (key, direction) = match sort.keys().next() {
+        Some(key) => (
+            cursor_key(key).to_owned(),
+            crate::sort_direction(&sort, key)?,
+        ),
+        None => ("_id".to_owned(), 1_i64),
+    };
+
+    let bounds = split_points(
+        &collection,
+        &config,
+        filter.clone(),
+        &options,
+        &key,
+        workers,
+    )
+    .await?;
+    let mut ranges: Vec<Document> = Vec::new();
+    for (index, lower) in bounds.iter().enumerate() {
+        let mut range = Document::new();
+        if index > 0 {
+            range.insert("$gte", lower.clone());
+        }
+        if let Some(upper) = bounds.get(index.saturating_add(1)) {
+            range.insert("$lt", upper.clone());
+        }
+        ranges.push(if range.is_empty() {
+            range
+        } else {
+            doc! { key.as_str(): range }
+        });
+    }
+    if ranges.len() > 1 {
+        // Range queries only match values of the type of their bound, the items with a `null`,
+        // missing or differently typed key are walked in a partition of their own. `null` and
+        // missing keys sort first, so it goes first, other types may sort after the ranges.
+        ranges.insert(0, doc! { "$nor": ranges.clone() });
+    }
+    if ranges.is_empty() {
+        ranges.push(Document::new());
+    }
+    if direction < 0 {
+        ranges.reverse();
+    }
+
+    Ok(ranges
+        .into_iter()
+        .map(|range| {
+            let range_filter = if range.is_empty() {
+                filter.clone()
+            } else {
+                Some(match filter.clone() {
+                    Some(filter) => doc! { "$and": [filter, range] },
+                    None => range,
+                })
+            };
+            pages(
+                collection.clone(),
+                config.clone(),
+                range_filter,
+                options.clone(),
+            )
+        })
+        .collect())
+}
+
+/// Returns the lower bound of every range of `key`, in ascending order. The collation, read
+/// concern and hint of `options` apply to the split like to the pages.
+async fn split_points(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Option<Document>,
+    options: &FindOptions,
+    key: &str,
+    workers: usize,
+) -> Result<Vec<Bson>, CursorError> {
+    let count = i32::try_from(workers.max(1)).unwrap_or(i32::MAX);
+    let pipeline = vec![
+        doc! { "$match": config.scope(filter).unwrap_or_default() },
+        doc! { "$bucketAuto": { "groupBy": format!("${key}"), "buckets": count } },
+    ];
+    let mut aggregate_options = AggregateOptions::from(CursorOptions::new(options.clone(), None));
+    aggregate_options.batch_size = None;
+    aggregate_options.max_await_time = None;
+    let buckets: Vec<Document> = config
+        .run(collection.name(), || async {
+            Ok(collection
+                .aggregate(pipeline.clone(), aggregate_options.clone())
+                .await?
+                .try_collect()
+                .await?)
+        })
+        .await?;
+    Ok(buckets
+        .iter()
+        .filter_map(|bucket| bucket.get_document("_id").ok()?.get("min").cloned())
+        .collect())
+}
+
+/// Walks every page of the items matching `filter`.
+fn pages<T: DeserializeOwned + Send + 'static>(
+    collection: Collection<Document>,
+    config: PaginationConfig,
+    filter: Option<Document>,
+    options: FindOptions,
+) -> PageStream<T> {
+    stream::try_unfold(Some(None), move |cursor| {
+        let (collection, config, filter, options) = (
+            collection.clone(),
+            config.clone(),
+            filter.clone(),
+            options.clone(),
+        );
+        async move {
+            let Some(cursor) = cursor else {
+                return Ok(None);
+            };
+            let page: FindResult<T> = Box::pin(crate::find(
+                &collection,
+                &config,
+                filter,
+                Some(options),
+                cursor,
+                None,
+            ))
+            .await?;
+            let next = page.page_info.next_request().map(Some);
+            Ok(Some((page, next)))
+        }
+    })
+    .boxed()
+}
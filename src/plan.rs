@@ -0,0 +1,58 @@
+use bson::Document;
+use mongodb::options::FindOptions;
+
+use crate::option::CursorOptions;
+use crate::DirectedCursor;
+
+/// What a page was actually queried with, after the sort was normalized, the filter was
+/// extended by the cursor and the sort was reversed for a backwards cursor. Use it to see
+/// exactly what was sent to `MongoDB` when a page looks wrong.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct EffectiveOptions {
+    /// Filter of the find query, combining the filter passed in with the cursor bounds
+    pub filter: Document,
+    /// Normalized sort including the tiebreak, in the order of the first page
+    pub sort: Document,
+    /// Sort of the find query, reversed for a backwards cursor
+    pub directed_sort: Document,
+    /// Limit of the find query, one more than the page size when over-fetching
+    pub limit: Option<i64>,
+    /// Skip of the find query
+    pub skip: Option<u64>,
+    /// Projection of the find query, including the sort keys the cursors are built from
+    pub projection: Option<Document>,
+}
+
+impl EffectiveOptions {
+    pub(crate) fn new(filter: Document, options: &CursorOptions, overfetch: bool) -> Self {
+        Self {
+            filter,
+            sort: options.sort().cloned().unwrap_or_default(),
+            directed_sort: options.directed_options().sort.clone().unwrap_or_default(),
+            limit: options.limit.map(|limit| {
+                if overfetch {
+                    limit.saturating_add(1)
+                } else {
+                    limit
+                }
+            }),
+            skip: options.skip,
+            projection: options.projection.clone(),
+        }
+    }
+}
+
+/// The queries `find_paginated` would run for a page, see
+/// [`Pagination::plan`](crate::Pagination::plan).
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct QueryPlan {
+    /// Filter and options of the find query
+    pub effective_options: EffectiveOptions,
+    /// Every find option of the find query, including the settings of the
+    /// [`PaginationConfig`](crate::PaginationConfig) like `max_time` and the comment
+    pub find_options: FindOptions,
+    /// Cursor the page starts at
+    pub cursor: Option<DirectedCursor>,
+}
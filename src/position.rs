@@ -0,0 +1,72 @@
+use std::cmp::Ordering;
+
+use bson::Document;
+use serde::Serialize;
+
+use crate::compare::compare_keys;
+use crate::error::CursorError;
+use crate::model::{DirectedCursor, FindResult};
+
+/// Where an item belongs relative to a page, see [`FindResult::insert_position`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InsertPosition {
+    /// The item sorts before the first item of the page, onto a previous page.
+    Before,
+    /// The item belongs onto the page, at this index of its items.
+    Within(usize),
+    /// The item sorts after the last item of the page, onto a later page.
+    After,
+}
+
+impl<T: Serialize> FindResult<T> {
+    /// Finds where `item`, e.g. one that was just created, belongs relative to this page by
+    /// comparing its sort keys to the edges of the page, so a UI can place it without fetching
+    /// the page again. On the first page an item sorting before every item belongs to the page,
+    /// just as on the last page an item sorting after every item.
+    ///
+    /// The item needs every sort key including the `_id` tiebreak; missing keys sort like `null`.
+    /// Inserting into a full page pushes its last item onto the next page.
+    pub fn insert_position(&self, item: &T) -> Result<InsertPosition, CursorError> {
+        let document = bson::to_document(item)?;
+        let sort = &self.effective_options.sort;
+        let compare = |cursor: Option<&DirectedCursor>| {
+            cursor
+                .map(|cursor| compare_keys(sort, &document, cursor.inner()))
+                .transpose()
+        };
+
+        if compare(self.page_info.start_cursor.as_ref())? == Some(Ordering::Less)
+            && self.page_info.has_previous_page != Some(false)
+        {
+            return Ok(InsertPosition::Before);
+        }
+        if compare(self.page_info.end_cursor.as_ref())? == Some(Ordering::Greater)
+            && self.page_info.has_next_page != Some(false)
+        {
+            return Ok(InsertPosition::After);
+        }
+
+        let mut preceding: usize = 0;
+        for edge in &self.edges {
+            let keys: &Document = edge;
+            if compare_keys(sort, keys, &document)? == Ordering::Less {
+                preceding = preceding.saturating_add(1);
+            }
+        }
+        // The edges are in the opposite order of the sort with `reverse_results`.
+        Ok(InsertPosition::Within(if self.is_reversed() {
+            self.edges.len().saturating_sub(preceding)
+        } else {
+            preceding
+        }))
+    }
+
+    /// Whether `item` belongs onto this page, see [`FindResult::insert_position`].
+    pub fn would_contain(&self, item: &T) -> Result<bool, CursorError> {
+        Ok(matches!(
+            self.insert_position(item)?,
+            InsertPosition::Within(_)
+        ))
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use bson::Document;
+use mongodb::Collection;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+use crate::error::CursorError;
+use crate::model::FindResult;
+
+type PrefetchTask = JoinHandle<Result<FindResult<Document>, CursorError>>;
+
+#[derive(Debug)]
+struct Prefetched {
+    namespace: String,
+    started: Instant,
+    task: PrefetchTask,
+}
+
+/// Speculatively fetches the next page in a background task after every page with a next page,
+/// so sequential "next" requests are served without waiting for `MongoDB`.
+///
+/// Prefetched pages are keyed by their collection, filter, options and cursor. They are only used
+/// by a request with the exact same query and are dropped once they are older than the `ttl`.
+/// Clones share the same prefetched pages. Pages are only prefetched within a Tokio runtime.
+/// Requires the `tokio` feature.
+#[derive(Clone, Debug)]
+pub struct Prefetcher {
+    capacity: usize,
+    ttl: Duration,
+    pages: Arc<Mutex<HashMap<String, Prefetched>>>,
+}
+
+impl Default for Prefetcher {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+impl Prefetcher {
+    /// Creates a [`Prefetcher`] holding at most `capacity` pages, evicting the oldest first.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ttl: Duration::from_secs(30),
+            pages: Arc::default(),
+        }
+    }
+
+    /// Sets how long a prefetched page may be served, 30 seconds by default.
+    #[must_use]
+    pub const fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Drops every prefetched page of `collection`, e.g. after writing to it.
+    pub fn invalidate<I>(&self, collection: &Collection<I>) {
+        let namespace = collection.namespace().to_string();
+        self.lock().retain(|_key, page| {
+            let keep = page.namespace != namespace;
+            if !keep {
+                page.task.abort();
+            }
+            keep
+        });
+    }
+
+    /// Drops every prefetched page.
+    pub fn clear(&self) {
+        self.lock().retain(|_key, page| {
+            page.task.abort();
+            false
+        });
+    }
+
+    /// Returns the number of pages currently prefetched or being prefetched.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if no page is prefetched.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Removes the page prefetched for `key`, unless it expired.
+    pub(crate) fn take(&self, key: &str) -> Option<PrefetchTask> {
+        let page = self.lock().remove(key)?;
+        if page.started.elapsed() > self.ttl {
+            page.task.abort();
+            return None;
+        }
+        Some(page.task)
+    }
+
+    /// Starts prefetching the page for `key`, evicting the oldest page if the cache is full.
+    pub(crate) fn spawn<I, F>(&self, collection: &Collection<I>, key: String, page: F)
+    where
+        F: Future<Output = Result<FindResult<Document>, CursorError>> + Send + 'static,
+    {
+        // Outside of a Tokio runtime there is nothing to run the task on, the next page is
+        // fetched when it is requested instead.
+        let Ok(runtime) = Handle::try_current() else {
+            return;
+        };
+        if self.capacity == 0 {
+            return;
+        }
+        let mut pages = self.lock();
+        if !pages.contains_key(&key) && pages.len() >= self.capacity {
+            let oldest = pages
+                .iter()
+                .min_by_key(|(_name, entry)| entry.started)
+                .map(|(name, _entry)| name.clone());
+            if let Some(evicted) = oldest.and_then(|oldest| pages.remove(&oldest)) {
+                evicted.task.abort();
+            }
+        }
+        let prefetched = Prefetched {
+            namespace: collection.namespace().to_string(),
+            started: Instant::now(),
+            task: runtime.spawn(page),
+        };
+        if let Some(replaced) = pages.insert(key, prefetched) {
+            replaced.task.abort();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Prefetched>> {
+        self.pages.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
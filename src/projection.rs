@@ -0,0 +1,112 @@
+use bson::Document;
+use mongodb::options::FindOptions;
+use serde::de::value::Error;
+use serde::de::{self, Deserialize, Visitor};
+use serde::{forward_to_deserialize_any, Deserializer};
+
+use crate::error::CursorError;
+use crate::option::{is_parent, CursorOptions};
+
+/// Builds the projection of a page from the fields a client selected, e.g. the fields of a
+/// GraphQL selection set, so only those are fetched.
+///
+/// Nested fields are selected by their dotted path, e.g. `author.name`. Meta fields starting
+/// with `__` like `__typename` aren't stored and are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FindResultProjector {
+    fields: Vec<String>,
+}
+
+impl FindResultProjector {
+    /// Creates a projector of the selected `fields`.
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            fields: fields.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Returns the projection including every selected field, e.g. `{ "name": 1, "_id": 0 }`.
+    /// Fields whose parent is selected as well are left out, `MongoDB` rejects such a projection.
+    #[must_use]
+    pub fn projection(&self) -> Document {
+        let mut projection = Document::new();
+        for field in &self.fields {
+            if field.starts_with("__") || self.fields.iter().any(|parent| is_parent(parent, field))
+            {
+                continue;
+            }
+            projection.insert(field.as_str(), 1_i32);
+        }
+        if !projection.contains_key("_id") {
+            projection.insert("_id", 0_i32);
+        }
+        projection
+    }
+
+    /// Sets the projection of the selected fields on `options`. The sort keys the cursors are
+    /// built from are fetched as well and stripped from the items again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CursorError::ProjectionExcludesSortKey`] if only a child of a sort key is
+    /// selected, the sort key can't be fetched without returning fields left out.
+    pub fn project(&self, options: Option<FindOptions>) -> Result<FindOptions, CursorError> {
+        let mut options = options.unwrap_or_default();
+        options.projection = Some(self.projection());
+        if let Some(key) = CursorOptions::new(options.clone(), None).unprojected_sort_key() {
+            return Err(CursorError::ProjectionExcludesSortKey(key.to_owned()));
+        }
+        Ok(options)
+    }
+}
+
+/// Builds a projection returning only the fields `T` deserializes, e.g. `{ "name": 1, "_id": 0 }`
+/// for a struct with a single `name` field. Renamed fields are projected by their BSON name.
+///
+/// Returns `None` if the fields are not known up front, which is the case for anything but a
+/// plain struct, e.g. a struct with a `#[serde(flatten)]` field, an enum or a [`Document`].
+#[must_use]
+pub fn projection_of<'de, T: Deserialize<'de>>() -> Option<Document> {
+    let mut fields = None;
+    // Always fails, the fields are recorded before.
+    let _fails = T::deserialize(FieldNames(&mut fields));
+    let fields = fields?;
+    let mut projection = Document::new();
+    for field in fields {
+        projection.insert(*field, 1_i32);
+    }
+    if !fields.contains(&"_id") {
+        projection.insert("_id", 0_i32);
+    }
+    Some(projection)
+}
+
+/// A deserializer recording the fields of the struct it is asked to deserialize.
+struct FieldNames<'fields>(&'fields mut Option<&'static [&'static str]>);
+
+impl<'de> Deserializer<'de> for FieldNames<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(fields);
+        Err(de::Error::custom("fields recorded"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
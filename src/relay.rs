@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+use crate::cursor::Cursor;
+use crate::model::{ConnectionArgs, Count, FindResult};
+
+/// An item of a [`Connection`] together with its cursor.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ConnectionEdge<T> {
+    /// The item
+    pub node: T,
+    /// Cursor of the item, pass it as `after` or `before` to continue from it
+    pub cursor: Cursor,
+}
+
+/// The `pageInfo` of a Relay connection. Flags that are unknown, e.g. with
+/// [`HasPageStrategy::Skip`](crate::HasPageStrategy::Skip), are reported as `false`, which the
+/// specification only allows against the direction of the page.
+/// [`Pagination::find_relay_connection`](crate::Pagination::find_relay_connection) looks up the
+/// flag in the direction of the page instead and follows the rules of the specification for
+/// `first` and `last`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct RelayPageInfo {
+    /// True if there are items before the page
+    pub has_previous_page: bool,
+    /// True if there are items after the page
+    pub has_next_page: bool,
+    /// Cursor of the first item of the page
+    pub start_cursor: Option<Cursor>,
+    /// Cursor of the last item of the page
+    pub end_cursor: Option<Cursor>,
+}
+
+/// A page shaped like a connection of the
+/// [Relay specification](https://relay.dev/graphql/connections.htm), serialized in camel case.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Connection<T> {
+    /// Items of the page with their cursors
+    pub edges: Vec<ConnectionEdge<T>>,
+    /// Current page
+    pub page_info: RelayPageInfo,
+    /// Number of items matching the query
+    pub total_count: Count,
+}
+
+impl<T> Connection<T> {
+    /// Creates the connection of a page of `args` following the rules of the specification:
+    /// only the flag in the direction of the page, which has to be known, reports more items,
+    /// the other one only if the page continues from a cursor.
+    pub(crate) fn relay(mut page: FindResult<T>, args: &ConnectionArgs) -> Self {
+        let info = &mut page.page_info;
+        if args.last.is_some() || args.before.is_some() {
+            info.has_next_page = info.has_next_page.filter(|_| args.before.is_some());
+        } else {
+            info.has_previous_page = info.has_previous_page.filter(|_| args.after.is_some());
+        }
+        page.into()
+    }
+}
+
+impl<T> From<FindResult<T>> for Connection<T> {
+    fn from(page: FindResult<T>) -> Self {
+        let edges = page
+            .items
+            .into_iter()
+            .zip(page.edges)
+            .map(|(node, edge)| ConnectionEdge {
+                node,
+                cursor: edge.into(),
+            })
+            .collect();
+        Self {
+            edges,
+            page_info: RelayPageInfo {
+                has_previous_page: page.page_info.has_previous_page.unwrap_or(false),
+                has_next_page: page.page_info.has_next_page.unwrap_or(false),
+                start_cursor: page.page_info.start_cursor.map(Cursor::from),
+                end_cursor: page.page_info.end_cursor.map(Cursor::from),
+            },
+            total_count: page.total_count,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use futures_timer::Delay;
+
+use crate::error::CursorError;
+
+/// Retries the internal find and count queries when they fail with an error for which
+/// [`CursorError::is_retryable`] is `true`. Paginated reads are idempotent, so this is always safe.
+///
+/// The n-th retry waits `initial_backoff * multiplier^(n - 1)`, capped at `max_backoff`. With
+/// `jitter` a random duration between zero and that backoff is used instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Backoff before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the backoff between two attempts
+    pub max_backoff: Duration,
+    /// Factor the backoff grows by after every retry
+    pub multiplier: u32,
+    /// Randomize the backoff to avoid retrying in lockstep with other clients
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a [`RetryPolicy`] with `max_attempts` and the default backoff.
+    #[must_use]
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the backoff before the first retry and the upper bound for all later ones.
+    #[must_use]
+    pub const fn backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the factor the backoff grows by after every retry.
+    #[must_use]
+    pub const fn multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Enables or disables randomizing the backoff.
+    #[must_use]
+    pub const fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Returns how long to wait before the `retry`-th retry, starting at 1.
+    #[must_use]
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.saturating_pow(retry.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+        if !self.jitter {
+            return backoff;
+        }
+        let nanos = u64::try_from(backoff.as_nanos()).unwrap_or(u64::MAX);
+        let random = RandomState::new().build_hasher().finish();
+        Duration::from_nanos(random.checked_rem(nanos.saturating_add(1)).unwrap_or(0))
+    }
+}
+
+/// Runs `operation` until it succeeds, fails with a non retryable error or `policy` runs out
+/// of attempts. `on_retry` is called with the attempt that failed and its error before waiting.
+pub async fn retry<T, F, Fut, R>(
+    policy: Option<&RetryPolicy>,
+    mut operation: F,
+    on_retry: R,
+) -> Result<T, CursorError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, CursorError>>,
+    R: Fn(u32, &CursorError),
+{
+    let Some(policy) = policy else {
+        return operation().await;
+    };
+    let mut attempt = 1;
+    loop {
+        match operation().await {
+            Err(error) if error.is_retryable() && attempt < policy.max_attempts => {
+                on_retry(attempt, &error);
+                Delay::new(policy.delay(attempt)).await;
+                attempt = attempt.saturating_add(1);
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::future::Future;
+
+use bson::Document;
+use futures::lock::Mutex;
+use mongodb::error::{TRANSIENT_TRANSACTION_ERROR, UNKNOWN_TRANSACTION_COMMIT_RESULT};
+use mongodb::options::FindOptions;
+use mongodb::{ClientSession, Collection};
+use serde::de::DeserializeOwned;
+
+use crate::config::PaginationConfig;
+use crate::error::CursorError;
+use crate::event::PaginationEvent;
+use crate::model::FindResult;
+
+/// A session shared by the queries of a page, which run one after another.
+pub(crate) type SessionLock<'session> = Mutex<&'session mut ClientSession>;
+
+/// Number of attempts of a transaction without a [`RetryPolicy`](crate::RetryPolicy).
+const DEFAULT_ATTEMPTS: u32 = 3;
+
+/// Runs `callback` with every page inside one transaction on `session`, starting over from the
+/// first page if the transaction fails with a transient error.
+pub(crate) async fn for_each_page_in_transaction<T, F, Fut, E>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    session: &mut ClientSession,
+    filter: Option<Document>,
+    options: Option<FindOptions>,
+    mut callback: F,
+) -> Result<(), E>
+where
+    T: DeserializeOwned,
+    F: FnMut(FindResult<T>) -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+    E: From<CursorError>,
+{
+    // Single operations can't be retried inside a transaction, the whole transaction is instead.
+    let mut config = config.clone();
+    let attempts = config
+        .retry
+        .take()
+        .map_or(DEFAULT_ATTEMPTS, |policy| policy.max_attempts);
+    config.drop_page_sources();
+
+    let mut attempt: u32 = 1;
+    loop {
+        session
+            .start_transaction(None)
+            .await
+            .map_err(CursorError::from)?;
+        let walked = {
+            let lock = Mutex::new(&mut *session);
+            Box::pin(walk(
+                collection,
+                &config,
+                &lock,
+                filter.clone(),
+                options.clone(),
+                &mut callback,
+            ))
+            .await
+        };
+        let error = match walked {
+            Ok(Ok(())) => match commit(session, attempts).await {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            },
+            Ok(Err(error)) => {
+                abort(collection, &config, session).await;
+                return Err(error);
+            }
+            Err(error) => {
+                abort(collection, &config, session).await;
+                error
+            }
+        };
+        if !is_transient(&error) || attempt >= attempts {
+            return Err(error.into());
+        }
+        attempt = attempt.saturating_add(1);
+    }
+}
+
+/// Hands every page to `callback`, separating query errors from errors of the callback.
+async fn walk<T, F, Fut, E>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    session: &SessionLock<'_>,
+    filter: Option<Document>,
+    mut options: Option<FindOptions>,
+    callback: &mut F,
+) -> Result<Result<(), E>, CursorError>
+where
+    T: DeserializeOwned,
+    F: FnMut(FindResult<T>) -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+{
+    let mut cursor = None;
+    loop {
+        let page: FindResult<T> = Box::pin(crate::find(
+            collection,
+            config,
+            filter.clone(),
+            options.clone(),
+            cursor,
+            Some(session),
+        ))
+        .await?;
+        cursor = page.page_info.next_request();
+        crate::skip_once(&mut options);
+        if let Err(error) = callback(page).await {
+            return Ok(Err(error));
+        }
+        if cursor.is_none() {
+            return Ok(Ok(()));
+        }
+    }
+}
+
+/// Commits the transaction, retrying while its outcome is unknown.
+async fn commit(session: &mut ClientSession, attempts: u32) -> Result<(), CursorError> {
+    let mut attempt: u32 = 1;
+    loop {
+        match session.commit_transaction().await {
+            Err(error)
+                if error.contains_label(UNKNOWN_TRANSACTION_COMMIT_RESULT)
+                    && attempt < attempts =>
+            {
+                attempt = attempt.saturating_add(1);
+            }
+            result => return Ok(result?),
+        }
+    }
+}
+
+/// Aborts the transaction, reporting a failure as a [`PaginationEvent`] since the error that
+/// caused the abort is the one returned.
+async fn abort(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    session: &mut ClientSession,
+) {
+    if let Err(error) = session.abort_transaction().await {
+        config.emit(&PaginationEvent::AbortFailed {
+            collection: collection.name().to_owned(),
+            error: error.to_string(),
+        });
+    }
+}
+
+fn is_transient(error: &CursorError) -> bool {
+    matches!(error, CursorError::MongoDBError(error) if error.contains_label(TRANSIENT_TRANSACTION_ERROR))
+}
@@ -0,0 +1,134 @@
+use bson::Document;
+
+use crate::error::CursorError;
+
+/// Direction of a sort key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(clippy::exhaustive_enums)] // MongoDB only sorts in two directions
+pub enum SortDirection {
+    /// Smallest values first, `1` in a sort document.
+    Ascending,
+    /// Largest values first, `-1` in a sort document.
+    Descending,
+}
+
+impl SortDirection {
+    /// Returns the value of the direction in a sort document.
+    #[must_use]
+    pub const fn value(self) -> i32 {
+        match self {
+            Self::Ascending => 1_i32,
+            Self::Descending => -1_i32,
+        }
+    }
+}
+
+/// A sort document that can only hold valid directions, e.g.
+/// `Sort::asc("name").then_desc("created_at")` for `{ "name": 1, "created_at": -1 }`.
+///
+/// Converts into the `sort` of the find options, e.g. `FindOptions::builder().sort(sort)`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Sort(Document);
+
+impl Sort {
+    /// Sorts by `key` in `direction` first.
+    #[must_use]
+    pub fn by<K: Into<String>>(key: K, direction: SortDirection) -> Self {
+        Self::default().then_by(key, direction)
+    }
+
+    /// Sorts by `key` ascending first.
+    #[must_use]
+    pub fn asc<K: Into<String>>(key: K) -> Self {
+        Self::by(key, SortDirection::Ascending)
+    }
+
+    /// Sorts by `key` descending first.
+    #[must_use]
+    pub fn desc<K: Into<String>>(key: K) -> Self {
+        Self::by(key, SortDirection::Descending)
+    }
+
+    /// Sorts items with equal values of the previous keys by `key` in `direction`. Sorting by a
+    /// key a second time changes its direction but keeps its position.
+    #[must_use]
+    pub fn then_by<K: Into<String>>(mut self, key: K, direction: SortDirection) -> Self {
+        self.0.insert(key, direction.value());
+        self
+    }
+
+    /// Sorts items with equal values of the previous keys by `key` ascending.
+    #[must_use]
+    pub fn then_asc<K: Into<String>>(self, key: K) -> Self {
+        self.then_by(key, SortDirection::Ascending)
+    }
+
+    /// Sorts items with equal values of the previous keys by `key` descending.
+    #[must_use]
+    pub fn then_desc<K: Into<String>>(self, key: K) -> Self {
+        self.then_by(key, SortDirection::Descending)
+    }
+
+    /// Returns the sort document.
+    #[must_use]
+    pub const fn as_document(&self) -> &Document {
+        &self.0
+    }
+}
+
+impl TryFrom<Document> for Sort {
+    type Error = CursorError;
+
+    /// Accepts a sort document whose directions are all `1` or `-1`.
+    fn try_from(sort: Document) -> Result<Self, Self::Error> {
+        for key in sort.keys() {
+            crate::sort_direction(&sort, key)?;
+        }
+        Ok(Self(sort))
+    }
+}
+
+impl From<Sort> for Document {
+    fn from(sort: Sort) -> Self {
+        sort.0
+    }
+}
+
+impl From<Sort> for Option<Document> {
+    fn from(sort: Sort) -> Self {
+        Some(sort.0)
+    }
+}
+
+/// Builds a [`Sort`] from keys and the directions `asc` or `desc`, e.g.
+/// `sort! { name: asc, created_at: desc }`. Nested keys are given as string literals, e.g.
+/// `sort! { "author.name": asc }`. Any other direction fails to compile.
+#[macro_export]
+macro_rules! sort {
+    ($($key:tt : $direction:ident),+ $(,)?) => {
+        $crate::Sort::default()
+            $(.then_by($crate::__sort_key!($key), $crate::__sort_direction!($direction)))+
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __sort_key {
+    ($key:ident) => {
+        stringify!($key)
+    };
+    ($key:literal) => {
+        $key
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __sort_direction {
+    (asc) => {
+        $crate::SortDirection::Ascending
+    };
+    (desc) => {
+        $crate::SortDirection::Descending
+    };
+}
@@ -0,0 +1,101 @@
+//! Serializes an [`Edge`] as a readable document instead of a url-safe Base64 String, for
+//! serializers that are human-readable, e.g. JSON, TOML or YAML. Opt in per field with
+//! `#[serde(with = "mongodb_cursor_pagination::structured")]`, e.g. to review cursors stored in
+//! config files or test fixtures. Serializers that aren't human-readable, like the one the
+//! `MongoDB` driver stores documents with, keep the Base64 String.
+//!
+//! The document holds the sort keys and the annotations of the edge, like `$offset`, and reads
+//! back unverified, like an edge parsed from a string: a [`Paginator`](crate::Paginator) whose
+//! [`CursorCodec`](crate::CursorCodec) signs, scopes or adds claims to its cursors rejects it.
+//! Both forms are accepted when deserializing from a human-readable format.
+
+use std::fmt;
+
+use bson::Document;
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::model::Edge;
+
+/// Serializes `edge` as a document if `serializer` is human-readable, else as Base64 String.
+pub fn serialize<S: Serializer>(edge: &Edge, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        edge.to_document().serialize(serializer)
+    } else {
+        edge.serialize(serializer)
+    }
+}
+
+/// Deserializes an edge from a document or a Base64 String if `deserializer` is
+/// human-readable, else from a Base64 String only.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Edge, D::Error> {
+    struct Vis;
+    impl<'de> Visitor<'de> for Vis {
+        type Value = Edge;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a cursor document or a base64 string")
+        }
+
+        fn visit_str<E: de::Error>(self, str: &str) -> Result<Self::Value, E> {
+            str.parse().map_err(de::Error::custom)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            let document = Document::deserialize(de::value::MapAccessDeserializer::new(map))?;
+            Ok(Edge::from_document(document).unverified())
+        }
+    }
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(Vis)
+    } else {
+        Edge::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::{doc, Bson, Document};
+    use serde::{Deserialize, Serialize};
+
+    use crate::model::Edge;
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Stored {
+        #[serde(with = "super")]
+        cursor: Edge,
+    }
+
+    #[test]
+    fn serializes_documents_for_human_readable_formats_only() {
+        let stored = Stored {
+            cursor: Edge::from_document(doc! { "name": "a", "_id": 1_i32 }),
+        };
+        let readable = bson::to_bson(&stored).ok();
+        assert_eq!(
+            readable,
+            Some(Bson::Document(
+                doc! { "cursor": { "name": "a", "_id": 1_i64 } }
+            ))
+        );
+        let compact = bson::to_vec(&stored).ok();
+        let compact_document: Option<Document> = compact
+            .as_deref()
+            .and_then(|bytes| bson::from_slice(bytes).ok());
+        assert_eq!(
+            compact_document,
+            Some(doc! { "cursor": stored.cursor.to_string() })
+        );
+
+        let from_readable: Option<Stored> = readable.and_then(|bson| bson::from_bson(bson).ok());
+        assert_eq!(
+            from_readable.map(|decoded| decoded.cursor),
+            Some(stored.cursor.clone())
+        );
+        let from_compact: Option<Stored> = compact.and_then(|bytes| bson::from_slice(&bytes).ok());
+        assert_eq!(
+            from_compact.map(|decoded| decoded.cursor),
+            Some(stored.cursor)
+        );
+    }
+}
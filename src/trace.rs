@@ -0,0 +1,101 @@
+#[cfg(feature = "tracing")]
+use std::{future::Future, time::Instant};
+
+#[cfg(feature = "tracing")]
+use bson::{Bson, Document};
+#[cfg(feature = "tracing")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "tracing")]
+pub use tracing::field::Empty;
+#[cfg(feature = "tracing")]
+use tracing::{field, Instrument, Span};
+
+#[cfg(feature = "tracing")]
+use crate::DirectedCursor;
+
+/// Awaits `$future` inside the span `$span` when the `tracing` feature is enabled.
+/// Without the feature the span expression is never compiled.
+macro_rules! instrumented {
+    ($span:expr, $future:expr) => {{
+        #[cfg(feature = "tracing")]
+        let output = $crate::trace::timed($span, $future).await;
+        #[cfg(not(feature = "tracing"))]
+        let output = $future.await;
+        output
+    }};
+}
+pub(crate) use instrumented;
+
+/// Runs `future` inside `span` and records how long it took in the `elapsed` field.
+#[cfg(feature = "tracing")]
+pub async fn timed<F: Future>(span: Span, future: F) -> F::Output {
+    let start = Instant::now();
+    let output = future.instrument(span.clone()).await;
+    span.record("elapsed", field::debug(start.elapsed()));
+    output
+}
+
+/// Name of the direction a cursor points to, for span fields.
+#[cfg(feature = "tracing")]
+pub const fn direction(cursor: Option<&DirectedCursor>) -> &'static str {
+    match cursor {
+        None => "none",
+        Some(DirectedCursor::Forward(_)) => "forward",
+        Some(DirectedCursor::Backwards(_)) => "backwards",
+    }
+}
+
+/// Hashes the structure of `filter` (keys and value types, not values) so queries with the
+/// same shape can be grouped in traces without leaking the values. The hash is stable across
+/// processes and builds, and arrays hash as their type only, so `$in` lists of any length have
+/// the same shape.
+#[cfg(feature = "tracing")]
+pub fn filter_shape(filter: &Document) -> u64 {
+    let mut hasher = Sha256::new();
+    hash_document(filter, &mut hasher);
+    hasher
+        .finalize()
+        .iter()
+        .take(8)
+        .fold(0, |shape, byte| (shape << 8_u32) | u64::from(*byte))
+}
+
+#[cfg(feature = "tracing")]
+fn hash_document(document: &Document, hasher: &mut Sha256) {
+    for (key, value) in document {
+        // Keys never hold a NUL byte, it ends them as in BSON.
+        hasher.update(key.as_bytes());
+        hasher.update([0]);
+        #[allow(clippy::as_conversions)] // The element types are the type bytes of BSON
+        hasher.update([value.element_type() as u8]);
+        if let Bson::Document(embedded) = value {
+            hash_document(embedded, hasher);
+        }
+    }
+    // Ends the document, so the keys after an embedded document differ from the keys in it.
+    hasher.update([0]);
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use bson::doc;
+
+    use super::filter_shape;
+
+    #[test]
+    fn hashes_the_shape_of_filters() {
+        let shape = filter_shape(&doc! { "tenant": "a", "tag": { "$in": ["x"] } });
+        assert_eq!(
+            shape,
+            filter_shape(&doc! { "tenant": "b", "tag": { "$in": ["y", "z"] } })
+        );
+        assert_ne!(
+            shape,
+            filter_shape(&doc! { "tenant": 1_i32, "tag": { "$in": ["x"] } })
+        );
+        assert_ne!(
+            filter_shape(&doc! { "a": { "b": 1_i32 }, "c": 1_i32 }),
+            filter_shape(&doc! { "a": { "b": 1_i32, "c": 1_i32 } })
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use std::future::Future;
+
+use bson::{doc, Bson, Document};
+use mongodb::options::FindOptions;
+use mongodb::Collection;
+
+use crate::config::{CountMode, PaginationConfig};
+use crate::error::CursorError;
+use crate::model::FindResult;
+use crate::option::cursor_key;
+
+/// Number of items written per batch when the find options have no limit.
+const DEFAULT_BATCH_SIZE: i64 = 1000;
+
+/// Updates every item matching `filter` with `update`, one page of `_id`s at a time.
+pub(crate) async fn update_paginated(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Option<Document>,
+    options: Option<FindOptions>,
+    update: Document,
+) -> Result<u64, CursorError> {
+    let updated = write_paginated(collection, config, filter, options, |batch| {
+        let update = update.clone();
+        async move {
+            Ok(collection
+                .update_many(batch, update, None)
+                .await?
+                .modified_count)
+        }
+    })
+    .await;
+    // Even a failed walk may have written some pages.
+    config.invalidate_page_sources(collection);
+    updated
+}
+
+/// Deletes every item matching `filter`, one page of `_id`s at a time.
+pub(crate) async fn delete_paginated(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Option<Document>,
+    options: Option<FindOptions>,
+) -> Result<u64, CursorError> {
+    let deleted = write_paginated(collection, config, filter, options, |batch| async move {
+        Ok(collection.delete_many(batch, None).await?.deleted_count)
+    })
+    .await;
+    config.invalidate_page_sources(collection);
+    deleted
+}
+
+/// Walks the keyset of `filter` page by page and calls `write` with a filter selecting the
+/// items of each page. Only the `_id` and the sort keys of the items are fetched.
+async fn write_paginated<F, Fut>(
+    collection: &Collection<Document>,
+    config: &PaginationConfig,
+    filter: Option<Document>,
+    options: Option<FindOptions>,
+    mut write: F,
+) -> Result<u64, CursorError>
+where
+    F: FnMut(Document) -> Fut,
+    Fut: Future<Output = Result<u64, CursorError>>,
+{
+    let filter = config.scope(filter).unwrap_or_default();
+    let mut config = config.clone().count_mode(CountMode::Skip);
+    config.drop_page_sources();
+    config.ambient_filter = None;
+    let mut options = options.unwrap_or_default();
+    if options.limit.is_none() {
+        options.limit = Some(DEFAULT_BATCH_SIZE);
+    }
+    if options.projection.is_none() {
+        let mut projection = doc! { "_id": 1_i32 };
+        for key in options.sort.iter().flat_map(Document::keys) {
+            projection.insert(cursor_key(key), 1_i32);
+        }
+        options.projection = Some(projection);
+    }
+    let mut options = Some(options);
+
+    let mut written: u64 = 0;
+    let mut cursor = None;
+    loop {
+        let page: FindResult<Document> = Box::pin(crate::find(
+            collection,
+            &config,
+            Some(filter.clone()),
+            options.clone(),
+            cursor,
+            None,
+        ))
+        .await?;
+        let ids: Vec<Bson> = page
+            .items
+            .iter()
+            .filter_map(|item| item.get("_id").cloned())
+            .collect();
+        if !ids.is_empty() {
+            let batch = doc! { "$and": [filter.clone(), { "_id": { "$in": ids } }] };
+            written = written.saturating_add(write(batch).await?);
+        }
+        cursor = page.page_info.next_request();
+        crate::skip_once(&mut options);
+        if cursor.is_none() {
+            return Ok(written);
+        }
+    }
+}